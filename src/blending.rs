@@ -0,0 +1,672 @@
+//! A module containing the operations used to combine drawn colors with the colors already in the image.
+
+use crate::colors::{Color, ColorType};
+use std::sync::OnceLock;
+
+/// An enum that represents the Porter-Duff compositing operators.
+/// The operator decides how much of the source (the color being drawn)
+/// and of the destination (the current color of the image) is kept in the result.
+/// Alpha channels of both the source and the destination are taken into account.
+/// Images without an alpha channel behave as fully opaque destinations
+/// and the resulting color is flattened onto black if the result is not fully opaque.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum CompositeOp {
+    /// Neither the source nor the destination is shown
+    Clear,
+    /// Only the source is shown
+    Copy,
+    /// Only the destination is shown
+    Destination,
+    /// The source is placed over the destination
+    #[default]
+    SourceOver,
+    /// The destination is placed over the source
+    DestinationOver,
+    /// The part of the source inside the destination replaces the destination
+    SourceIn,
+    /// The part of the destination inside the source replaces the source
+    DestinationIn,
+    /// The part of the source outside the destination replaces the destination
+    SourceOut,
+    /// The part of the destination outside the source replaces the source
+    DestinationOut,
+    /// The part of the source inside the destination is placed over the destination
+    SourceAtop,
+    /// The part of the destination inside the source is placed over the source
+    DestinationAtop,
+    /// The parts of the source and the destination outside of each other are combined
+    Xor,
+    /// The source and the destination are added together
+    Lighter,
+}
+impl CompositeOp {
+    /// Returns the fractions of the source and the destination that are kept in the result.
+    /// # Arguments
+    /// * ```source_alpha``` - The alpha of the source.
+    /// * ```destination_alpha``` - The alpha of the destination.
+    /// # Returns
+    /// * The tuple with the fraction of the source and the fraction of the destination.
+    fn fractions(&self, source_alpha: f64, destination_alpha: f64) -> (f64, f64) {
+        match self {
+            CompositeOp::Clear => (0.0, 0.0),
+            CompositeOp::Copy => (1.0, 0.0),
+            CompositeOp::Destination => (0.0, 1.0),
+            CompositeOp::SourceOver => (1.0, 1.0 - source_alpha),
+            CompositeOp::DestinationOver => (1.0 - destination_alpha, 1.0),
+            CompositeOp::SourceIn => (destination_alpha, 0.0),
+            CompositeOp::DestinationIn => (0.0, source_alpha),
+            CompositeOp::SourceOut => (1.0 - destination_alpha, 0.0),
+            CompositeOp::DestinationOut => (0.0, 1.0 - source_alpha),
+            CompositeOp::SourceAtop => (destination_alpha, 1.0 - source_alpha),
+            CompositeOp::DestinationAtop => (1.0 - destination_alpha, source_alpha),
+            CompositeOp::Xor => (1.0 - destination_alpha, 1.0 - source_alpha),
+            CompositeOp::Lighter => (1.0, 1.0),
+        }
+    }
+
+    /// Returns whether a fully transparent source leaves the destination unchanged.
+    pub(crate) fn keeps_destination(&self) -> bool {
+        matches!(
+            self,
+            CompositeOp::Destination
+                | CompositeOp::SourceOver
+                | CompositeOp::DestinationOver
+                | CompositeOp::SourceAtop
+                | CompositeOp::DestinationOut
+                | CompositeOp::Xor
+                | CompositeOp::Lighter
+        )
+    }
+
+    /// Composites the source color with the destination color.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with premultiplied alpha.
+    /// * ```destination``` - The normalized ```[red, green, blue, alpha]``` destination color with premultiplied alpha.
+    /// # Returns
+    /// * The normalized ```[red, green, blue, alpha]``` resulting color with premultiplied alpha.
+    pub(crate) fn composite_premultiplied(&self, source: [f64; 4], destination: [f64; 4]) -> [f64; 4] {
+        let (source_fraction, destination_fraction) = self.fractions(source[3], destination[3]);
+        let mut result = [0.0; 4];
+        for (i, channel) in result.iter_mut().enumerate() {
+            // clamped for the additive operator
+            *channel = (source[i] * source_fraction + destination[i] * destination_fraction).min(1.0);
+        }
+        result
+    }
+
+    /// Composites the source color with the destination color.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with straight alpha.
+    /// * ```destination``` - The normalized ```[red, green, blue, alpha]``` destination color with straight alpha.
+    /// # Returns
+    /// * The normalized ```[red, green, blue, alpha]``` resulting color with straight alpha.
+    pub(crate) fn composite(&self, source: [f64; 4], destination: [f64; 4]) -> [f64; 4] {
+        unpremultiply(self.composite_premultiplied(premultiply(source), premultiply(destination)))
+    }
+}
+
+/// Multiplies the color channels by the alpha channel.
+/// # Arguments
+/// * ```color``` - The normalized ```[red, green, blue, alpha]``` color with straight alpha.
+/// # Returns
+/// * The normalized ```[red, green, blue, alpha]``` color with premultiplied alpha.
+pub(crate) fn premultiply(color: [f64; 4]) -> [f64; 4] {
+    [color[0] * color[3], color[1] * color[3], color[2] * color[3], color[3]]
+}
+
+/// Divides the color channels by the alpha channel.
+/// Fully transparent colors become transparent black.
+/// # Arguments
+/// * ```color``` - The normalized ```[red, green, blue, alpha]``` color with premultiplied alpha.
+/// # Returns
+/// * The normalized ```[red, green, blue, alpha]``` color with straight alpha.
+pub(crate) fn unpremultiply(color: [f64; 4]) -> [f64; 4] {
+    if color[3] <= 0.0 {
+        [0.0; 4]
+    } else {
+        [(color[0] / color[3]).min(1.0), (color[1] / color[3]).min(1.0), (color[2] / color[3]).min(1.0), color[3]]
+    }
+}
+
+/// An enum that represents the blend modes.
+/// The blend mode decides how the color of the source (the color being drawn)
+/// is mixed with the color of the destination (the current color of the image)
+/// before the result is composited with the [CompositeOp].
+/// Grayscale colors are treated as RGB colors with equal channels.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum BlendMode {
+    /// The source color is used as is
+    #[default]
+    Normal,
+    /// The colors are multiplied, the result is always darker
+    Multiply,
+    /// The inverted colors are multiplied, the result is always lighter
+    Screen,
+    /// Multiplies or screens the colors, depending on the destination color
+    Overlay,
+    /// The darker of the colors is used
+    Darken,
+    /// The lighter of the colors is used
+    Lighten,
+    /// Brightens the destination color to reflect the source color
+    ColorDodge,
+    /// Darkens the destination color to reflect the source color
+    ColorBurn,
+    /// Multiplies or screens the colors, depending on the source color
+    HardLight,
+    /// Darkens or lightens the colors, depending on the source color
+    SoftLight,
+    /// The darker of the colors is subtracted from the lighter one
+    Difference,
+    /// Similar to [BlendMode::Difference], but with lower contrast
+    Exclusion,
+    /// The hue of the source with the saturation and luminosity of the destination
+    Hue,
+    /// The saturation of the source with the hue and luminosity of the destination
+    Saturation,
+    /// The hue and saturation of the source with the luminosity of the destination
+    Color,
+    /// The luminosity of the source with the hue and saturation of the destination
+    Luminosity,
+}
+impl BlendMode {
+    /// Mixes a single channel of the source and the destination color.
+    /// Only used for separable blend modes.
+    /// # Arguments
+    /// * ```source``` - The normalized channel of the source color.
+    /// * ```destination``` - The normalized channel of the destination color.
+    /// # Returns
+    /// * The mixed channel.
+    fn mix_channel(&self, source: f64, destination: f64) -> f64 {
+        match self {
+            BlendMode::Multiply => source * destination,
+            BlendMode::Screen => source + destination - source * destination,
+            BlendMode::Overlay => BlendMode::HardLight.mix_channel(destination, source),
+            BlendMode::Darken => source.min(destination),
+            BlendMode::Lighten => source.max(destination),
+            BlendMode::ColorDodge => {
+                if destination == 0.0 {
+                    0.0
+                } else if source >= 1.0 {
+                    1.0
+                } else {
+                    (destination / (1.0 - source)).min(1.0)
+                }
+            }
+            BlendMode::ColorBurn => {
+                if destination >= 1.0 {
+                    1.0
+                } else if source == 0.0 {
+                    0.0
+                } else {
+                    1.0 - ((1.0 - destination) / source).min(1.0)
+                }
+            }
+            BlendMode::HardLight => {
+                if source <= 0.5 {
+                    BlendMode::Multiply.mix_channel(2.0 * source, destination)
+                } else {
+                    BlendMode::Screen.mix_channel(2.0 * source - 1.0, destination)
+                }
+            }
+            BlendMode::SoftLight => {
+                if source <= 0.5 {
+                    destination - (1.0 - 2.0 * source) * destination * (1.0 - destination)
+                } else {
+                    let d = if destination <= 0.25 {
+                        ((16.0 * destination - 12.0) * destination + 4.0) * destination
+                    } else {
+                        destination.sqrt()
+                    };
+                    destination + (2.0 * source - 1.0) * (d - destination)
+                }
+            }
+            BlendMode::Difference => (source - destination).abs(),
+            BlendMode::Exclusion => source + destination - 2.0 * source * destination,
+            _ => source,
+        }
+    }
+
+    /// Mixes the source and the destination color.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue]``` source color.
+    /// * ```destination``` - The normalized ```[red, green, blue]``` destination color.
+    /// # Returns
+    /// * The mixed ```[red, green, blue]``` color.
+    pub(crate) fn mix(&self, source: [f64; 3], destination: [f64; 3]) -> [f64; 3] {
+        match self {
+            BlendMode::Normal => source,
+            BlendMode::Hue => set_luminosity(set_saturation(source, saturation(destination)), luminosity(destination)),
+            BlendMode::Saturation => set_luminosity(set_saturation(destination, saturation(source)), luminosity(destination)),
+            BlendMode::Color => set_luminosity(source, luminosity(destination)),
+            BlendMode::Luminosity => set_luminosity(destination, luminosity(source)),
+            _ => [
+                self.mix_channel(source[0], destination[0]),
+                self.mix_channel(source[1], destination[1]),
+                self.mix_channel(source[2], destination[2]),
+            ],
+        }
+    }
+}
+
+/// Returns the luminosity of the color.
+fn luminosity(color: [f64; 3]) -> f64 {
+    0.3 * color[0] + 0.59 * color[1] + 0.11 * color[2]
+}
+
+/// Returns the saturation of the color.
+fn saturation(color: [f64; 3]) -> f64 {
+    color[0].max(color[1]).max(color[2]) - color[0].min(color[1]).min(color[2])
+}
+
+/// Brings the channels of the color back into range ```[0.0, 1.0]``` while keeping its luminosity.
+fn clip_color(color: [f64; 3]) -> [f64; 3] {
+    let lum = luminosity(color);
+    let min_channel = color[0].min(color[1]).min(color[2]);
+    let max_channel = color[0].max(color[1]).max(color[2]);
+    let mut result = color;
+    if min_channel < 0.0 {
+        result = result.map(|channel| lum + (channel - lum) * lum / (lum - min_channel));
+    }
+    if max_channel > 1.0 {
+        result = result.map(|channel| lum + (channel - lum) * (1.0 - lum) / (max_channel - lum));
+    }
+    result
+}
+
+/// Returns the color with its luminosity replaced.
+fn set_luminosity(color: [f64; 3], lum: f64) -> [f64; 3] {
+    let difference = lum - luminosity(color);
+    clip_color(color.map(|channel| channel + difference))
+}
+
+/// Returns the color with its saturation replaced.
+fn set_saturation(color: [f64; 3], sat: f64) -> [f64; 3] {
+    let min_channel = color[0].min(color[1]).min(color[2]);
+    let max_channel = color[0].max(color[1]).max(color[2]);
+    if max_channel > min_channel {
+        color.map(|channel| (channel - min_channel) * sat / (max_channel - min_channel))
+    } else {
+        [0.0; 3]
+    }
+}
+
+/// An enum that represents the space in which the colors are blended.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum BlendSpace {
+    /// The colors are blended directly on their sRGB-encoded values
+    #[default]
+    Srgb,
+    /// The colors are decoded from sRGB to linear light, blended, and encoded back.
+    /// Anti-aliased edges keep their perceived thickness and gradients stay bright,
+    /// at the cost of slower blending.
+    Linear,
+}
+impl BlendSpace {
+    /// Returns the normalized ```[red, green, blue, alpha]``` channels of the color in this space.
+    /// # Arguments
+    /// * ```color``` - The color with straight alpha.
+    /// # Returns
+    /// * The normalized channels of the color.
+    pub(crate) fn decode(&self, color: Color) -> [f64; 4] {
+        match self {
+            BlendSpace::Srgb => color.to_rgba_f64(),
+            BlendSpace::Linear => color.to_linear_rgba_f64(),
+        }
+    }
+
+    /// Creates a color of the given type from the normalized ```[red, green, blue, alpha]``` channels in this space.
+    /// # Arguments
+    /// * ```color_type``` - The color type of the new color.
+    /// * ```rgba``` - The normalized channels of the color with straight alpha.
+    /// # Returns
+    /// * The new color.
+    pub(crate) fn encode(&self, color_type: ColorType, rgba: [f64; 4]) -> Color {
+        match self {
+            BlendSpace::Srgb => Color::from_rgba_f64(color_type, rgba),
+            BlendSpace::Linear => Color::from_linear_rgba_f64(color_type, rgba),
+        }
+    }
+}
+
+/// Converts a normalized sRGB-encoded value to linear light.
+pub(crate) fn srgb_to_linear(value: f64) -> f64 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a normalized linear light value to the sRGB encoding.
+pub(crate) fn linear_to_srgb(value: f64) -> f64 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Returns the lookup table of linear light values for all u8 sRGB-encoded values.
+fn srgb_u8_table() -> &'static [f64; 256] {
+    static TABLE: OnceLock<[f64; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0.0; 256];
+        for (i, value) in table.iter_mut().enumerate() {
+            *value = srgb_to_linear(i as f64 / 255.0);
+        }
+        table
+    })
+}
+
+/// Returns the lookup table of linear light values halfway between consecutive u8 sRGB-encoded values.
+fn srgb_u8_thresholds() -> &'static [f64; 255] {
+    static TABLE: OnceLock<[f64; 255]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0.0; 255];
+        for (i, value) in table.iter_mut().enumerate() {
+            *value = srgb_to_linear((i as f64 + 0.5) / 255.0);
+        }
+        table
+    })
+}
+
+/// Converts a u8 sRGB-encoded value to a normalized linear light value using a lookup table.
+#[inline]
+pub(crate) fn srgb_u8_to_linear(value: u8) -> f64 {
+    srgb_u8_table()[value as usize]
+}
+
+/// Converts a normalized linear light value to the closest u8 sRGB-encoded value using a lookup table.
+#[inline]
+pub(crate) fn linear_to_srgb_u8(value: f64) -> u8 {
+    // number of thresholds below the value is the encoded value
+    srgb_u8_thresholds().partition_point(|&threshold| threshold <= value) as u8
+}
+
+/// A struct that describes how a drawn color is combined with the image.
+/// The source color is first mixed with the destination using the [BlendMode],
+/// and the result is then composited onto the destination using the [CompositeOp].
+/// Both steps are performed in the [BlendSpace].
+/// It can be created from a [BlendMode], a [CompositeOp] or a [BlendSpace] alone, the other parts take their default values.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct Blend {
+    /// The blend mode
+    pub mode: BlendMode,
+    /// The compositing operator
+    pub composite_op: CompositeOp,
+    /// The space in which the colors are blended
+    pub space: BlendSpace,
+}
+impl Blend {
+    /// Creates a new blend from the blend mode and the compositing operator.
+    /// The colors are blended in [BlendSpace::Srgb].
+    /// # Arguments
+    /// * ```mode``` - The blend mode.
+    /// * ```composite_op``` - The compositing operator.
+    /// # Returns
+    /// * The new blend.
+    pub const fn new(mode: BlendMode, composite_op: CompositeOp) -> Self {
+        Self { mode, composite_op, space: BlendSpace::Srgb }
+    }
+
+    /// Returns the blend with the colors blended in the given space.
+    /// # Arguments
+    /// * ```space``` - The space in which the colors are blended.
+    /// # Returns
+    /// * The new blend.
+    pub const fn in_space(self, space: BlendSpace) -> Self {
+        Self { space, ..self }
+    }
+
+    /// Returns whether the blend is plain source-over compositing.
+    pub(crate) fn is_source_over(&self) -> bool {
+        self.mode == BlendMode::Normal && self.composite_op == CompositeOp::SourceOver
+    }
+
+    /// Blends the color with the color of the pixel of the given type.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with straight alpha in the space of the blend.
+    /// * ```destination``` - The destination color as stored in the image.
+    /// * ```premultiplied``` - Whether the destination is stored with premultiplied alpha.
+    /// # Returns
+    /// * The resulting color to be stored in the image.
+    pub(crate) fn blend_color(&self, source: [f64; 4], destination: Color, premultiplied: bool) -> Color {
+        let color_type = ColorType::from(destination);
+        let premultiplied = premultiplied && color_type.has_alpha();
+        if premultiplied && self.space == BlendSpace::Srgb {
+            return Color::from_rgba_f64(color_type, self.blend_premultiplied(source, destination.to_rgba_f64()));
+        }
+
+        let destination = if premultiplied { destination.unpremultiplied() } else { destination };
+        let mut result = self.blend(source, self.space.decode(destination));
+        if !color_type.has_alpha() {
+            // flatten the result onto black, as the image can't store transparency
+            let alpha = result[3];
+            for channel in &mut result[..3] {
+                *channel *= alpha;
+            }
+        }
+        let result = self.space.encode(color_type, result);
+        if premultiplied { result.premultiplied() } else { result }
+    }
+
+    /// Blends the source color with the destination color.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with straight alpha.
+    /// * ```destination``` - The normalized ```[red, green, blue, alpha]``` destination color with straight alpha.
+    /// # Returns
+    /// * The normalized ```[red, green, blue, alpha]``` resulting color with straight alpha.
+    pub(crate) fn blend(&self, source: [f64; 4], destination: [f64; 4]) -> [f64; 4] {
+        let mut source = source;
+        if self.mode != BlendMode::Normal {
+            // the mixed color is only used where the destination is opaque
+            let mixed = self.mode.mix([source[0], source[1], source[2]], [destination[0], destination[1], destination[2]]);
+            for (channel, mixed_channel) in source.iter_mut().zip(mixed) {
+                *channel = (1.0 - destination[3]) * *channel + destination[3] * mixed_channel.clamp(0.0, 1.0);
+            }
+        }
+        self.composite_op.composite(source, destination)
+    }
+
+    /// Blends the source color with the destination color stored with premultiplied alpha.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with straight alpha.
+    /// * ```destination``` - The normalized ```[red, green, blue, alpha]``` destination color with premultiplied alpha.
+    /// # Returns
+    /// * The normalized ```[red, green, blue, alpha]``` resulting color with premultiplied alpha.
+    pub(crate) fn blend_premultiplied(&self, source: [f64; 4], destination: [f64; 4]) -> [f64; 4] {
+        if self.mode == BlendMode::Normal {
+            self.composite_op.composite_premultiplied(premultiply(source), destination)
+        } else {
+            premultiply(self.blend(source, unpremultiply(destination)))
+        }
+    }
+}
+impl From<BlendMode> for Blend {
+    fn from(mode: BlendMode) -> Self {
+        Self { mode, ..Default::default() }
+    }
+}
+impl From<CompositeOp> for Blend {
+    fn from(composite_op: CompositeOp) -> Self {
+        Self { composite_op, ..Default::default() }
+    }
+}
+impl From<BlendSpace> for Blend {
+    fn from(space: BlendSpace) -> Self {
+        Self { space, ..Default::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: [f64; 4] = [1.0, 0.0, 0.0, 0.5];
+    const DESTINATION: [f64; 4] = [0.0, 0.0, 1.0, 0.5];
+
+    fn assert_close(actual: [f64; 4], expected: [f64; 4]) {
+        for i in 0..4 {
+            assert!((actual[i] - expected[i]).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_default() {
+        assert_eq!(CompositeOp::default(), CompositeOp::SourceOver);
+    }
+
+    #[test]
+    fn test_opaque_destination() {
+        let opaque = [0.0, 0.0, 1.0, 1.0];
+        assert_close(CompositeOp::SourceOver.composite(SOURCE, opaque), [0.5, 0.0, 0.5, 1.0]);
+        assert_close(CompositeOp::DestinationOver.composite(SOURCE, opaque), opaque);
+        assert_close(CompositeOp::SourceAtop.composite(SOURCE, opaque), [0.5, 0.0, 0.5, 1.0]);
+        assert_close(CompositeOp::SourceIn.composite(SOURCE, opaque), SOURCE);
+        assert_close(CompositeOp::SourceOut.composite(SOURCE, opaque), [0.0; 4]);
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_close(CompositeOp::Clear.composite(SOURCE, DESTINATION), [0.0; 4]);
+        assert_close(CompositeOp::Copy.composite(SOURCE, DESTINATION), SOURCE);
+        assert_close(CompositeOp::Destination.composite(SOURCE, DESTINATION), DESTINATION);
+        assert_close(CompositeOp::SourceOver.composite(SOURCE, DESTINATION), [2.0 / 3.0, 0.0, 1.0 / 3.0, 0.75]);
+        assert_close(CompositeOp::DestinationOver.composite(SOURCE, DESTINATION), [1.0 / 3.0, 0.0, 2.0 / 3.0, 0.75]);
+        assert_close(CompositeOp::SourceIn.composite(SOURCE, DESTINATION), [1.0, 0.0, 0.0, 0.25]);
+        assert_close(CompositeOp::DestinationIn.composite(SOURCE, DESTINATION), [0.0, 0.0, 1.0, 0.25]);
+        assert_close(CompositeOp::SourceOut.composite(SOURCE, DESTINATION), [1.0, 0.0, 0.0, 0.25]);
+        assert_close(CompositeOp::DestinationOut.composite(SOURCE, DESTINATION), [0.0, 0.0, 1.0, 0.25]);
+        assert_close(CompositeOp::SourceAtop.composite(SOURCE, DESTINATION), [0.5, 0.0, 0.5, 0.5]);
+        assert_close(CompositeOp::DestinationAtop.composite(SOURCE, DESTINATION), [0.5, 0.0, 0.5, 0.5]);
+        assert_close(CompositeOp::Xor.composite(SOURCE, DESTINATION), [0.5, 0.0, 0.5, 0.5]);
+        assert_close(CompositeOp::Lighter.composite(SOURCE, DESTINATION), [0.5, 0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_keeps_destination() {
+        let transparent = [0.3, 0.6, 0.9, 0.0];
+        for op in [
+            CompositeOp::Clear,
+            CompositeOp::Copy,
+            CompositeOp::Destination,
+            CompositeOp::SourceOver,
+            CompositeOp::DestinationOver,
+            CompositeOp::SourceIn,
+            CompositeOp::DestinationIn,
+            CompositeOp::SourceOut,
+            CompositeOp::DestinationOut,
+            CompositeOp::SourceAtop,
+            CompositeOp::DestinationAtop,
+            CompositeOp::Xor,
+            CompositeOp::Lighter,
+        ] {
+            assert_eq!(op.composite(transparent, DESTINATION) == DESTINATION, op.keeps_destination(), "{:?}", op);
+        }
+    }
+
+    #[test]
+    fn test_transparent_colors() {
+        let transparent = [0.3, 0.6, 0.9, 0.0];
+        assert_close(CompositeOp::SourceOver.composite(transparent, DESTINATION), DESTINATION);
+        assert_close(CompositeOp::SourceOver.composite(SOURCE, transparent), SOURCE);
+        assert_close(CompositeOp::SourceOver.composite(transparent, transparent), [0.0; 4]);
+    }
+
+    fn assert_close_rgb(actual: [f64; 3], expected: [f64; 3]) {
+        for i in 0..3 {
+            assert!((actual[i] - expected[i]).abs() < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_separable_modes() {
+        let source = [0.25, 0.5, 1.0];
+        let destination = [0.5, 0.5, 0.0];
+        assert_close_rgb(BlendMode::Normal.mix(source, destination), source);
+        assert_close_rgb(BlendMode::Multiply.mix(source, destination), [0.125, 0.25, 0.0]);
+        assert_close_rgb(BlendMode::Screen.mix(source, destination), [0.625, 0.75, 1.0]);
+        assert_close_rgb(BlendMode::Overlay.mix(source, destination), [0.25, 0.5, 0.0]);
+        assert_close_rgb(BlendMode::Darken.mix(source, destination), [0.25, 0.5, 0.0]);
+        assert_close_rgb(BlendMode::Lighten.mix(source, destination), [0.5, 0.5, 1.0]);
+        assert_close_rgb(BlendMode::ColorDodge.mix(source, destination), [2.0 / 3.0, 1.0, 0.0]);
+        assert_close_rgb(BlendMode::ColorBurn.mix(source, destination), [0.0, 0.0, 0.0]);
+        assert_close_rgb(BlendMode::HardLight.mix(source, destination), [0.25, 0.5, 1.0]);
+        assert_close_rgb(BlendMode::SoftLight.mix(source, destination), [0.375, 0.5, 0.0]);
+        assert_close_rgb(BlendMode::Difference.mix(source, destination), [0.25, 0.0, 1.0]);
+        assert_close_rgb(BlendMode::Exclusion.mix(source, destination), [0.5, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_non_separable_modes() {
+        let red = [1.0, 0.0, 0.0];
+        let gray = [0.5, 0.5, 0.5];
+        assert_close_rgb(BlendMode::Hue.mix(red, gray), gray);
+        assert_close_rgb(BlendMode::Saturation.mix(red, gray), gray);
+        assert_close_rgb(BlendMode::Luminosity.mix(gray, red), [1.0, 0.5 - 0.15 / 0.7, 0.5 - 0.15 / 0.7]);
+        assert_close_rgb(BlendMode::Color.mix(red, gray), [1.0, 0.5 - 0.15 / 0.7, 0.5 - 0.15 / 0.7]);
+        let color = BlendMode::Color.mix([0.2, 0.4, 0.9], [0.1, 0.8, 0.3]);
+        assert!((luminosity(color) - luminosity([0.1, 0.8, 0.3])).abs() < 1e-9);
+        assert!(color.iter().all(|channel| (0.0..=1.0).contains(channel)));
+    }
+
+    #[test]
+    fn test_blend() {
+        assert_eq!(Blend::default(), Blend::new(BlendMode::Normal, CompositeOp::SourceOver));
+        assert_eq!(Blend::from(BlendMode::Screen), Blend::new(BlendMode::Screen, CompositeOp::SourceOver));
+        assert_eq!(Blend::from(CompositeOp::Xor), Blend::new(BlendMode::Normal, CompositeOp::Xor));
+
+        // normal blending is plain compositing
+        assert_close(Blend::default().blend(SOURCE, DESTINATION), CompositeOp::SourceOver.composite(SOURCE, DESTINATION));
+
+        // opaque colors
+        let multiply = Blend::from(BlendMode::Multiply);
+        assert_close(multiply.blend([0.5, 0.5, 0.5, 1.0], [0.5, 1.0, 0.0, 1.0]), [0.25, 0.5, 0.0, 1.0]);
+
+        // transparent destination keeps the source color
+        assert_close(multiply.blend([0.5, 0.5, 0.5, 1.0], [0.5, 1.0, 0.0, 0.0]), [0.5, 0.5, 0.5, 1.0]);
+
+        // half transparent source
+        assert_close(multiply.blend([0.5, 0.5, 0.5, 0.5], [0.5, 1.0, 0.0, 1.0]), [0.375, 0.75, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_premultiplied() {
+        assert_close(premultiply([1.0, 0.5, 0.0, 0.5]), [0.5, 0.25, 0.0, 0.5]);
+        assert_close(unpremultiply([0.5, 0.25, 0.0, 0.5]), [1.0, 0.5, 0.0, 0.5]);
+        assert_close(unpremultiply([0.5, 0.25, 0.0, 0.0]), [0.0; 4]);
+
+        for blend in [Blend::default(), Blend::from(CompositeOp::Xor), Blend::from(BlendMode::Multiply)] {
+            assert_close(blend.blend_premultiplied(SOURCE, premultiply(DESTINATION)), premultiply(blend.blend(SOURCE, DESTINATION)));
+        }
+    }
+
+    #[test]
+    fn test_srgb_lookup_tables() {
+        for value in 0..=255_u8 {
+            let linear = srgb_u8_to_linear(value);
+            assert!((linear - srgb_to_linear(value as f64 / 255.0)).abs() < 1e-12);
+            assert_eq!(linear_to_srgb_u8(linear), value);
+            assert_eq!(linear_to_srgb_u8(linear), (linear_to_srgb(linear) * 255.0).round() as u8);
+        }
+        assert_eq!(linear_to_srgb_u8(-1.0), 0);
+        assert_eq!(linear_to_srgb_u8(2.0), 255);
+        assert_eq!(linear_to_srgb_u8(0.5), 188);
+        assert!((srgb_u8_to_linear(128) - 0.215_860_500_113_899_3).abs() < 1e-12);
+        assert!((linear_to_srgb(srgb_to_linear(0.3)) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_blend_space() {
+        assert_eq!(Blend::from(BlendSpace::Linear), Blend::default().in_space(BlendSpace::Linear));
+        assert_eq!(Blend::new(BlendMode::Screen, CompositeOp::Xor).space, BlendSpace::Srgb);
+
+        let black = Color::RGB8([0, 0, 0]);
+        let white = [1.0, 1.0, 1.0, 0.5];
+        assert_eq!(Blend::default().blend_color(white, black, false), Color::RGB8([128, 128, 128]));
+        assert_eq!(Blend::from(BlendSpace::Linear).blend_color(white, black, false), Color::RGB8([188, 188, 188]));
+        assert_eq!(Blend::from(BlendSpace::Linear).blend_color(white, Color::GRAY16(0), false), Color::GRAY16(48_192));
+        assert_eq!(Blend::from(BlendSpace::Linear).blend_color(white, Color::RGBA8([0, 0, 0, 255]).premultiplied(), true), Color::RGBA8([188, 188, 188, 255]));
+    }
+}
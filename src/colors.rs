@@ -1,6 +1,8 @@
 //! A module about the colors
 
+use crate::blending::{linear_to_srgb, linear_to_srgb_u8, premultiply, srgb_to_linear, srgb_u8_to_linear, unpremultiply};
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
 use std::slice;
 
 #[cfg(feature = "image")]
@@ -25,9 +27,40 @@ pub enum ColorType {
     RGB16,
     /// An image with 16-bit RGB pixels + 16-bit alpha channel
     RGBA16,
+    /// An image with 32-bit floating point grayscale pixels
+    GRAY32F,
+    /// An image with 32-bit floating point grayscale pixels + 32-bit floating point alpha channel
+    GRAYA32F,
+    /// An image with 32-bit floating point RGB pixels
+    RGB32F,
+    /// An image with 32-bit floating point RGB pixels + 32-bit floating point alpha channel
+    RGBA32F,
+    /// An image with 8-bit RGB pixels stored in the blue, green, red order
+    BGR8,
+    /// An image with 8-bit RGB pixels + 8-bit alpha channel stored in the blue, green, red, alpha order
+    BGRA8,
+    /// An image with 8-bit RGB pixels + 8-bit alpha channel stored in the alpha, red, green, blue order
+    ARGB8,
+    /// An image with 16-bit RGB pixels stored in the blue, green, red order
+    BGR16,
+    /// An image with 16-bit RGB pixels + 16-bit alpha channel stored in the blue, green, red, alpha order
+    BGRA16,
+    /// An image with 16-bit RGB pixels + 16-bit alpha channel stored in the alpha, red, green, blue order
+    ARGB16,
+    /// An image with 1-bit monochrome pixels, packed 8 pixels per byte starting with the most significant bit
+    GRAY1,
+    /// An image with 4-bit grayscale pixels, packed 2 pixels per byte starting with the most significant bits
+    GRAY4,
+    /// An image with 16-bit RGB pixels with 5 bits of red, 6 bits of green and 5 bits of blue
+    RGB565,
+    /// An image with 8-bit RGB pixels with 3 bits of red, 3 bits of green and 2 bits of blue
+    RGB332,
+    /// An image with 8-bit indices into the [Palette](crate::Palette) of the image
+    INDEXED8,
 }
 impl ColorType {
-    /// Return the number of bytes per pixel
+    /// Return the number of bytes per pixel.
+    /// Colors of the types with less than 8 bits per pixel take one byte, while images pack them (see [ColorType::bits_per_pixel()]).
     #[inline]
     pub const fn bytes_per_pixel(&self) -> usize {
         match self {
@@ -39,6 +72,68 @@ impl ColorType {
             ColorType::RGBA8 => 4,
             ColorType::RGB16 => 6,
             ColorType::RGBA16 => 8,
+            ColorType::GRAY32F => 4,
+            ColorType::GRAYA32F => 8,
+            ColorType::RGB32F => 12,
+            ColorType::RGBA32F => 16,
+            ColorType::BGR8 => 3,
+            ColorType::BGRA8 | ColorType::ARGB8 => 4,
+            ColorType::BGR16 => 6,
+            ColorType::BGRA16 | ColorType::ARGB16 => 8,
+            ColorType::GRAY1 | ColorType::GRAY4 => 1,
+            ColorType::RGB565 => 2,
+            ColorType::RGB332 => 1,
+            ColorType::INDEXED8 => 1,
+        }
+    }
+
+    /// Return the number of bits per pixel in the pixel data of images
+    #[inline]
+    pub const fn bits_per_pixel(&self) -> usize {
+        match self {
+            ColorType::GRAY1 => 1,
+            ColorType::GRAY4 => 4,
+            _ => self.bytes_per_pixel() * 8,
+        }
+    }
+
+    /// Return the number of bytes of a row of pixels of the given width in the pixel data of images.
+    /// Rows of the types with less than 8 bits per pixel are padded to whole bytes.
+    #[inline]
+    pub const fn row_len(&self, width: usize) -> usize {
+        (width * self.bits_per_pixel()).div_ceil(8)
+    }
+
+    /// Return whether the color type has an alpha channel
+    #[inline]
+    pub const fn has_alpha(&self) -> bool {
+        matches!(self, ColorType::GRAYA8 | ColorType::GRAYA16 | ColorType::RGBA8 | ColorType::RGBA16 | ColorType::GRAYA32F | ColorType::RGBA32F)
+            || matches!(self, ColorType::BGRA8 | ColorType::ARGB8 | ColorType::BGRA16 | ColorType::ARGB16)
+    }
+
+    /// Return whether the color type has floating point channels
+    #[inline]
+    pub const fn is_float(&self) -> bool {
+        matches!(self, ColorType::GRAY32F | ColorType::GRAYA32F | ColorType::RGB32F | ColorType::RGBA32F)
+    }
+
+    /// Return the color type with the same channels and an alpha channel
+    #[inline]
+    pub const fn with_alpha(&self) -> ColorType {
+        match self {
+            ColorType::GRAY8 | ColorType::GRAYA8 => ColorType::GRAYA8,
+            ColorType::GRAY16 | ColorType::GRAYA16 => ColorType::GRAYA16,
+            ColorType::RGB8 | ColorType::RGBA8 => ColorType::RGBA8,
+            ColorType::RGB16 | ColorType::RGBA16 => ColorType::RGBA16,
+            ColorType::GRAY32F | ColorType::GRAYA32F => ColorType::GRAYA32F,
+            ColorType::RGB32F | ColorType::RGBA32F => ColorType::RGBA32F,
+            ColorType::BGR8 | ColorType::BGRA8 => ColorType::BGRA8,
+            ColorType::ARGB8 => ColorType::ARGB8,
+            ColorType::BGR16 | ColorType::BGRA16 => ColorType::BGRA16,
+            ColorType::ARGB16 => ColorType::ARGB16,
+            ColorType::GRAY1 | ColorType::GRAY4 => ColorType::GRAYA8,
+            ColorType::RGB565 | ColorType::RGB332 => ColorType::RGBA8,
+            ColorType::INDEXED8 => ColorType::RGBA8,
         }
     }
 }
@@ -53,6 +148,21 @@ impl Display for ColorType {
             ColorType::RGBA8 => write!(f, "RGBA8"),
             ColorType::RGB16 => write!(f, "RGB16"),
             ColorType::RGBA16 => write!(f, "RGBA16"),
+            ColorType::GRAY32F => write!(f, "GRAY32F"),
+            ColorType::GRAYA32F => write!(f, "GRAYA32F"),
+            ColorType::RGB32F => write!(f, "RGB32F"),
+            ColorType::RGBA32F => write!(f, "RGBA32F"),
+            ColorType::BGR8 => write!(f, "BGR8"),
+            ColorType::BGRA8 => write!(f, "BGRA8"),
+            ColorType::ARGB8 => write!(f, "ARGB8"),
+            ColorType::BGR16 => write!(f, "BGR16"),
+            ColorType::BGRA16 => write!(f, "BGRA16"),
+            ColorType::ARGB16 => write!(f, "ARGB16"),
+            ColorType::GRAY1 => write!(f, "GRAY1"),
+            ColorType::GRAY4 => write!(f, "GRAY4"),
+            ColorType::RGB565 => write!(f, "RGB565"),
+            ColorType::RGB332 => write!(f, "RGB332"),
+            ColorType::INDEXED8 => write!(f, "INDEXED8"),
         }
     }
 }
@@ -67,6 +177,21 @@ impl From<Color> for ColorType {
             Color::RGBA8(_) => ColorType::RGBA8,
             Color::RGB16(_) => ColorType::RGB16,
             Color::RGBA16(_) => ColorType::RGBA16,
+            Color::GRAY32F(_) => ColorType::GRAY32F,
+            Color::GRAYA32F(_) => ColorType::GRAYA32F,
+            Color::RGB32F(_) => ColorType::RGB32F,
+            Color::RGBA32F(_) => ColorType::RGBA32F,
+            Color::BGR8(_) => ColorType::BGR8,
+            Color::BGRA8(_) => ColorType::BGRA8,
+            Color::ARGB8(_) => ColorType::ARGB8,
+            Color::BGR16(_) => ColorType::BGR16,
+            Color::BGRA16(_) => ColorType::BGRA16,
+            Color::ARGB16(_) => ColorType::ARGB16,
+            Color::GRAY1(_) => ColorType::GRAY1,
+            Color::GRAY4(_) => ColorType::GRAY4,
+            Color::RGB565(_) => ColorType::RGB565,
+            Color::RGB332(_) => ColorType::RGB332,
+            Color::INDEXED8(_) => ColorType::INDEXED8,
         }
     }
 }
@@ -82,6 +207,8 @@ impl From<ImageColorType> for ColorType {
             ImageColorType::Rgba8 => ColorType::RGBA8,
             ImageColorType::Rgb16 => ColorType::RGB16,
             ImageColorType::Rgba16 => ColorType::RGBA16,
+            ImageColorType::Rgb32F => ColorType::RGB32F,
+            ImageColorType::Rgba32F => ColorType::RGBA32F,
             _ => panic!("Unsupported color type"),
         }
     }
@@ -98,12 +225,110 @@ impl From<ColorType> for ImageColorType {
             ColorType::RGBA8 => ImageColorType::Rgba8,
             ColorType::RGB16 => ImageColorType::Rgb16,
             ColorType::RGBA16 => ImageColorType::Rgba16,
+            ColorType::RGB32F => ImageColorType::Rgb32F,
+            ColorType::RGBA32F => ImageColorType::Rgba32F,
+            ColorType::GRAY32F | ColorType::GRAYA32F => panic!("Unsupported color type"),
+            ColorType::BGR8 | ColorType::BGRA8 | ColorType::ARGB8 | ColorType::BGR16 | ColorType::BGRA16 | ColorType::ARGB16 => panic!("Unsupported color type"),
+            ColorType::GRAY1 | ColorType::GRAY4 | ColorType::RGB565 | ColorType::RGB332 => panic!("Unsupported color type"),
+            ColorType::INDEXED8 => panic!("Unsupported color type"),
         }
     }
 }
 
-/// An enum that holds the color information
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+/// An enum that holds the information about how the alpha channel is stored
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum AlphaMode {
+    /// The color channels are stored independently of the alpha channel
+    #[default]
+    Straight,
+    /// The color channels are stored multiplied by the alpha channel
+    Premultiplied,
+}
+impl Display for AlphaMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AlphaMode::Straight => write!(f, "Straight"),
+            AlphaMode::Premultiplied => write!(f, "Premultiplied"),
+        }
+    }
+}
+
+/// Converts a u8 color value to a normalized f64 value.
+#[inline]
+fn val_u8_to_f64(value: u8) -> f64 {
+    value as f64 / 255.0
+}
+
+/// Converts a u16 color value to a normalized f64 value.
+#[inline]
+fn val_u16_to_f64(value: u16) -> f64 {
+    value as f64 / 65535.0
+}
+
+/// Converts a normalized f64 value to a u8 color value.
+#[inline]
+fn val_f64_to_u8(value: f64) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Converts a normalized f64 value to a u16 color value.
+#[inline]
+fn val_f64_to_u16(value: f64) -> u16 {
+    (value.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+/// Converts a color value with the given number of bits to a normalized f64 value.
+#[inline]
+fn val_bits_to_f64(value: u16, bits: u32) -> f64 {
+    value as f64 / ((1 << bits) - 1) as f64
+}
+
+/// Converts a normalized f64 value to a color value with the given number of bits.
+#[inline]
+fn val_f64_to_bits(value: f64, bits: u32) -> u16 {
+    (value.clamp(0.0, 1.0) * ((1 << bits) - 1) as f64).round() as u16
+}
+
+/// Converts a normalized f64 value to a f32 color value.
+/// Values above 1.0 are kept, as floating point colors can hold high dynamic range.
+#[inline]
+fn val_f64_to_f32(value: f64) -> f32 {
+    value.max(0.0) as f32
+}
+
+/// Converts a normalized f64 value to a f32 alpha value.
+#[inline]
+fn alpha_f64_to_f32(value: f64) -> f32 {
+    value.clamp(0.0, 1.0) as f32
+}
+
+/// Reads the f32 value from the native endian bytes.
+/// # Arguments
+/// * ```bytes``` - The bytes of the color.
+/// * ```index``` - The index of the channel.
+/// # Returns
+/// * The value of the channel.
+const fn f32_from_bytes(bytes: &[u8], index: usize) -> f32 {
+    let i = index * 4;
+    f32::from_ne_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+}
+
+/// Reads the u16 value from the native endian bytes.
+/// # Arguments
+/// * ```bytes``` - The bytes of the color.
+/// * ```index``` - The index of the channel.
+/// # Returns
+/// * The value of the channel.
+const fn u16_from_bytes(bytes: &[u8], index: usize) -> u16 {
+    let i = index * 2;
+    u16::from_ne_bytes([bytes[i], bytes[i + 1]])
+}
+
+/// An enum that holds the color information.
+/// Floating point channels are normalized as the integer ones (1.0 is the full intensity),
+/// but the color channels may exceed 1.0 to hold high dynamic range.
+/// Colors are compared by their bits, so floating point colors with NaN channels are equal to themselves.
+#[derive(Debug, Copy, Clone)]
 pub enum Color {
     /// The 8-bit grayscale color
     GRAY8(u8),
@@ -121,6 +346,50 @@ pub enum Color {
     RGB16([u16; 3]),
     /// The 16-bit RGB color + 16-bit alpha channel
     RGBA16([u16; 4]),
+    /// The 32-bit floating point grayscale color
+    GRAY32F(f32),
+    /// The 32-bit floating point grayscale color + 32-bit floating point alpha channel
+    GRAYA32F([f32; 2]),
+    /// The 32-bit floating point RGB color
+    RGB32F([f32; 3]),
+    /// The 32-bit floating point RGB color + 32-bit floating point alpha channel
+    RGBA32F([f32; 4]),
+    /// The 8-bit RGB color with the channels in the blue, green, red order
+    BGR8([u8; 3]),
+    /// The 8-bit RGB color + 8-bit alpha channel with the channels in the blue, green, red, alpha order
+    BGRA8([u8; 4]),
+    /// The 8-bit RGB color + 8-bit alpha channel with the channels in the alpha, red, green, blue order
+    ARGB8([u8; 4]),
+    /// The 16-bit RGB color with the channels in the blue, green, red order
+    BGR16([u16; 3]),
+    /// The 16-bit RGB color + 16-bit alpha channel with the channels in the blue, green, red, alpha order
+    BGRA16([u16; 4]),
+    /// The 16-bit RGB color + 16-bit alpha channel with the channels in the alpha, red, green, blue order
+    ARGB16([u16; 4]),
+    /// The 1-bit monochrome color, 0 or 1, only the lowest bit is used
+    GRAY1(u8),
+    /// The 4-bit grayscale color, from 0 to 15, only the lowest 4 bits are used
+    GRAY4(u8),
+    /// The 16-bit RGB color with red in the 5 highest bits, green in the middle 6 bits and blue in the 5 lowest bits
+    RGB565(u16),
+    /// The 8-bit RGB color with red in the 3 highest bits, green in the middle 3 bits and blue in the 2 lowest bits
+    RGB332(u8),
+    /// The 8-bit index into the [Palette](crate::Palette) of an image.
+    /// Without a palette (e.g. when blended on its own), the index is treated as an 8-bit grayscale color.
+    INDEXED8(u8),
+}
+impl PartialEq for Color {
+    fn eq(&self, other: &Self) -> bool {
+        // floating point channels are compared by their bits, so the equality is reflexive as required by Eq
+        self.as_bytes() == other.as_bytes() && ColorType::from(*self) == ColorType::from(*other)
+    }
+}
+impl Eq for Color {}
+impl Hash for Color {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ColorType::from(*self).hash(state);
+        self.as_bytes().hash(state);
+    }
 }
 impl Color {
     /// Return the number of bytes per pixel
@@ -130,9 +399,9 @@ impl Color {
     }
 
     /// Returns the slice of bytes of the color.
-    /// The bytes of u16 are represented in native endianness.
-    /// If the u16 is constructed back from the bytes,
-    /// it should be done using [u16::from_ne_bytes()] to ensure the correct value.
+    /// The bytes of u16 and f32 are represented in native endianness.
+    /// If the u16 or f32 is constructed back from the bytes,
+    /// it should be done using [u16::from_ne_bytes()] or [f32::from_ne_bytes()] to ensure the correct value.
     pub const fn as_bytes(&self) -> &[u8] {
         match self {
             Color::GRAY8(color) => slice::from_ref(color),
@@ -143,11 +412,21 @@ impl Color {
             Color::RGBA8(color) => color as &[u8],
             Color::RGB16(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 6) },
             Color::RGBA16(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 8) },
+            Color::GRAY32F(color) => unsafe { slice::from_raw_parts(color as *const f32 as *const u8, 4) },
+            Color::GRAYA32F(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 8) },
+            Color::RGB32F(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 12) },
+            Color::RGBA32F(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 16) },
+            Color::BGR8(color) => color as &[u8],
+            Color::BGRA8(color) | Color::ARGB8(color) => color as &[u8],
+            Color::BGR16(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 6) },
+            Color::BGRA16(color) | Color::ARGB16(color) => unsafe { slice::from_raw_parts(color.as_ptr() as *const u8, 8) },
+            Color::GRAY1(color) | Color::GRAY4(color) | Color::RGB332(color) | Color::INDEXED8(color) => slice::from_ref(color),
+            Color::RGB565(color) => unsafe { slice::from_raw_parts(color as *const u16 as *const u8, 2) },
         }
     }
 
     /// Create a color from the bytes.
-    /// The bytes of u16 should be represented in native endianness.
+    /// The bytes of u16 and f32 should be represented in native endianness.
     /// The length of the bytes should match the color type.
     /// # Returns
     /// The color
@@ -175,6 +454,184 @@ impl Color {
                 u16::from_ne_bytes([bytes[4], bytes[5]]),
                 u16::from_ne_bytes([bytes[6], bytes[7]])
             ]),
+            ColorType::GRAY32F => Color::GRAY32F(f32_from_bytes(bytes, 0)),
+            ColorType::GRAYA32F => Color::GRAYA32F([f32_from_bytes(bytes, 0), f32_from_bytes(bytes, 1)]),
+            ColorType::RGB32F => Color::RGB32F([f32_from_bytes(bytes, 0), f32_from_bytes(bytes, 1), f32_from_bytes(bytes, 2)]),
+            ColorType::RGBA32F => Color::RGBA32F([f32_from_bytes(bytes, 0), f32_from_bytes(bytes, 1), f32_from_bytes(bytes, 2), f32_from_bytes(bytes, 3)]),
+            ColorType::BGR8 => Color::BGR8([bytes[0], bytes[1], bytes[2]]),
+            ColorType::BGRA8 => Color::BGRA8([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            ColorType::ARGB8 => Color::ARGB8([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            ColorType::BGR16 => Color::BGR16([u16_from_bytes(bytes, 0), u16_from_bytes(bytes, 1), u16_from_bytes(bytes, 2)]),
+            ColorType::BGRA16 => Color::BGRA16([u16_from_bytes(bytes, 0), u16_from_bytes(bytes, 1), u16_from_bytes(bytes, 2), u16_from_bytes(bytes, 3)]),
+            ColorType::ARGB16 => Color::ARGB16([u16_from_bytes(bytes, 0), u16_from_bytes(bytes, 1), u16_from_bytes(bytes, 2), u16_from_bytes(bytes, 3)]),
+            ColorType::GRAY1 => Color::GRAY1(bytes[0] & 0x01),
+            ColorType::GRAY4 => Color::GRAY4(bytes[0] & 0x0F),
+            ColorType::RGB565 => Color::RGB565(u16_from_bytes(bytes, 0)),
+            ColorType::RGB332 => Color::RGB332(bytes[0]),
+            ColorType::INDEXED8 => Color::INDEXED8(bytes[0]),
+        }
+    }
+
+    /// Returns the color with the color channels multiplied by the alpha channel.
+    /// Colors without an alpha channel are returned unchanged.
+    /// # Returns
+    /// The premultiplied color
+    pub fn premultiplied(self) -> Self {
+        let color_type = ColorType::from(self);
+        if color_type.has_alpha() {
+            Self::from_rgba_f64(color_type, premultiply(self.to_rgba_f64()))
+        } else {
+            self
+        }
+    }
+
+    /// Returns the color with the color channels divided by the alpha channel.
+    /// This is the inverse of [Color::premultiplied()], up to the precision of the color type.
+    /// Colors without an alpha channel are returned unchanged.
+    /// # Returns
+    /// The color with straight alpha
+    pub fn unpremultiplied(self) -> Self {
+        let color_type = ColorType::from(self);
+        if color_type.has_alpha() {
+            Self::from_rgba_f64(color_type, unpremultiply(self.to_rgba_f64()))
+        } else {
+            self
+        }
+    }
+
+    /// Returns the normalized channels of the color as ```[red, green, blue, alpha]``` in range ```[0.0, 1.0]```
+    /// (the color channels of floating point colors may be larger).
+    /// Grayscale colors are expanded to equal red, green and blue channels.
+    /// Colors without an alpha channel are fully opaque.
+    pub(crate) fn to_rgba_f64(self) -> [f64; 4] {
+        match self {
+            Color::GRAY8(value) => {
+                let value = val_u8_to_f64(value);
+                [value, value, value, 1.0]
+            }
+            Color::GRAYA8(value) => {
+                let gray = val_u8_to_f64(value[0]);
+                [gray, gray, gray, val_u8_to_f64(value[1])]
+            }
+            Color::GRAY16(value) => {
+                let value = val_u16_to_f64(value);
+                [value, value, value, 1.0]
+            }
+            Color::GRAYA16(value) => {
+                let gray = val_u16_to_f64(value[0]);
+                [gray, gray, gray, val_u16_to_f64(value[1])]
+            }
+            Color::RGB8(value) => [val_u8_to_f64(value[0]), val_u8_to_f64(value[1]), val_u8_to_f64(value[2]), 1.0],
+            Color::RGBA8(value) => [val_u8_to_f64(value[0]), val_u8_to_f64(value[1]), val_u8_to_f64(value[2]), val_u8_to_f64(value[3])],
+            Color::RGB16(value) => [val_u16_to_f64(value[0]), val_u16_to_f64(value[1]), val_u16_to_f64(value[2]), 1.0],
+            Color::RGBA16(value) => [val_u16_to_f64(value[0]), val_u16_to_f64(value[1]), val_u16_to_f64(value[2]), val_u16_to_f64(value[3])],
+            Color::GRAY32F(value) => {
+                let value = value as f64;
+                [value, value, value, 1.0]
+            }
+            Color::GRAYA32F(value) => {
+                let gray = value[0] as f64;
+                [gray, gray, gray, value[1] as f64]
+            }
+            Color::RGB32F(value) => [value[0] as f64, value[1] as f64, value[2] as f64, 1.0],
+            Color::RGBA32F(value) => value.map(|channel| channel as f64),
+            Color::BGR8(value) => Color::RGB8([value[2], value[1], value[0]]).to_rgba_f64(),
+            Color::BGRA8(value) => Color::RGBA8([value[2], value[1], value[0], value[3]]).to_rgba_f64(),
+            Color::ARGB8(value) => Color::RGBA8([value[1], value[2], value[3], value[0]]).to_rgba_f64(),
+            Color::BGR16(value) => Color::RGB16([value[2], value[1], value[0]]).to_rgba_f64(),
+            Color::BGRA16(value) => Color::RGBA16([value[2], value[1], value[0], value[3]]).to_rgba_f64(),
+            Color::ARGB16(value) => Color::RGBA16([value[1], value[2], value[3], value[0]]).to_rgba_f64(),
+            Color::GRAY1(value) => {
+                let value = val_bits_to_f64(value as u16 & 0x01, 1);
+                [value, value, value, 1.0]
+            }
+            Color::GRAY4(value) => {
+                let value = val_bits_to_f64(value as u16 & 0x0F, 4);
+                [value, value, value, 1.0]
+            }
+            Color::RGB565(value) => [val_bits_to_f64(value >> 11, 5), val_bits_to_f64((value >> 5) & 0x3F, 6), val_bits_to_f64(value & 0x1F, 5), 1.0],
+            Color::RGB332(value) => {
+                let value = value as u16;
+                [val_bits_to_f64(value >> 5, 3), val_bits_to_f64((value >> 2) & 0x07, 3), val_bits_to_f64(value & 0x03, 2), 1.0]
+            }
+            Color::INDEXED8(value) => Color::GRAY8(value).to_rgba_f64(),
+        }
+    }
+
+    /// Returns the normalized channels of the color decoded from sRGB to linear light as ```[red, green, blue, alpha]```.
+    /// The alpha channel is not decoded.
+    pub(crate) fn to_linear_rgba_f64(self) -> [f64; 4] {
+        match self {
+            Color::GRAY8(value) => {
+                let value = srgb_u8_to_linear(value);
+                [value, value, value, 1.0]
+            }
+            Color::GRAYA8(value) => {
+                let gray = srgb_u8_to_linear(value[0]);
+                [gray, gray, gray, val_u8_to_f64(value[1])]
+            }
+            Color::RGB8(value) => [srgb_u8_to_linear(value[0]), srgb_u8_to_linear(value[1]), srgb_u8_to_linear(value[2]), 1.0],
+            Color::RGBA8(value) => [srgb_u8_to_linear(value[0]), srgb_u8_to_linear(value[1]), srgb_u8_to_linear(value[2]), val_u8_to_f64(value[3])],
+            _ => {
+                let rgba = self.to_rgba_f64();
+                [srgb_to_linear(rgba[0]), srgb_to_linear(rgba[1]), srgb_to_linear(rgba[2]), rgba[3]]
+            }
+        }
+    }
+
+    /// Creates a color of the given type from the normalized linear light ```[red, green, blue, alpha]``` channels,
+    /// encoding them to sRGB. The alpha channel is not encoded.
+    /// Values outside of range ```[0.0, 1.0]``` are clamped, except for the color channels of floating point colors larger than 1.0.
+    pub(crate) fn from_linear_rgba_f64(color_type: ColorType, rgba: [f64; 4]) -> Self {
+        let max = if color_type.is_float() { f64::INFINITY } else { 1.0 };
+        let gray = (rgba[0] + rgba[1] + rgba[2]) / 3.0;
+        match color_type {
+            ColorType::GRAY8 => Color::GRAY8(linear_to_srgb_u8(gray)),
+            ColorType::GRAYA8 => Color::GRAYA8([linear_to_srgb_u8(gray), val_f64_to_u8(rgba[3])]),
+            ColorType::RGB8 => Color::RGB8([linear_to_srgb_u8(rgba[0]), linear_to_srgb_u8(rgba[1]), linear_to_srgb_u8(rgba[2])]),
+            ColorType::RGBA8 => Color::RGBA8([linear_to_srgb_u8(rgba[0]), linear_to_srgb_u8(rgba[1]), linear_to_srgb_u8(rgba[2]), val_f64_to_u8(rgba[3])]),
+            _ => Color::from_rgba_f64(
+                color_type,
+                [
+                    linear_to_srgb(rgba[0].clamp(0.0, max)),
+                    linear_to_srgb(rgba[1].clamp(0.0, max)),
+                    linear_to_srgb(rgba[2].clamp(0.0, max)),
+                    rgba[3],
+                ],
+            ),
+        }
+    }
+
+    /// Creates a color of the given type from the normalized ```[red, green, blue, alpha]``` channels.
+    /// Values outside of range ```[0.0, 1.0]``` are clamped, except for the color channels of floating point colors larger than 1.0.
+    /// Grayscale colors take the average of the red, green and blue channels.
+    /// The alpha channel is dropped for color types without one.
+    pub(crate) fn from_rgba_f64(color_type: ColorType, rgba: [f64; 4]) -> Self {
+        let gray = (rgba[0] + rgba[1] + rgba[2]) / 3.0;
+        match color_type {
+            ColorType::GRAY8 => Color::GRAY8(val_f64_to_u8(gray)),
+            ColorType::GRAYA8 => Color::GRAYA8([val_f64_to_u8(gray), val_f64_to_u8(rgba[3])]),
+            ColorType::GRAY16 => Color::GRAY16(val_f64_to_u16(gray)),
+            ColorType::GRAYA16 => Color::GRAYA16([val_f64_to_u16(gray), val_f64_to_u16(rgba[3])]),
+            ColorType::RGB8 => Color::RGB8([val_f64_to_u8(rgba[0]), val_f64_to_u8(rgba[1]), val_f64_to_u8(rgba[2])]),
+            ColorType::RGBA8 => Color::RGBA8([val_f64_to_u8(rgba[0]), val_f64_to_u8(rgba[1]), val_f64_to_u8(rgba[2]), val_f64_to_u8(rgba[3])]),
+            ColorType::RGB16 => Color::RGB16([val_f64_to_u16(rgba[0]), val_f64_to_u16(rgba[1]), val_f64_to_u16(rgba[2])]),
+            ColorType::RGBA16 => Color::RGBA16([val_f64_to_u16(rgba[0]), val_f64_to_u16(rgba[1]), val_f64_to_u16(rgba[2]), val_f64_to_u16(rgba[3])]),
+            ColorType::GRAY32F => Color::GRAY32F(val_f64_to_f32(gray)),
+            ColorType::GRAYA32F => Color::GRAYA32F([val_f64_to_f32(gray), alpha_f64_to_f32(rgba[3])]),
+            ColorType::RGB32F => Color::RGB32F([val_f64_to_f32(rgba[0]), val_f64_to_f32(rgba[1]), val_f64_to_f32(rgba[2])]),
+            ColorType::RGBA32F => Color::RGBA32F([val_f64_to_f32(rgba[0]), val_f64_to_f32(rgba[1]), val_f64_to_f32(rgba[2]), alpha_f64_to_f32(rgba[3])]),
+            ColorType::BGR8 => Color::BGR8([val_f64_to_u8(rgba[2]), val_f64_to_u8(rgba[1]), val_f64_to_u8(rgba[0])]),
+            ColorType::BGRA8 => Color::BGRA8([val_f64_to_u8(rgba[2]), val_f64_to_u8(rgba[1]), val_f64_to_u8(rgba[0]), val_f64_to_u8(rgba[3])]),
+            ColorType::ARGB8 => Color::ARGB8([val_f64_to_u8(rgba[3]), val_f64_to_u8(rgba[0]), val_f64_to_u8(rgba[1]), val_f64_to_u8(rgba[2])]),
+            ColorType::BGR16 => Color::BGR16([val_f64_to_u16(rgba[2]), val_f64_to_u16(rgba[1]), val_f64_to_u16(rgba[0])]),
+            ColorType::BGRA16 => Color::BGRA16([val_f64_to_u16(rgba[2]), val_f64_to_u16(rgba[1]), val_f64_to_u16(rgba[0]), val_f64_to_u16(rgba[3])]),
+            ColorType::ARGB16 => Color::ARGB16([val_f64_to_u16(rgba[3]), val_f64_to_u16(rgba[0]), val_f64_to_u16(rgba[1]), val_f64_to_u16(rgba[2])]),
+            ColorType::GRAY1 => Color::GRAY1(val_f64_to_bits(gray, 1) as u8),
+            ColorType::GRAY4 => Color::GRAY4(val_f64_to_bits(gray, 4) as u8),
+            ColorType::RGB565 => Color::RGB565((val_f64_to_bits(rgba[0], 5) << 11) | (val_f64_to_bits(rgba[1], 6) << 5) | val_f64_to_bits(rgba[2], 5)),
+            ColorType::RGB332 => Color::RGB332(((val_f64_to_bits(rgba[0], 3) << 5) | (val_f64_to_bits(rgba[1], 3) << 2) | val_f64_to_bits(rgba[2], 2)) as u8),
+            ColorType::INDEXED8 => Color::INDEXED8(val_f64_to_u8(gray)),
         }
     }
 }
@@ -189,6 +646,21 @@ impl Display for Color {
             Color::RGBA8(value) => write!(f, "RGBA8({:?})", value),
             Color::RGB16(value) => write!(f, "RGB16({:?})", value),
             Color::RGBA16(value) => write!(f, "RGBA16({:?})", value),
+            Color::GRAY32F(value) => write!(f, "GRAY32F({:?})", value),
+            Color::GRAYA32F(value) => write!(f, "GRAYA32F({:?})", value),
+            Color::RGB32F(value) => write!(f, "RGB32F({:?})", value),
+            Color::RGBA32F(value) => write!(f, "RGBA32F({:?})", value),
+            Color::BGR8(value) => write!(f, "BGR8({:?})", value),
+            Color::BGRA8(value) => write!(f, "BGRA8({:?})", value),
+            Color::ARGB8(value) => write!(f, "ARGB8({:?})", value),
+            Color::BGR16(value) => write!(f, "BGR16({:?})", value),
+            Color::BGRA16(value) => write!(f, "BGRA16({:?})", value),
+            Color::ARGB16(value) => write!(f, "ARGB16({:?})", value),
+            Color::GRAY1(value) => write!(f, "GRAY1({:?})", value),
+            Color::GRAY4(value) => write!(f, "GRAY4({:?})", value),
+            Color::RGB565(value) => write!(f, "RGB565({:#06X})", value),
+            Color::RGB332(value) => write!(f, "RGB332({:#04X})", value),
+            Color::INDEXED8(value) => write!(f, "INDEXED8({:?})", value),
         }
     }
 }
@@ -207,6 +679,157 @@ mod tests {
         assert_eq!(ColorType::RGBA8.bytes_per_pixel(), 4);
         assert_eq!(ColorType::RGB16.bytes_per_pixel(), 6);
         assert_eq!(ColorType::RGBA16.bytes_per_pixel(), 8);
+        assert_eq!(ColorType::GRAY32F.bytes_per_pixel(), 4);
+        assert_eq!(ColorType::GRAYA32F.bytes_per_pixel(), 8);
+        assert_eq!(ColorType::RGB32F.bytes_per_pixel(), 12);
+        assert_eq!(ColorType::RGBA32F.bytes_per_pixel(), 16);
+        assert_eq!(ColorType::BGR8.bytes_per_pixel(), 3);
+        assert_eq!(ColorType::BGRA8.bytes_per_pixel(), 4);
+        assert_eq!(ColorType::ARGB8.bytes_per_pixel(), 4);
+        assert_eq!(ColorType::BGR16.bytes_per_pixel(), 6);
+        assert_eq!(ColorType::BGRA16.bytes_per_pixel(), 8);
+        assert_eq!(ColorType::ARGB16.bytes_per_pixel(), 8);
+        assert_eq!(ColorType::GRAY1.bytes_per_pixel(), 1);
+        assert_eq!(ColorType::GRAY4.bytes_per_pixel(), 1);
+        assert_eq!(ColorType::RGB565.bytes_per_pixel(), 2);
+        assert_eq!(ColorType::RGB332.bytes_per_pixel(), 1);
+        assert_eq!(ColorType::INDEXED8.bytes_per_pixel(), 1);
+    }
+
+    #[test]
+    fn test_color_type_bits_per_pixel() {
+        assert_eq!(ColorType::GRAY1.bits_per_pixel(), 1);
+        assert_eq!(ColorType::GRAY4.bits_per_pixel(), 4);
+        assert_eq!(ColorType::RGB332.bits_per_pixel(), 8);
+        assert_eq!(ColorType::RGB565.bits_per_pixel(), 16);
+        assert_eq!(ColorType::RGBA32F.bits_per_pixel(), 128);
+
+        assert_eq!(ColorType::GRAY1.row_len(8), 1);
+        assert_eq!(ColorType::GRAY1.row_len(9), 2);
+        assert_eq!(ColorType::GRAY4.row_len(3), 2);
+        assert_eq!(ColorType::RGB565.row_len(3), 6);
+        assert_eq!(ColorType::RGB8.row_len(0), 0);
+    }
+
+    #[test]
+    fn test_color_type_has_alpha() {
+        assert!(!ColorType::GRAY8.has_alpha());
+        assert!(ColorType::GRAYA8.has_alpha());
+        assert!(!ColorType::GRAY16.has_alpha());
+        assert!(ColorType::GRAYA16.has_alpha());
+        assert!(!ColorType::RGB8.has_alpha());
+        assert!(ColorType::RGBA8.has_alpha());
+        assert!(!ColorType::RGB16.has_alpha());
+        assert!(ColorType::RGBA16.has_alpha());
+        assert!(!ColorType::GRAY32F.has_alpha());
+        assert!(ColorType::GRAYA32F.has_alpha());
+        assert!(!ColorType::RGB32F.has_alpha());
+        assert!(ColorType::RGBA32F.has_alpha());
+        assert!(!ColorType::BGR8.has_alpha());
+        assert!(ColorType::BGRA8.has_alpha());
+        assert!(ColorType::ARGB8.has_alpha());
+        assert!(!ColorType::BGR16.has_alpha());
+        assert!(ColorType::BGRA16.has_alpha());
+        assert!(ColorType::ARGB16.has_alpha());
+        assert!(!ColorType::GRAY1.has_alpha());
+        assert!(!ColorType::GRAY4.has_alpha());
+        assert!(!ColorType::RGB565.has_alpha());
+        assert!(!ColorType::RGB332.has_alpha());
+        assert!(!ColorType::INDEXED8.has_alpha());
+    }
+
+    #[test]
+    fn test_color_type_is_float() {
+        assert!(!ColorType::GRAY8.is_float());
+        assert!(!ColorType::RGBA16.is_float());
+        assert!(ColorType::GRAY32F.is_float());
+        assert!(ColorType::GRAYA32F.is_float());
+        assert!(ColorType::RGB32F.is_float());
+        assert!(ColorType::RGBA32F.is_float());
+    }
+
+    #[test]
+    fn test_color_type_with_alpha() {
+        assert_eq!(ColorType::GRAY8.with_alpha(), ColorType::GRAYA8);
+        assert_eq!(ColorType::GRAYA8.with_alpha(), ColorType::GRAYA8);
+        assert_eq!(ColorType::GRAY16.with_alpha(), ColorType::GRAYA16);
+        assert_eq!(ColorType::RGB8.with_alpha(), ColorType::RGBA8);
+        assert_eq!(ColorType::RGB16.with_alpha(), ColorType::RGBA16);
+        assert_eq!(ColorType::RGBA16.with_alpha(), ColorType::RGBA16);
+        assert_eq!(ColorType::GRAY32F.with_alpha(), ColorType::GRAYA32F);
+        assert_eq!(ColorType::RGB32F.with_alpha(), ColorType::RGBA32F);
+        assert_eq!(ColorType::BGR8.with_alpha(), ColorType::BGRA8);
+        assert_eq!(ColorType::ARGB8.with_alpha(), ColorType::ARGB8);
+        assert_eq!(ColorType::BGR16.with_alpha(), ColorType::BGRA16);
+        assert_eq!(ColorType::ARGB16.with_alpha(), ColorType::ARGB16);
+        assert_eq!(ColorType::GRAY1.with_alpha(), ColorType::GRAYA8);
+        assert_eq!(ColorType::GRAY4.with_alpha(), ColorType::GRAYA8);
+        assert_eq!(ColorType::RGB565.with_alpha(), ColorType::RGBA8);
+        assert_eq!(ColorType::RGB332.with_alpha(), ColorType::RGBA8);
+        assert_eq!(ColorType::INDEXED8.with_alpha(), ColorType::RGBA8);
+    }
+
+    #[test]
+    fn test_color_rgba_f64_round_trip() {
+        let colors = [
+            Color::GRAY8(17),
+            Color::GRAYA8([17, 200]),
+            Color::GRAY16(4_000),
+            Color::GRAYA16([4_000, 60_000]),
+            Color::RGB8([1, 128, 254]),
+            Color::RGBA8([1, 128, 254, 77]),
+            Color::RGB16([1, 32_768, 65_534]),
+            Color::RGBA16([1, 32_768, 65_534, 12_345]),
+            Color::GRAY32F(0.3),
+            Color::GRAYA32F([0.3, 0.75]),
+            Color::RGB32F([0.0, 0.5, 1.0]),
+            Color::RGBA32F([0.125, 0.5, 1.0, 0.25]),
+            Color::BGR8([1, 128, 254]),
+            Color::BGRA8([1, 128, 254, 77]),
+            Color::ARGB8([77, 1, 128, 254]),
+            Color::BGR16([1, 32_768, 65_534]),
+            Color::BGRA16([1, 32_768, 65_534, 12_345]),
+            Color::ARGB16([12_345, 1, 32_768, 65_534]),
+            Color::GRAY1(0),
+            Color::GRAY1(1),
+            Color::GRAY4(7),
+            Color::RGB565(0xABA3),
+            Color::RGB332(0xAE),
+            Color::INDEXED8(42),
+        ];
+        for color in colors {
+            assert_eq!(Color::from_rgba_f64(ColorType::from(color), color.to_rgba_f64()), color);
+            assert_eq!(Color::from_linear_rgba_f64(ColorType::from(color), color.to_linear_rgba_f64()), color);
+        }
+        assert_eq!(Color::GRAY8(255).to_rgba_f64(), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(Color::RGBA8([0, 0, 0, 0]).to_rgba_f64(), [0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(Color::from_rgba_f64(ColorType::GRAY8, [1.0, 0.5, 0.0, 1.0]), Color::GRAY8(128));
+        assert_eq!(Color::from_rgba_f64(ColorType::RGB8, [2.0, -1.0, 0.5, 0.0]), Color::RGB8([255, 0, 128]));
+        assert_eq!(Color::BGRA8([0, 51, 255, 102]).to_rgba_f64(), [1.0, 0.2, 0.0, 0.4]);
+        assert_eq!(Color::from_rgba_f64(ColorType::ARGB8, [1.0, 0.2, 0.0, 0.4]), Color::ARGB8([102, 255, 51, 0]));
+        assert_eq!(Color::RGB565(0xF800).to_rgba_f64(), [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(Color::RGB332(0x1C).to_rgba_f64(), [0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(Color::GRAY4(15).to_rgba_f64(), [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(Color::from_rgba_f64(ColorType::RGB565, [0.0, 0.0, 1.0, 1.0]), Color::RGB565(0x001F));
+        assert_eq!(Color::from_rgba_f64(ColorType::GRAY1, [0.6, 0.6, 0.6, 1.0]), Color::GRAY1(1));
+        assert_eq!(Color::from_rgba_f64(ColorType::GRAY1, [0.4, 0.4, 0.4, 1.0]), Color::GRAY1(0));
+
+        // floating point colors keep the high dynamic range, but not negative values or alpha out of range
+        assert_eq!(Color::from_rgba_f64(ColorType::RGBA32F, [2.0, -1.0, 0.5, 1.5]), Color::RGBA32F([2.0, 0.0, 0.5, 1.0]));
+        assert_eq!(Color::RGB32F([4.0, 0.0, 1.0]).to_rgba_f64(), [4.0, 0.0, 1.0, 1.0]);
+        assert_eq!(Color::from_linear_rgba_f64(ColorType::GRAY32F, Color::GRAY32F(3.0).to_linear_rgba_f64()), Color::GRAY32F(3.0));
+    }
+
+    #[test]
+    fn test_color_premultiplied() {
+        assert_eq!(Color::RGBA8([255, 128, 0, 128]).premultiplied(), Color::RGBA8([128, 64, 0, 128]));
+        assert_eq!(Color::RGBA8([128, 64, 0, 128]).unpremultiplied(), Color::RGBA8([255, 128, 0, 128]));
+        assert_eq!(Color::GRAYA16([65_535, 0]).premultiplied(), Color::GRAYA16([0, 0]));
+        assert_eq!(Color::GRAYA16([0, 0]).unpremultiplied(), Color::GRAYA16([0, 0]));
+        assert_eq!(Color::RGB8([255, 128, 0]).premultiplied(), Color::RGB8([255, 128, 0]));
+        assert_eq!(Color::GRAY8(10).unpremultiplied(), Color::GRAY8(10));
+        assert_eq!(format!("{}", AlphaMode::Straight), "Straight");
+        assert_eq!(format!("{}", AlphaMode::Premultiplied), "Premultiplied");
     }
 
     #[test]
@@ -219,6 +842,21 @@ mod tests {
         assert_eq!(Color::RGBA8([0, 1, 2, 3]).bytes_per_pixel(), 4);
         assert_eq!(Color::RGB16([0, 1, 2]).bytes_per_pixel(), 6);
         assert_eq!(Color::RGBA16([0, 1, 2, 3]).bytes_per_pixel(), 8);
+        assert_eq!(Color::GRAY32F(0.0).bytes_per_pixel(), 4);
+        assert_eq!(Color::GRAYA32F([0.0, 1.0]).bytes_per_pixel(), 8);
+        assert_eq!(Color::RGB32F([0.0, 1.0, 2.0]).bytes_per_pixel(), 12);
+        assert_eq!(Color::RGBA32F([0.0, 1.0, 2.0, 3.0]).bytes_per_pixel(), 16);
+        assert_eq!(Color::BGR8([0, 1, 2]).bytes_per_pixel(), 3);
+        assert_eq!(Color::BGRA8([0, 1, 2, 3]).bytes_per_pixel(), 4);
+        assert_eq!(Color::ARGB8([0, 1, 2, 3]).bytes_per_pixel(), 4);
+        assert_eq!(Color::BGR16([0, 1, 2]).bytes_per_pixel(), 6);
+        assert_eq!(Color::BGRA16([0, 1, 2, 3]).bytes_per_pixel(), 8);
+        assert_eq!(Color::ARGB16([0, 1, 2, 3]).bytes_per_pixel(), 8);
+        assert_eq!(Color::GRAY1(1).bytes_per_pixel(), 1);
+        assert_eq!(Color::GRAY4(1).bytes_per_pixel(), 1);
+        assert_eq!(Color::RGB565(1).bytes_per_pixel(), 2);
+        assert_eq!(Color::RGB332(1).bytes_per_pixel(), 1);
+        assert_eq!(Color::INDEXED8(1).bytes_per_pixel(), 1);
     }
 
     #[test]
@@ -231,6 +869,21 @@ mod tests {
         assert_eq!(format!("{}", ColorType::RGBA8), "RGBA8");
         assert_eq!(format!("{}", ColorType::RGB16), "RGB16");
         assert_eq!(format!("{}", ColorType::RGBA16), "RGBA16");
+        assert_eq!(format!("{}", ColorType::GRAY32F), "GRAY32F");
+        assert_eq!(format!("{}", ColorType::GRAYA32F), "GRAYA32F");
+        assert_eq!(format!("{}", ColorType::RGB32F), "RGB32F");
+        assert_eq!(format!("{}", ColorType::RGBA32F), "RGBA32F");
+        assert_eq!(format!("{}", ColorType::BGR8), "BGR8");
+        assert_eq!(format!("{}", ColorType::BGRA8), "BGRA8");
+        assert_eq!(format!("{}", ColorType::ARGB8), "ARGB8");
+        assert_eq!(format!("{}", ColorType::BGR16), "BGR16");
+        assert_eq!(format!("{}", ColorType::BGRA16), "BGRA16");
+        assert_eq!(format!("{}", ColorType::ARGB16), "ARGB16");
+        assert_eq!(format!("{}", ColorType::GRAY1), "GRAY1");
+        assert_eq!(format!("{}", ColorType::GRAY4), "GRAY4");
+        assert_eq!(format!("{}", ColorType::RGB565), "RGB565");
+        assert_eq!(format!("{}", ColorType::RGB332), "RGB332");
+        assert_eq!(format!("{}", ColorType::INDEXED8), "INDEXED8");
     }
 
     #[test]
@@ -243,6 +896,21 @@ mod tests {
         assert_eq!(format!("{}", Color::RGBA8([0, 1, 2, 3])), "RGBA8([0, 1, 2, 3])");
         assert_eq!(format!("{}", Color::RGB16([0, 1, 2])), "RGB16([0, 1, 2])");
         assert_eq!(format!("{}", Color::RGBA16([0, 1, 2, 3])), "RGBA16([0, 1, 2, 3])");
+        assert_eq!(format!("{}", Color::GRAY32F(0.5)), "GRAY32F(0.5)");
+        assert_eq!(format!("{}", Color::GRAYA32F([0.5, 1.0])), "GRAYA32F([0.5, 1.0])");
+        assert_eq!(format!("{}", Color::RGB32F([0.0, 1.0, 2.5])), "RGB32F([0.0, 1.0, 2.5])");
+        assert_eq!(format!("{}", Color::RGBA32F([0.0, 1.0, 2.5, 1.0])), "RGBA32F([0.0, 1.0, 2.5, 1.0])");
+        assert_eq!(format!("{}", Color::BGR8([0, 1, 2])), "BGR8([0, 1, 2])");
+        assert_eq!(format!("{}", Color::BGRA8([0, 1, 2, 3])), "BGRA8([0, 1, 2, 3])");
+        assert_eq!(format!("{}", Color::ARGB8([0, 1, 2, 3])), "ARGB8([0, 1, 2, 3])");
+        assert_eq!(format!("{}", Color::BGR16([0, 1, 2])), "BGR16([0, 1, 2])");
+        assert_eq!(format!("{}", Color::BGRA16([0, 1, 2, 3])), "BGRA16([0, 1, 2, 3])");
+        assert_eq!(format!("{}", Color::ARGB16([0, 1, 2, 3])), "ARGB16([0, 1, 2, 3])");
+        assert_eq!(format!("{}", Color::GRAY1(1)), "GRAY1(1)");
+        assert_eq!(format!("{}", Color::GRAY4(12)), "GRAY4(12)");
+        assert_eq!(format!("{}", Color::RGB565(0xF800)), "RGB565(0xF800)");
+        assert_eq!(format!("{}", Color::RGB332(0x1C)), "RGB332(0x1C)");
+        assert_eq!(format!("{}", Color::INDEXED8(7)), "INDEXED8(7)");
     }
 
     #[test]
@@ -255,6 +923,40 @@ mod tests {
         assert_eq!(ColorType::from(Color::RGBA8([0, 1, 2, 3])), ColorType::RGBA8);
         assert_eq!(ColorType::from(Color::RGB16([0, 1, 2])), ColorType::RGB16);
         assert_eq!(ColorType::from(Color::RGBA16([0, 1, 2, 3])), ColorType::RGBA16);
+        assert_eq!(ColorType::from(Color::GRAY32F(0.0)), ColorType::GRAY32F);
+        assert_eq!(ColorType::from(Color::GRAYA32F([0.0, 1.0])), ColorType::GRAYA32F);
+        assert_eq!(ColorType::from(Color::RGB32F([0.0, 1.0, 2.0])), ColorType::RGB32F);
+        assert_eq!(ColorType::from(Color::RGBA32F([0.0, 1.0, 2.0, 3.0])), ColorType::RGBA32F);
+        assert_eq!(ColorType::from(Color::BGR8([0, 1, 2])), ColorType::BGR8);
+        assert_eq!(ColorType::from(Color::BGRA8([0, 1, 2, 3])), ColorType::BGRA8);
+        assert_eq!(ColorType::from(Color::ARGB8([0, 1, 2, 3])), ColorType::ARGB8);
+        assert_eq!(ColorType::from(Color::BGR16([0, 1, 2])), ColorType::BGR16);
+        assert_eq!(ColorType::from(Color::BGRA16([0, 1, 2, 3])), ColorType::BGRA16);
+        assert_eq!(ColorType::from(Color::ARGB16([0, 1, 2, 3])), ColorType::ARGB16);
+        assert_eq!(ColorType::from(Color::GRAY1(1)), ColorType::GRAY1);
+        assert_eq!(ColorType::from(Color::GRAY4(1)), ColorType::GRAY4);
+        assert_eq!(ColorType::from(Color::RGB565(1)), ColorType::RGB565);
+        assert_eq!(ColorType::from(Color::RGB332(1)), ColorType::RGB332);
+        assert_eq!(ColorType::from(Color::INDEXED8(1)), ColorType::INDEXED8);
+    }
+
+    #[test]
+    fn test_color_eq_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        let hash = |color: Color| {
+            let mut hasher = DefaultHasher::new();
+            color.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(Color::RGB8([1, 2, 3]), Color::RGB8([1, 2, 3]));
+        assert_ne!(Color::RGB8([1, 2, 3]), Color::RGB8([1, 2, 4]));
+        // colors of different types are different even with the same bytes
+        assert_ne!(Color::GRAY16(0), Color::GRAYA8([0, 0]));
+        assert_ne!(hash(Color::GRAY16(0)), hash(Color::GRAYA8([0, 0])));
+        // floating point colors are compared by their bits
+        assert_eq!(Color::GRAY32F(f32::NAN), Color::GRAY32F(f32::NAN));
+        assert_ne!(Color::GRAY32F(0.0), Color::GRAY32F(-0.0));
+        assert_eq!(hash(Color::RGBA32F([0.5, 1.0, 2.0, 1.0])), hash(Color::RGBA32F([0.5, 1.0, 2.0, 1.0])));
     }
     
     #[test]
@@ -268,6 +970,8 @@ mod tests {
         assert_eq!(ColorType::from(ImageColorType::Rgba8), ColorType::RGBA8);
         assert_eq!(ColorType::from(ImageColorType::Rgb16), ColorType::RGB16);
         assert_eq!(ColorType::from(ImageColorType::Rgba16), ColorType::RGBA16);
+        assert_eq!(ColorType::from(ImageColorType::Rgb32F), ColorType::RGB32F);
+        assert_eq!(ColorType::from(ImageColorType::Rgba32F), ColorType::RGBA32F);
     }
     
     #[test]
@@ -281,6 +985,8 @@ mod tests {
         assert_eq!(ImageColorType::from(ColorType::RGBA8), ImageColorType::Rgba8);
         assert_eq!(ImageColorType::from(ColorType::RGB16), ImageColorType::Rgb16);
         assert_eq!(ImageColorType::from(ColorType::RGBA16), ImageColorType::Rgba16);
+        assert_eq!(ImageColorType::from(ColorType::RGB32F), ImageColorType::Rgb32F);
+        assert_eq!(ImageColorType::from(ColorType::RGBA32F), ImageColorType::Rgba32F);
     }
     
     #[test]
@@ -293,5 +999,31 @@ mod tests {
         assert_eq!(Color::RGBA8([0, 1, 2, 3]).as_bytes(), &[0, 1, 2, 3]);
         assert_eq!(Color::RGB16([1, 2, 3]).as_bytes(), &[1u16.to_ne_bytes(), 2u16.to_ne_bytes(), 3u16.to_ne_bytes()].concat());
         assert_eq!(Color::RGBA16([1, 2, 3, 4]).as_bytes(), &[1u16.to_ne_bytes(), 2u16.to_ne_bytes(), 3u16.to_ne_bytes(), 4u16.to_ne_bytes()].concat());
+        assert_eq!(Color::GRAY32F(0.5).as_bytes(), &0.5f32.to_ne_bytes());
+        assert_eq!(Color::RGBA32F([1.0, 2.0, 3.0, 4.0]).as_bytes(), &[1f32.to_ne_bytes(), 2f32.to_ne_bytes(), 3f32.to_ne_bytes(), 4f32.to_ne_bytes()].concat());
+        assert_eq!(Color::BGRA8([1, 2, 3, 4]).as_bytes(), &[1, 2, 3, 4]);
+        assert_eq!(Color::RGB565(0xF800).as_bytes(), &0xF800u16.to_ne_bytes());
+        // the unused bits of packed pixels are ignored
+        assert_eq!(Color::from_bytes(ColorType::GRAY4, &[0xF3]), Color::GRAY4(3));
+        assert_eq!(Color::ARGB16([1, 2, 3, 4]).as_bytes(), &[1u16.to_ne_bytes(), 2u16.to_ne_bytes(), 3u16.to_ne_bytes(), 4u16.to_ne_bytes()].concat());
+        for color in [
+            Color::GRAY32F(-1.5),
+            Color::GRAYA32F([0.5, 1.0]),
+            Color::RGB32F([0.0, 1.0, 2.0]),
+            Color::RGBA32F([0.0, 1.0, 2.0, 3.0]),
+            Color::BGR8([0, 1, 2]),
+            Color::BGRA8([0, 1, 2, 3]),
+            Color::ARGB8([0, 1, 2, 3]),
+            Color::BGR16([0, 1, 2]),
+            Color::BGRA16([0, 1, 2, 3]),
+            Color::ARGB16([0, 1, 2, 3]),
+            Color::GRAY1(1),
+            Color::GRAY4(9),
+            Color::RGB565(0xABCD),
+            Color::RGB332(0xAB),
+            Color::INDEXED8(200),
+        ] {
+            assert_eq!(Color::from_bytes(ColorType::from(color), color.as_bytes()), color);
+        }
     }
 }
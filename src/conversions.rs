@@ -1,7 +1,9 @@
 //! Functions for converting between different color/image types.
 
-use crate::colors::{Color, ColorType};
+use crate::blending::{premultiply, unpremultiply};
+use crate::colors::{AlphaMode, Color, ColorType};
 use crate::image::Image;
+use crate::palette::Palette;
 
 /// Calculates the average of a slice of integer values.
 /// # Arguments
@@ -37,6 +39,53 @@ fn val_u16_to_u8(value: u16) -> u8 {
     (value as f64 * 0.003_891_050_583_657_587_6_f64).round() as u8
 }
 
+/// Returns whether the channels of the color type are packed in the bits of the pixel.
+/// # Arguments
+/// * ```color_type``` - The color type.
+/// # Returns
+/// * ```true``` if the channels are packed, ```false``` otherwise.
+const fn is_packed(color_type: ColorType) -> bool {
+    matches!(color_type, ColorType::GRAY1 | ColorType::GRAY4 | ColorType::RGB565 | ColorType::RGB332)
+}
+
+/// Returns the color type with the same channels in the red, green, blue, alpha order.
+/// # Arguments
+/// * ```color_type``` - The color type.
+/// # Returns
+/// * The color type with the channels in the RGB order or [None] if the channels are already in that order.
+const fn rgb_order(color_type: ColorType) -> Option<ColorType> {
+    match color_type {
+        ColorType::BGR8 => Some(ColorType::RGB8),
+        ColorType::BGRA8 | ColorType::ARGB8 => Some(ColorType::RGBA8),
+        ColorType::BGR16 => Some(ColorType::RGB16),
+        ColorType::BGRA16 | ColorType::ARGB16 => Some(ColorType::RGBA16),
+        _ => None,
+    }
+}
+
+/// Reorders the channels of the pixels between the color type and the same channels in the RGB order.
+/// # Arguments
+/// * ```data``` - The pixel data.
+/// * ```color_type``` - The color type with the channels in other than the RGB order.
+/// * ```to_rgb``` - If true, the channels are reordered to the RGB order, otherwise from it.
+fn reorder_channels(data: &mut [u8], color_type: ColorType, to_rgb: bool) {
+    let channel_size = match color_type {
+        ColorType::BGR16 | ColorType::BGRA16 | ColorType::ARGB16 => 2,
+        _ => 1,
+    };
+    for pixel in data.chunks_exact_mut(color_type.bytes_per_pixel()) {
+        match color_type {
+            ColorType::ARGB8 | ColorType::ARGB16 if to_rgb => pixel.rotate_left(channel_size),
+            ColorType::ARGB8 | ColorType::ARGB16 => pixel.rotate_right(channel_size),
+            _ => {
+                // swapping blue and red is its own inverse
+                let (blue, rest) = pixel.split_at_mut(channel_size);
+                blue.swap_with_slice(&mut rest[channel_size..2 * channel_size]);
+            }
+        }
+    }
+}
+
 impl Image {
     /// Checks if conversion to the specified color type is lossless.
     /// # Arguments
@@ -57,1152 +106,1298 @@ impl Image {
     /// assert!(!Image::is_lossless_conversion(image_type1, image_type2));
     /// ```
     pub const fn is_lossless_conversion(image_type1: ColorType, image_type2: ColorType) -> bool {
-        match image_type1 {
-            ColorType::GRAY8 => true,
-            ColorType::GRAYA8 => match image_type2 {
-                ColorType::GRAY8 => false,
-                ColorType::GRAYA8 => true,
-                ColorType::GRAY16 => false,
-                ColorType::GRAYA16 => true,
-                ColorType::RGB8 => false,
-                ColorType::RGBA8 => true,
-                ColorType::RGB16 => false,
-                ColorType::RGBA16 => true,
-            },
-            ColorType::GRAY16 => match image_type2 {
-                ColorType::GRAY8 => false,
-                ColorType::GRAYA8 => false,
-                ColorType::GRAY16 => true,
-                ColorType::GRAYA16 => true,
-                ColorType::RGB8 => false,
-                ColorType::RGBA8 => false,
-                ColorType::RGB16 => true,
-                ColorType::RGBA16 => true,
-            },
-            ColorType::GRAYA16 => match image_type2 {
-                ColorType::GRAY8 => false,
-                ColorType::GRAYA8 => false,
-                ColorType::GRAY16 => false,
-                ColorType::GRAYA16 => true,
-                ColorType::RGB8 => false,
-                ColorType::RGBA8 => false,
-                ColorType::RGB16 => false,
-                ColorType::RGBA16 => true,
-            },
-            ColorType::RGB8 => match image_type2 {
-                ColorType::GRAY8 => false,
-                ColorType::GRAYA8 => false,
-                ColorType::GRAY16 => false,
-                ColorType::GRAYA16 => false,
-                ColorType::RGB8 => true,
-                ColorType::RGBA8 => true,
-                ColorType::RGB16 => true,
-                ColorType::RGBA16 => true,
-            },
-            ColorType::RGBA8 => match image_type2 {
-                ColorType::GRAY8 => false,
-                ColorType::GRAYA8 => false,
-                ColorType::GRAY16 => false,
-                ColorType::GRAYA16 => false,
-                ColorType::RGB8 => false,
-                ColorType::RGBA8 => true,
-                ColorType::RGB16 => false,
-                ColorType::RGBA16 => true,
-            },
-            ColorType::RGB16 => match image_type2 {
-                ColorType::GRAY8 => false,
-                ColorType::GRAYA8 => false,
-                ColorType::GRAY16 => false,
-                ColorType::GRAYA16 => false,
-                ColorType::RGB8 => false,
-                ColorType::RGBA8 => false,
-                ColorType::RGB16 => true,
-                ColorType::RGBA16 => true,
-            },
-            ColorType::RGBA16 => {
-                matches!(image_type2, ColorType::RGBA16)
+        /// Returns the number of bits of the least precise channel of the color type.
+        const fn precision(color_type: ColorType) -> u8 {
+            match color_type {
+                ColorType::GRAY1 => 1,
+                ColorType::RGB332 => 2,
+                ColorType::GRAY4 => 4,
+                ColorType::RGB565 => 5,
+                ColorType::GRAY8 | ColorType::GRAYA8 | ColorType::RGB8 | ColorType::RGBA8 | ColorType::BGR8 | ColorType::BGRA8 | ColorType::ARGB8 | ColorType::INDEXED8 => 8,
+                ColorType::GRAY16 | ColorType::GRAYA16 | ColorType::RGB16 | ColorType::RGBA16 | ColorType::BGR16 | ColorType::BGRA16 | ColorType::ARGB16 => 16,
+                // f32 holds every u16 value exactly
+                ColorType::GRAY32F | ColorType::GRAYA32F | ColorType::RGB32F | ColorType::RGBA32F => 32,
             }
         }
+        /// Returns whether the color type has the red, green and blue channels.
+        const fn is_rgb(color_type: ColorType) -> bool {
+            !matches!(
+                color_type,
+                ColorType::GRAY1 | ColorType::GRAY4 | ColorType::GRAY8 | ColorType::GRAYA8 | ColorType::GRAY16 | ColorType::GRAYA16 | ColorType::GRAY32F | ColorType::GRAYA32F
+            )
+        }
+
+        // the colors of the image don't have to be in the palette made for it
+        if matches!(image_type2, ColorType::INDEXED8) {
+            return matches!(image_type1, ColorType::INDEXED8);
+        }
+
+        // floating point values above 1.0 or between the integer steps are lost when converted to integers
+        let precision_kept = precision(image_type2) >= precision(image_type1);
+        let channels_kept = !is_rgb(image_type1) || is_rgb(image_type2);
+        // the colors of the palette have the alpha channel
+        let alpha_kept = !(image_type1.has_alpha() || matches!(image_type1, ColorType::INDEXED8)) || image_type2.has_alpha();
+        precision_kept && channels_kept && alpha_kept
     }
-    
+
     /// Converts the image to the specified color type.
     /// If the image is already in the specified color type, this function does nothing.
+    /// The alpha mode of the image is kept.
+    /// Indexed images are converted through the colors of their palette, which is dropped afterwards.
+    /// Images converted to [ColorType::INDEXED8] get a palette of their colors if there are at most 256 of them,
+    /// otherwise the 256 colors of [ColorType::RGB332], see [Image::convert_with_palette()] for converting to a given palette.
     /// # Arguments
     /// * ```color_type``` - The color type to which the image will be converted.
     pub fn convert(&mut self, color_type: ColorType) {
-        if self.color_type != color_type {
-            // convert image data
-            let data = &mut self.data;
-            match self.color_type {
-                ColorType::GRAY8 => {
-                    match color_type {
-                        ColorType::GRAY8 => {} // do nothing (same type)
-                        ColorType::GRAYA8 => {
-                            let original_len = data.len();
-
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(255);
-                            }
-
-                            for i in (0..original_len).rev() {
-                                data[i << 1] = data[i];
-                            }
-                            for i in (1..original_len).step_by(2) {
-                                data[i] = 255;
-                            }
-                        }
-                        ColorType::GRAY16 => {
-                            let original_len = data.len();
-
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_loc = i << 1;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-                        }
-                        ColorType::GRAYA16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len * 3;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
-
-                            for i in (0..original_len).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_loc = i << 2;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-
-                            for i in (2..original_len).step_by(4) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+        if self.color_type != color_type && self.alpha_mode == AlphaMode::Premultiplied {
+            // convert with straight alpha
+            self.set_alpha_mode(AlphaMode::Straight);
+            self.convert(color_type);
+            self.set_alpha_mode(AlphaMode::Premultiplied);
+        } else if self.color_type != color_type && self.color_type == ColorType::INDEXED8 {
+            // the indices are resolved to the colors of the palette, indices without a palette are gray levels
+            let palette = self.palette.take();
+            let resolve = |color: Color| match &palette {
+                Some(palette) => palette.resolve(color),
+                None => Color::from_rgba_f64(ColorType::RGBA8, color.to_rgba_f64()),
+            };
+            let mut data = Vec::with_capacity(self.data.len() * ColorType::RGBA8.bytes_per_pixel());
+            for &index in &self.data {
+                data.extend_from_slice(resolve(Color::INDEXED8(index)).as_bytes());
+            }
+            self.data = data;
+            self.background_color = self.background_color.map(resolve);
+            self.color_type = ColorType::RGBA8;
+            self.convert(color_type);
+        } else if self.color_type != color_type && color_type == ColorType::INDEXED8 {
+            let palette = Palette::from_image(self);
+            self.convert_with_palette(palette);
+        } else if self.color_type != color_type && (self.color_type.bits_per_pixel() < 8 || color_type.bits_per_pixel() < 8) {
+            // pixels packed in the bits of the bytes are converted one by one through the normalized channels
+            let mut image = Self {
+                data: vec![0; color_type.row_len(self.width) * self.height],
+                width: self.width,
+                height: self.height,
+                color_type,
+                background_color: None,
+                alpha_mode: self.alpha_mode,
+                palette: None,
+            };
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    image.set_unchecked((x, y), Color::from_rgba_f64(color_type, self.get_unchecked((x, y)).to_rgba_f64()));
+                }
+            }
+            self.data = image.data;
+            self.background_color = self.background_color.map(|color| Color::from_rgba_f64(color_type, color.to_rgba_f64()));
+            self.color_type = color_type;
+        } else if self.color_type != color_type && (is_packed(self.color_type) || is_packed(color_type) || self.color_type.is_float() || color_type.is_float()) {
+            // floating point and packed color types are converted through the normalized channels
+            let bytes_per_pixel = self.color_type.bytes_per_pixel();
+            let mut data = Vec::with_capacity(self.width * self.height * color_type.bytes_per_pixel());
+            for bytes in self.data.chunks_exact(bytes_per_pixel) {
+                let color = Color::from_rgba_f64(color_type, Color::from_bytes(self.color_type, bytes).to_rgba_f64());
+                data.extend_from_slice(color.as_bytes());
+            }
+            self.data = data;
+            self.background_color = self.background_color.map(|color| Color::from_rgba_f64(color_type, color.to_rgba_f64()));
+            self.color_type = color_type;
+        } else if self.color_type != color_type && (rgb_order(self.color_type).is_some() || rgb_order(color_type).is_some()) {
+            // the channels are reordered to the RGB order, converted and reordered to the requested order
+            if let Some(rgb_color_type) = rgb_order(self.color_type) {
+                reorder_channels(&mut self.data, self.color_type, true);
+                self.background_color = self.background_color.map(|color| Color::from_rgba_f64(rgb_color_type, color.to_rgba_f64()));
+                self.color_type = rgb_color_type;
+            }
+            if let Some(rgb_color_type) = rgb_order(color_type) {
+                self.convert(rgb_color_type);
+                reorder_channels(&mut self.data, color_type, false);
+                self.background_color = self.background_color.map(|color| Color::from_rgba_f64(color_type, color.to_rgba_f64()));
+                self.color_type = color_type;
+            } else {
+                self.convert(color_type);
+            }
+        } else if self.color_type != color_type {
+            self.convert_gray_rgb(color_type);
+        }
+    }
+
+    /// Converts the image between the gray and RGB color types with 8 or 16 bits per channel
+    /// ([ColorType::GRAY8], [ColorType::GRAYA8], [ColorType::GRAY16], [ColorType::GRAYA16], [ColorType::RGB8], [ColorType::RGBA8], [ColorType::RGB16] and [ColorType::RGBA16]).
+    /// The other color types are converted by [Image::convert()] through these.
+    /// # Arguments
+    /// * ```color_type``` - The color type to which the image will be converted, different from the current one.
+    fn convert_gray_rgb(&mut self, color_type: ColorType) {
+        // convert image data
+        let data = &mut self.data;
+        match self.color_type {
+            ColorType::GRAY8 => {
+                match color_type {
+                    ColorType::GRAY8 => {} // do nothing (same type)
+                    ColorType::GRAYA8 => {
+                        let original_len = data.len();
+
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(255);
                         }
-                        ColorType::RGB8 => {
-                            let original_len = data.len();
 
-                            let reserve_amount = original_len * 2;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
+                        for i in (0..original_len).rev() {
+                            data[i << 1] = data[i];
+                        }
+                        for i in (1..original_len).step_by(2) {
+                            data[i] = 255;
+                        }
+                    }
+                    ColorType::GRAY16 => {
+                        let original_len = data.len();
 
-                            for i in (0..original_len).rev() {
-                                let new_loc = i * 3;
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i];
-                                data[new_loc + 2] = data[i];
-                            }
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(0);
                         }
-                        ColorType::RGBA8 => {
-                            let original_len = data.len();
 
-                            let reserve_amount = original_len * 3;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
+                        for i in (0..original_len).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_loc = i << 1;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+                    }
+                    ColorType::GRAYA16 => {
+                        let original_len = data.len();
 
-                            for i in (0..original_len).rev() {
-                                let new_loc = i << 2;
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i];
-                                data[new_loc + 2] = data[i];
-                            }
+                        let reserve_amount = original_len * 3;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
+                        }
 
-                            for i in (3..original_len).step_by(4) {
-                                data[i] = 255;
-                            }
+                        for i in (0..original_len).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_loc = i << 2;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
                         }
-                        ColorType::RGB16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len * 5;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_loc = i * 6;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
-                            }
+
+                        for i in (2..original_len).step_by(4) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
                         }
-                        ColorType::RGBA16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len * 7;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
-
-                            for i in (0..original_len).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_loc = i << 3;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
-                            }
-
-                            for i in (6..original_len).step_by(8) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+                    }
+                    ColorType::RGB8 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len * 2;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
+                        }
+
+                        for i in (0..original_len).rev() {
+                            let new_loc = i * 3;
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i];
+                            data[new_loc + 2] = data[i];
                         }
                     }
+                    ColorType::RGBA8 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len * 3;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
+                        }
+
+                        for i in (0..original_len).rev() {
+                            let new_loc = i << 2;
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i];
+                            data[new_loc + 2] = data[i];
+                        }
+
+                        for i in (3..original_len).step_by(4) {
+                            data[i] = 255;
+                        }
+                    }
+                    ColorType::RGB16 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len * 5;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
+                        }
+
+                        for i in (0..original_len).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_loc = i * 6;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
+                        }
+                    }
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len * 7;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
+                        }
+
+                        for i in (0..original_len).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_loc = i << 3;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
+                        }
+
+                        for i in (6..original_len).step_by(8) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
+                        }
+                    }
+                    _ => unreachable!(),
                 }
-                ColorType::GRAYA8 => {
-                    match color_type {
-                        ColorType::GRAY8 => {
-                            // bit shift to the right by 1 == divide by 2
+            }
+            ColorType::GRAYA8 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        // bit shift to the right by 1 == divide by 2
 
-                            for i in (0..data.len()).step_by(2) {
-                                data[i >> 1] = data[i];
-                            }
+                        for i in (0..data.len()).step_by(2) {
+                            data[i >> 1] = data[i];
+                        }
 
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {} // do nothing (same type)
+                    ColorType::GRAY16 => {
+                        for i in (0..data.len()).step_by(2) {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            data[i] = new_val[0];
+                            data[i + 1] = new_val[1];
                         }
-                        ColorType::GRAYA8 => {} // do nothing (same type)
-                        ColorType::GRAY16 => {
-                            for i in (0..data.len()).step_by(2) {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                data[i] = new_val[0];
-                                data[i + 1] = new_val[1];
-                            }
-                        }
-                        ColorType::GRAYA16 => {
-                            let original_len = data.len();
-
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_transparency = val_u8_to_u16(data[i + 1]).to_ne_bytes();
-                                let new_loc = i << 1;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = new_transparency[0];
-                                data[new_loc + 3] = new_transparency[1];
-                            }
+                    }
+                    ColorType::GRAYA16 => {
+                        let original_len = data.len();
+
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(0);
                         }
-                        ColorType::RGB8 => {
-                            let original_len = data.len();
 
-                            let reserve_amount = original_len >> 1;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_transparency = val_u8_to_u16(data[i + 1]).to_ne_bytes();
+                            let new_loc = i << 1;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = new_transparency[0];
+                            data[new_loc + 3] = new_transparency[1];
+                        }
+                    }
+                    ColorType::RGB8 => {
+                        let original_len = data.len();
 
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_loc = i + (i >> 1);
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i];
-                                data[new_loc + 2] = data[i];
-                            }
+                        let reserve_amount = original_len >> 1;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
                         }
-                        ColorType::RGBA8 => {
-                            let original_len = data.len();
 
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(0);
-                            }
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_loc = i + (i >> 1);
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i];
+                            data[new_loc + 2] = data[i];
+                        }
+                    }
+                    ColorType::RGBA8 => {
+                        let original_len = data.len();
 
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_loc = i << 1;
-                                data[new_loc + 3] = data[i + 1];
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(0);
+                        }
 
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i];
-                                data[new_loc + 2] = data[i];
-                            }
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_loc = i << 1;
+                            data[new_loc + 3] = data[i + 1];
+
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i];
+                            data[new_loc + 2] = data[i];
                         }
-                        ColorType::RGB16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len << 1;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_loc = i * 3;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
-                            }
+                    }
+                    ColorType::RGB16 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len << 1;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
                         }
-                        ColorType::RGBA16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len * 3;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                let new_transparency = val_u8_to_u16(data[i + 1]).to_ne_bytes();
-                                let new_loc = i << 2;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
-                                data[new_loc + 6] = new_transparency[0];
-                                data[new_loc + 7] = new_transparency[1];
-                            }
+
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_loc = i * 3;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
+                        }
+                    }
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len * 3;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
+                        }
+
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            let new_transparency = val_u8_to_u16(data[i + 1]).to_ne_bytes();
+                            let new_loc = i << 2;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
+                            data[new_loc + 6] = new_transparency[0];
+                            data[new_loc + 7] = new_transparency[1];
                         }
                     }
+                    _ => unreachable!(),
                 }
-                ColorType::GRAY16 => {
-                    match color_type {
-                        ColorType::GRAY8 => {
-                            for i in (0..data.len()).step_by(2) {
-                                data[i >> 1] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                            }
+            }
+            ColorType::GRAY16 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        for i in (0..data.len()).step_by(2) {
+                            data[i >> 1] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                        }
 
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {
+                        for i in (0..data.len()).step_by(2) {
+                            data[i] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            data[i + 1] = 255;
                         }
-                        ColorType::GRAYA8 => {
-                            for i in (0..data.len()).step_by(2) {
-                                data[i] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                data[i + 1] = 255;
-                            }
+                    }
+                    ColorType::GRAY16 => {} // do nothing (same type)
+                    ColorType::GRAYA16 => {
+                        let original_len = data.len();
+
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(255);
                         }
-                        ColorType::GRAY16 => {} // do nothing (same type)
-                        ColorType::GRAYA16 => {
-                            let original_len = data.len();
 
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(255);
-                            }
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_loc = i << 1;
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                        }
 
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_loc = i << 1;
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                            }
+                        for i in (2..original_len).step_by(4) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
+                        }
+                    }
+                    ColorType::RGB8 => {
+                        let original_len = data.len();
 
-                            for i in (2..original_len).step_by(4) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+                        let reserve_amount = original_len >> 1;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
                         }
-                        ColorType::RGB8 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len >> 1;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                let new_loc = i + (i >> 1);
-                                data[new_loc] = new_val;
-                                data[new_loc + 1] = new_val;
-                                data[new_loc + 2] = new_val;
-                            }
+
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            let new_loc = i + (i >> 1);
+                            data[new_loc] = new_val;
+                            data[new_loc + 1] = new_val;
+                            data[new_loc + 2] = new_val;
                         }
-                        ColorType::RGBA8 => {
-                            let original_len = data.len();
+                    }
+                    ColorType::RGBA8 => {
+                        let original_len = data.len();
 
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(255);
-                            }
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(255);
+                        }
 
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                let new_loc = i << 1;
-                                data[new_loc] = new_val;
-                                data[new_loc + 1] = new_val;
-                                data[new_loc + 2] = new_val;
-                            }
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            let new_loc = i << 1;
+                            data[new_loc] = new_val;
+                            data[new_loc + 1] = new_val;
+                            data[new_loc + 2] = new_val;
+                        }
 
-                            for i in (3..original_len).step_by(4) {
-                                data[i] = 255;
-                            }
+                        for i in (3..original_len).step_by(4) {
+                            data[i] = 255;
                         }
-                        ColorType::RGB16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len << 1;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_loc = i * 3;
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc + 2] = data[i];
-                                data[new_loc + 3] = data[i + 1];
-                                data[new_loc + 4] = data[i];
-                                data[new_loc + 5] = data[i + 1];
-                            }
+                    }
+                    ColorType::RGB16 => {
+                        let original_len = data.len();
+
+                        let reserve_amount = original_len << 1;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
                         }
-                        ColorType::RGBA16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len * 3;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
-
-                            for i in (0..original_len).step_by(2).rev() {
-                                let new_loc = i << 2;
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc + 2] = data[i];
-                                data[new_loc + 3] = data[i + 1];
-                                data[new_loc + 4] = data[i];
-                                data[new_loc + 5] = data[i + 1];
-                            }
-
-                            for i in (6..original_len).step_by(8) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_loc = i * 3;
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc + 2] = data[i];
+                            data[new_loc + 3] = data[i + 1];
+                            data[new_loc + 4] = data[i];
+                            data[new_loc + 5] = data[i + 1];
                         }
                     }
-                }
-                ColorType::GRAYA16 => {
-                    match color_type {
-                        ColorType::GRAY8 => {
-                            // bit shift to the right by 2 == divide by 4
-                            for i in (0..data.len()).step_by(4) {
-                                data[i >> 2] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                            }
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
 
-                            data.truncate(data.len() >> 2);
-                            data.shrink_to_fit();
+                        let reserve_amount = original_len * 3;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
                         }
-                        ColorType::GRAYA8 => {
-                            for i in (0..data.len()).step_by(2) {
-                                data[i >> 1] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                            }
 
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                        for i in (0..original_len).step_by(2).rev() {
+                            let new_loc = i << 2;
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc + 2] = data[i];
+                            data[new_loc + 3] = data[i + 1];
+                            data[new_loc + 4] = data[i];
+                            data[new_loc + 5] = data[i + 1];
                         }
-                        ColorType::GRAY16 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_loc = i >> 1;
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                            }
 
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                        for i in (6..original_len).step_by(8) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            ColorType::GRAYA16 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        // bit shift to the right by 2 == divide by 4
+                        for i in (0..data.len()).step_by(4) {
+                            data[i >> 2] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
                         }
-                        ColorType::GRAYA16 => {} // do nothing (same type)
-                        ColorType::RGB8 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                let new_loc = (i >> 1) + (i >> 2);
-                                data[new_loc] = new_val;
-                                data[new_loc + 1] = new_val;
-                                data[new_loc + 2] = new_val;
-                            }
 
-                            data.truncate((data.len() >> 1) + (data.len() >> 2));
-                            data.shrink_to_fit();
+                        data.truncate(data.len() >> 2);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {
+                        for i in (0..data.len()).step_by(2) {
+                            data[i >> 1] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
                         }
-                        ColorType::RGBA8 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                let new_transparency = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
-                                data[i] = new_val;
-                                data[i + 1] = new_val;
-                                data[i + 2] = new_val;
-                                data[i + 3] = new_transparency;
-                            }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAY16 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_loc = i >> 1;
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
                         }
-                        ColorType::RGB16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len >> 1;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
-
-                            for i in (0..original_len).step_by(4).rev() {
-                                let new_loc = i + (i >> 1);
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc + 2] = data[i];
-                                data[new_loc + 3] = data[i + 1];
-                                data[new_loc + 4] = data[i];
-                                data[new_loc + 5] = data[i + 1];
-                            }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA16 => {} // do nothing (same type)
+                    ColorType::RGB8 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            let new_loc = (i >> 1) + (i >> 2);
+                            data[new_loc] = new_val;
+                            data[new_loc + 1] = new_val;
+                            data[new_loc + 2] = new_val;
+                        }
+
+                        data.truncate((data.len() >> 1) + (data.len() >> 2));
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGBA8 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_val = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            let new_transparency = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
+                            data[i] = new_val;
+                            data[i + 1] = new_val;
+                            data[i + 2] = new_val;
+                            data[i + 3] = new_transparency;
                         }
-                        ColorType::RGBA16 => {
-                            let original_len = data.len();
+                    }
+                    ColorType::RGB16 => {
+                        let original_len = data.len();
 
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(0);
-                            }
+                        let reserve_amount = original_len >> 1;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
+                        }
 
-                            for i in (0..original_len).step_by(4).rev() {
-                                let new_loc = i << 1;
-                                data[new_loc + 6] = data[i + 2];
-                                data[new_loc + 7] = data[i + 3];
+                        for i in (0..original_len).step_by(4).rev() {
+                            let new_loc = i + (i >> 1);
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc + 2] = data[i];
+                            data[new_loc + 3] = data[i + 1];
+                            data[new_loc + 4] = data[i];
+                            data[new_loc + 5] = data[i + 1];
+                        }
+                    }
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
 
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc + 2] = data[i];
-                                data[new_loc + 3] = data[i + 1];
-                                data[new_loc + 4] = data[i];
-                                data[new_loc + 5] = data[i + 1];
-                            }
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(0);
+                        }
+
+                        for i in (0..original_len).step_by(4).rev() {
+                            let new_loc = i << 1;
+                            data[new_loc + 6] = data[i + 2];
+                            data[new_loc + 7] = data[i + 3];
+
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc + 2] = data[i];
+                            data[new_loc + 3] = data[i + 1];
+                            data[new_loc + 4] = data[i];
+                            data[new_loc + 5] = data[i + 1];
                         }
                     }
+                    _ => unreachable!(),
                 }
-                ColorType::RGB8 => {
-                    match color_type {
-                        ColorType::GRAY8 => {
-                            for i in (0..data.len()).step_by(3) {
-                                data[i / 3] = average(&data[i..(i + 3)]);
-                            }
-
-                            data.truncate(data.len() / 3);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA8 => {
-                            for i in (0..data.len()).step_by(3) {
-                                let new_loc = (i / 3) << 1; // multiply by 2/3
-                                data[new_loc] = average(&data[i..(i + 3)]);
-                                data[new_loc + 1] = 255;
-                            }
-
-                            data.truncate((data.len() / 3) << 1);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAY16 => {
-                            for i in (0..data.len()).step_by(3) {
-                                let new_loc = (i / 3) << 1; // multiply by 2/3
-                                let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-
-                            data.truncate((data.len() / 3) << 1);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA16 => {
-                            let original_len = data.len();
-
-                            let reserve_amount = original_len / 3;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
-
-                            for i in (0..original_len).step_by(3).rev() {
-                                let new_loc = (i / 3) << 2; // multiply by 4/3
-                                let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-
-                            for i in (2..original_len).step_by(4) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+            }
+            ColorType::RGB8 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        for i in (0..data.len()).step_by(3) {
+                            data[i / 3] = average(&data[i..(i + 3)]);
                         }
-                        ColorType::RGB8 => {} // do nothing (same type)
-                        ColorType::RGBA8 => {
-                            let original_len = data.len();
 
-                            let reserve_amount = original_len / 3;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
+                        data.truncate(data.len() / 3);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {
+                        for i in (0..data.len()).step_by(3) {
+                            let new_loc = (i / 3) << 1; // multiply by 2/3
+                            data[new_loc] = average(&data[i..(i + 3)]);
+                            data[new_loc + 1] = 255;
+                        }
 
-                            for i in (0..original_len).step_by(3).rev() {
-                                let new_loc = (i / 3) << 2; // multiply by 4/3
-                                data[new_loc + 2] = data[i + 2];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc] = data[i];
-                            }
+                        data.truncate((data.len() / 3) << 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAY16 => {
+                        for i in (0..data.len()).step_by(3) {
+                            let new_loc = (i / 3) << 1; // multiply by 2/3
+                            let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+
+                        data.truncate((data.len() / 3) << 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA16 => {
+                        let original_len = data.len();
 
-                            for i in (3..original_len).step_by(4) {
-                                data[i] = 255;
-                            }
+                        let reserve_amount = original_len / 3;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
                         }
-                        ColorType::RGB16 => {
-                            let original_len = data.len();
 
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(0);
-                            }
+                        for i in (0..original_len).step_by(3).rev() {
+                            let new_loc = (i / 3) << 2; // multiply by 4/3
+                            let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
 
-                            for i in (0..original_len).step_by(3).rev() {
-                                let new_loc = i << 1; // multiply by 2
+                        for i in (2..original_len).step_by(4) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
+                        }
+                    }
+                    ColorType::RGB8 => {} // do nothing (same type)
+                    ColorType::RGBA8 => {
+                        let original_len = data.len();
 
-                                let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
+                        let reserve_amount = original_len / 3;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
+                        }
 
-                                let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
+                        for i in (0..original_len).step_by(3).rev() {
+                            let new_loc = (i / 3) << 2; // multiply by 4/3
+                            data[new_loc + 2] = data[i + 2];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc] = data[i];
+                        }
 
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
+                        for i in (3..original_len).step_by(4) {
+                            data[i] = 255;
                         }
-                        ColorType::RGBA16 => {
-                            let original_len = data.len();
+                    }
+                    ColorType::RGB16 => {
+                        let original_len = data.len();
 
-                            let reserve_amount = 5 * (original_len / 3);
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(255);
-                            }
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(0);
+                        }
 
-                            for i in (0..original_len).step_by(3).rev() {
-                                let new_loc = (i / 3) * 8; // multiply by 8/3
+                        for i in (0..original_len).step_by(3).rev() {
+                            let new_loc = i << 1; // multiply by 2
 
-                                let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+                    }
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
 
-                            for i in (6..original_len).step_by(8) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+                        let reserve_amount = 5 * (original_len / 3);
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(255);
+                        }
+
+                        for i in (0..original_len).step_by(3).rev() {
+                            let new_loc = (i / 3) * 8; // multiply by 8/3
+
+                            let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
+
+                            let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
+
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+
+                        for i in (6..original_len).step_by(8) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
                         }
                     }
+                    _ => unreachable!(),
                 }
-                ColorType::RGBA8 => {
-                    match color_type {
-                        ColorType::GRAY8 => {
-                            for i in (0..data.len()).step_by(4) {
-                                data[i >> 2] = average(&data[i..(i + 3)]);
-                            }
-
-                            data.truncate(data.len() >> 2);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA8 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_loc = i >> 1; // divide by 2
-                                data[new_loc] = average(&data[i..(i + 3)]);
-                                data[new_loc + 1] = data[i + 3];
-                            }
-
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAY16 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_loc = i >> 1; // divide by 2
-                                let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA16 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
-                                let new_transparency = val_u8_to_u16(data[i + 3]).to_ne_bytes();
-                                data[i] = new_val[0];
-                                data[i + 1] = new_val[1];
-                                data[i + 2] = new_transparency[0];
-                                data[i + 3] = new_transparency[1];
-                            }
+            }
+            ColorType::RGBA8 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        for i in (0..data.len()).step_by(4) {
+                            data[i >> 2] = average(&data[i..(i + 3)]);
                         }
-                        ColorType::RGB8 => {
-                            for i in (0..data.len()).step_by(4) {
-                                let new_loc = (i >> 1) + (i >> 2); // multiply by 3/4
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc + 2] = data[i + 2];
-                            }
 
-                            data.truncate((data.len() >> 1) + (data.len() >> 2));
-                            data.shrink_to_fit();
+                        data.truncate(data.len() >> 2);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_loc = i >> 1; // divide by 2
+                            data[new_loc] = average(&data[i..(i + 3)]);
+                            data[new_loc + 1] = data[i + 3];
+                        }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAY16 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_loc = i >> 1; // divide by 2
+                            let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA16 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_val = val_u8_to_u16(average(&data[i..(i + 3)])).to_ne_bytes();
+                            let new_transparency = val_u8_to_u16(data[i + 3]).to_ne_bytes();
+                            data[i] = new_val[0];
+                            data[i + 1] = new_val[1];
+                            data[i + 2] = new_transparency[0];
+                            data[i + 3] = new_transparency[1];
+                        }
+                    }
+                    ColorType::RGB8 => {
+                        for i in (0..data.len()).step_by(4) {
+                            let new_loc = (i >> 1) + (i >> 2); // multiply by 3/4
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc + 2] = data[i + 2];
                         }
-                        ColorType::RGBA8 => {} // do nothing (same type)
-                        ColorType::RGB16 => {
-                            let original_len = data.len();
 
-                            let reserve_amount = original_len >> 1;
-                            data.reserve_exact(reserve_amount);
-                            for _ in 0..reserve_amount {
-                                data.push(0);
-                            }
+                        data.truncate((data.len() >> 1) + (data.len() >> 2));
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGBA8 => {} // do nothing (same type)
+                    ColorType::RGB16 => {
+                        let original_len = data.len();
 
-                            for i in (0..original_len).step_by(4).rev() {
-                                let new_loc = i + (i >> 1); // multiply by 3/2
+                        let reserve_amount = original_len >> 1;
+                        data.reserve_exact(reserve_amount);
+                        for _ in 0..reserve_amount {
+                            data.push(0);
+                        }
+
+                        for i in (0..original_len).step_by(4).rev() {
+                            let new_loc = i + (i >> 1); // multiply by 3/2
 
-                                let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
                         }
-                        ColorType::RGBA16 => {
-                            let original_len = data.len();
+                    }
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
 
-                            data.reserve_exact(original_len);
-                            for _ in 0..original_len {
-                                data.push(0);
-                            }
+                        data.reserve_exact(original_len);
+                        for _ in 0..original_len {
+                            data.push(0);
+                        }
 
-                            for i in (0..original_len).step_by(4).rev() {
-                                let new_loc = i << 1; // multiply by 2
+                        for i in (0..original_len).step_by(4).rev() {
+                            let new_loc = i << 1; // multiply by 2
 
-                                let new_val = val_u8_to_u16(data[i + 3]).to_ne_bytes();
-                                data[new_loc + 6] = new_val[0];
-                                data[new_loc + 7] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 3]).to_ne_bytes();
+                            data[new_loc + 6] = new_val[0];
+                            data[new_loc + 7] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
-                                data[new_loc + 4] = new_val[0];
-                                data[new_loc + 5] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 2]).to_ne_bytes();
+                            data[new_loc + 4] = new_val[0];
+                            data[new_loc + 5] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
-                                data[new_loc + 2] = new_val[0];
-                                data[new_loc + 3] = new_val[1];
+                            let new_val = val_u8_to_u16(data[i + 1]).to_ne_bytes();
+                            data[new_loc + 2] = new_val[0];
+                            data[new_loc + 3] = new_val[1];
 
-                                let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
+                            let new_val = val_u8_to_u16(data[i]).to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
                         }
                     }
+                    _ => unreachable!(),
                 }
-                ColorType::RGB16 => {
-                    match color_type {
-                        ColorType::GRAY8 => {
-                            for i in (0..data.len()).step_by(6) {
-                                data[i / 6] = val_u16_to_u8(average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ]));
-                            }
-
-                            data.truncate(data.len() / 6);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA8 => {
-                            for i in (0..data.len()).step_by(6) {
-                                let new_loc = i / 3;
-                                data[new_loc] = val_u16_to_u8(average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ]));
-                                data[new_loc + 1] = 255;
-                            }
-
-                            data.truncate(data.len() / 3);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAY16 => {
-                            for i in (0..data.len()).step_by(6) {
-                                let new_val = average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ])
-                                    .to_ne_bytes();
-                                let new_loc = i / 3;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-
-                            data.truncate(data.len() / 3);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA16 => {
-                            for i in (0..data.len()).step_by(6) {
-                                let new_val = average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ])
-                                    .to_ne_bytes();
-                                let new_loc = (i / 3) << 1;
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = 255;
-                                data[new_loc + 3] = 255;
-                            }
-
-                            data.truncate((data.len() / 3) << 1);
-                            data.shrink_to_fit();
+            }
+            ColorType::RGB16 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        for i in (0..data.len()).step_by(6) {
+                            data[i / 6] = val_u16_to_u8(average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ]));
+                        }
+
+                        data.truncate(data.len() / 6);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {
+                        for i in (0..data.len()).step_by(6) {
+                            let new_loc = i / 3;
+                            data[new_loc] = val_u16_to_u8(average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ]));
+                            data[new_loc + 1] = 255;
+                        }
+
+                        data.truncate(data.len() / 3);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAY16 => {
+                        for i in (0..data.len()).step_by(6) {
+                            let new_val = average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ])
+                                .to_ne_bytes();
+                            let new_loc = i / 3;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+
+                        data.truncate(data.len() / 3);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA16 => {
+                        for i in (0..data.len()).step_by(6) {
+                            let new_val = average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ])
+                                .to_ne_bytes();
+                            let new_loc = (i / 3) << 1;
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = 255;
+                            data[new_loc + 3] = 255;
+                        }
+
+                        data.truncate((data.len() / 3) << 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGB8 => {
+                        for i in (0..data.len()).step_by(2) {
+                            data[i >> 1] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                        }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGBA8 => {
+                        for i in (0..data.len()).step_by(6) {
+                            let new_loc = (i / 3) << 1; // multiply by 2/3
+                            data[new_loc] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
+                            data[new_loc + 2] = val_u16_to_u8(u16::from_ne_bytes([data[i + 4], data[i + 5]]));
+                            data[new_loc + 3] = 255;
                         }
-                        ColorType::RGB8 => {
-                            for i in (0..data.len()).step_by(2) {
-                                data[i >> 1] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                            }
 
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                        data.truncate((data.len() / 3) << 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGB16 => {} // do nothing (same type)
+                    ColorType::RGBA16 => {
+                        let original_len = data.len();
+
+                        let reserve_len = original_len / 3;
+                        data.reserve_exact(reserve_len);
+                        for _ in 0..reserve_len {
+                            data.push(255);
                         }
-                        ColorType::RGBA8 => {
-                            for i in (0..data.len()).step_by(6) {
-                                let new_loc = (i / 3) << 1; // multiply by 2/3
-                                data[new_loc] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
-                                data[new_loc + 2] = val_u16_to_u8(u16::from_ne_bytes([data[i + 4], data[i + 5]]));
-                                data[new_loc + 3] = 255;
-                            }
 
-                            data.truncate((data.len() / 3) << 1);
-                            data.shrink_to_fit();
+                        for i in (0..original_len).step_by(6).rev() {
+                            let new_loc = (i / 3) << 2; // multiply by 4/3
+                            data[new_loc + 5] = data[i + 5];
+                            data[new_loc + 4] = data[i + 4];
+                            data[new_loc + 3] = data[i + 3];
+                            data[new_loc + 2] = data[i + 2];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc] = data[i];
+                        }
+
+                        for i in (6..original_len).step_by(8) {
+                            data[i] = 255;
+                            data[i + 1] = 255;
+                        }
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            ColorType::RGBA16 => {
+                match color_type {
+                    ColorType::GRAY8 => {
+                        for i in (0..data.len()).step_by(8) {
+                            data[i >> 3] = val_u16_to_u8(average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ]));
+                        }
+
+                        data.truncate(data.len() >> 3);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA8 => {
+                        for i in (0..data.len()).step_by(8) {
+                            let new_loc = i >> 2; // divide by 4
+                            data[new_loc] = val_u16_to_u8(average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ]));
+                            data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 6], data[i + 7]]));
+                        }
+
+                        data.truncate(data.len() >> 2);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAY16 => {
+                        for i in (0..data.len()).step_by(8) {
+                            let new_loc = i >> 2; // divide by 4
+                            let new_val = average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ])
+                                .to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                        }
+
+                        data.truncate(data.len() >> 2);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::GRAYA16 => {
+                        for i in (0..data.len()).step_by(8) {
+                            let new_loc = i >> 1; // divide by 2
+                            let new_val = average(&[
+                                u16::from_ne_bytes([data[i], data[i + 1]]),
+                                u16::from_ne_bytes([data[i + 2], data[i + 3]]),
+                                u16::from_ne_bytes([data[i + 4], data[i + 5]]),
+                            ])
+                                .to_ne_bytes();
+                            data[new_loc] = new_val[0];
+                            data[new_loc + 1] = new_val[1];
+                            data[new_loc + 2] = data[i + 6];
+                            data[new_loc + 3] = data[i + 7];
+                        }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGB8 => {
+                        for i in (0..data.len()).step_by(8) {
+                            let new_loc = (i >> 3) + (i >> 2); // multiply by 3/8
+                            data[new_loc] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
+                            data[new_loc + 2] = val_u16_to_u8(u16::from_ne_bytes([data[i + 4], data[i + 5]]));
+                        }
+
+                        data.truncate((data.len() >> 3) + (data.len() >> 2));
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGBA8 => {
+                        for i in (0..data.len()).step_by(8) {
+                            let new_loc = i >> 1; // divide by 2
+                            data[new_loc] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
+                            data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
+                            data[new_loc + 2] = val_u16_to_u8(u16::from_ne_bytes([data[i + 4], data[i + 5]]));
+                            data[new_loc + 3] = val_u16_to_u8(u16::from_ne_bytes([data[i + 6], data[i + 7]]));
+                        }
+
+                        data.truncate(data.len() >> 1);
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGB16 => {
+                        for i in (0..data.len()).step_by(8) {
+                            let new_loc = (i >> 1) + (i >> 2); // multiply by 3/4
+                            data[new_loc] = data[i];
+                            data[new_loc + 1] = data[i + 1];
+                            data[new_loc + 2] = data[i + 2];
+                            data[new_loc + 3] = data[i + 3];
+                            data[new_loc + 4] = data[i + 4];
+                            data[new_loc + 5] = data[i + 5];
+                        }
+
+                        data.truncate((data.len() >> 1) + (data.len() >> 2));
+                        data.shrink_to_fit();
+                    }
+                    ColorType::RGBA16 => {} // do nothing (same type)
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        // convert background data
+        if let Some(current_color) = &mut self.background_color {
+            match *current_color {
+                Color::GRAY8(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => {} // do nothing (same type)
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([color, 255]),
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(color)),
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(color), 65535]),
+                        ColorType::RGB8 => *current_color = Color::RGB8([color, color, color]),
+                        ColorType::RGBA8 => *current_color = Color::RGBA8([color, color, color, 255]),
+                        ColorType::RGB16 => {
+                            let new_val = val_u8_to_u16(color);
+                            *current_color = Color::RGB16([new_val, new_val, new_val])
                         }
-                        ColorType::RGB16 => {} // do nothing (same type)
                         ColorType::RGBA16 => {
-                            let original_len = data.len();
-
-                            let reserve_len = original_len / 3;
-                            data.reserve_exact(reserve_len);
-                            for _ in 0..reserve_len {
-                                data.push(255);
-                            }
-
-                            for i in (0..original_len).step_by(6).rev() {
-                                let new_loc = (i / 3) << 2; // multiply by 4/3
-                                data[new_loc + 5] = data[i + 5];
-                                data[new_loc + 4] = data[i + 4];
-                                data[new_loc + 3] = data[i + 3];
-                                data[new_loc + 2] = data[i + 2];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc] = data[i];
-                            }
-
-                            for i in (6..original_len).step_by(8) {
-                                data[i] = 255;
-                                data[i + 1] = 255;
-                            }
+                            let new_val = val_u8_to_u16(color);
+                            *current_color = Color::RGBA16([new_val, new_val, new_val, 65535])
                         }
+                        _ => unreachable!(),
                     }
                 }
-                ColorType::RGBA16 => {
+                Color::GRAYA8(color) => {
                     match color_type {
-                        ColorType::GRAY8 => {
-                            for i in (0..data.len()).step_by(8) {
-                                data[i >> 3] = val_u16_to_u8(average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ]));
-                            }
-
-                            data.truncate(data.len() >> 3);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA8 => {
-                            for i in (0..data.len()).step_by(8) {
-                                let new_loc = i >> 2; // divide by 4
-                                data[new_loc] = val_u16_to_u8(average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ]));
-                                data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 6], data[i + 7]]));
-                            }
-
-                            data.truncate(data.len() >> 2);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAY16 => {
-                            for i in (0..data.len()).step_by(8) {
-                                let new_loc = i >> 2; // divide by 4
-                                let new_val = average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ])
-                                    .to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                            }
-
-                            data.truncate(data.len() >> 2);
-                            data.shrink_to_fit();
-                        }
-                        ColorType::GRAYA16 => {
-                            for i in (0..data.len()).step_by(8) {
-                                let new_loc = i >> 1; // divide by 2
-                                let new_val = average(&[
-                                    u16::from_ne_bytes([data[i], data[i + 1]]),
-                                    u16::from_ne_bytes([data[i + 2], data[i + 3]]),
-                                    u16::from_ne_bytes([data[i + 4], data[i + 5]]),
-                                ])
-                                    .to_ne_bytes();
-                                data[new_loc] = new_val[0];
-                                data[new_loc + 1] = new_val[1];
-                                data[new_loc + 2] = data[i + 6];
-                                data[new_loc + 3] = data[i + 7];
-                            }
-
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(color[0]),
+                        ColorType::GRAYA8 => {} // do nothing (same type)
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(color[0])),
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1])]),
+                        ColorType::RGB8 => *current_color = Color::RGB8([color[0], color[0], color[0]]),
+                        ColorType::RGBA8 => *current_color = Color::RGBA8([color[0], color[0], color[0], color[1]]),
+                        ColorType::RGB16 => {
+                            let new_val = val_u8_to_u16(color[0]);
+                            *current_color = Color::RGB16([new_val, new_val, new_val])
+                        }
+                        ColorType::RGBA16 => {
+                            let new_val = val_u8_to_u16(color[0]);
+                            *current_color = Color::RGBA16([new_val, new_val, new_val, val_u8_to_u16(color[1])])
                         }
+                        _ => unreachable!(),
+                    }
+                }
+                Color::GRAY16(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(color)),
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(color), 255]),
+                        ColorType::GRAY16 => {} // do nothing (same type)
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([color, 65535]),
                         ColorType::RGB8 => {
-                            for i in (0..data.len()).step_by(8) {
-                                let new_loc = (i >> 3) + (i >> 2); // multiply by 3/8
-                                data[new_loc] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
-                                data[new_loc + 2] = val_u16_to_u8(u16::from_ne_bytes([data[i + 4], data[i + 5]]));
-                            }
-
-                            data.truncate((data.len() >> 3) + (data.len() >> 2));
-                            data.shrink_to_fit();
+                            let new_val = val_u16_to_u8(color);
+                            *current_color = Color::RGB8([new_val, new_val, new_val])
                         }
                         ColorType::RGBA8 => {
-                            for i in (0..data.len()).step_by(8) {
-                                let new_loc = i >> 1; // divide by 2
-                                data[new_loc] = val_u16_to_u8(u16::from_ne_bytes([data[i], data[i + 1]]));
-                                data[new_loc + 1] = val_u16_to_u8(u16::from_ne_bytes([data[i + 2], data[i + 3]]));
-                                data[new_loc + 2] = val_u16_to_u8(u16::from_ne_bytes([data[i + 4], data[i + 5]]));
-                                data[new_loc + 3] = val_u16_to_u8(u16::from_ne_bytes([data[i + 6], data[i + 7]]));
-                            }
-
-                            data.truncate(data.len() >> 1);
-                            data.shrink_to_fit();
+                            let new_val = val_u16_to_u8(color);
+                            *current_color = Color::RGBA8([new_val, new_val, new_val, 255])
                         }
-                        ColorType::RGB16 => {
-                            for i in (0..data.len()).step_by(8) {
-                                let new_loc = (i >> 1) + (i >> 2); // multiply by 3/4
-                                data[new_loc] = data[i];
-                                data[new_loc + 1] = data[i + 1];
-                                data[new_loc + 2] = data[i + 2];
-                                data[new_loc + 3] = data[i + 3];
-                                data[new_loc + 4] = data[i + 4];
-                                data[new_loc + 5] = data[i + 5];
-                            }
-
-                            data.truncate((data.len() >> 1) + (data.len() >> 2));
-                            data.shrink_to_fit();
+                        ColorType::RGB16 => *current_color = Color::RGB16([color, color, color]),
+                        ColorType::RGBA16 => *current_color = Color::RGBA16([color, color, color, 65535]),
+                        _ => unreachable!(),
+                    }
+                }
+                Color::GRAYA16(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(color[0])),
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1])]),
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(color[0]),
+                        ColorType::GRAYA16 => {} // do nothing (same type)
+                        ColorType::RGB8 => {
+                            let new_val = val_u16_to_u8(color[0]);
+                            *current_color = Color::RGB8([new_val, new_val, new_val])
                         }
-                        ColorType::RGBA16 => {} // do nothing (same type)
+                        ColorType::RGBA8 => {
+                            let new_val = val_u16_to_u8(color[0]);
+                            *current_color = Color::RGBA8([new_val, new_val, new_val, val_u16_to_u8(color[1])])
+                        }
+                        ColorType::RGB16 => *current_color = Color::RGB16([color[0], color[0], color[0]]),
+                        ColorType::RGBA16 => *current_color = Color::RGBA16([color[0], color[0], color[0], color[1]]),
+                        _ => unreachable!(),
                     }
                 }
-            }
-
-            // convert background data
-            if let Some(current_color) = &mut self.background_color {
-                match *current_color {
-                    Color::GRAY8(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => {} // do nothing (same type)
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([color, 255]),
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(color)),
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(color), 65535]),
-                            ColorType::RGB8 => *current_color = Color::RGB8([color, color, color]),
-                            ColorType::RGBA8 => *current_color = Color::RGBA8([color, color, color, 255]),
-                            ColorType::RGB16 => {
-                                let new_val = val_u8_to_u16(color);
-                                *current_color = Color::RGB16([new_val, new_val, new_val])
-                            }
-                            ColorType::RGBA16 => {
-                                let new_val = val_u8_to_u16(color);
-                                *current_color = Color::RGBA16([new_val, new_val, new_val, 65535])
-                            }
-                        }
-                    }
-                    Color::GRAYA8(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(color[0]),
-                            ColorType::GRAYA8 => {} // do nothing (same type)
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(color[0])),
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1])]),
-                            ColorType::RGB8 => *current_color = Color::RGB8([color[0], color[0], color[0]]),
-                            ColorType::RGBA8 => *current_color = Color::RGBA8([color[0], color[0], color[0], color[1]]),
-                            ColorType::RGB16 => {
-                                let new_val = val_u8_to_u16(color[0]);
-                                *current_color = Color::RGB16([new_val, new_val, new_val])
-                            }
-                            ColorType::RGBA16 => {
-                                let new_val = val_u8_to_u16(color[0]);
-                                *current_color = Color::RGBA16([new_val, new_val, new_val, val_u8_to_u16(color[1])])
-                            }
-                        }
-                    }
-                    Color::GRAY16(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(color)),
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(color), 255]),
-                            ColorType::GRAY16 => {} // do nothing (same type)
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([color, 65535]),
-                            ColorType::RGB8 => {
-                                let new_val = val_u16_to_u8(color);
-                                *current_color = Color::RGB8([new_val, new_val, new_val])
-                            }
-                            ColorType::RGBA8 => {
-                                let new_val = val_u16_to_u8(color);
-                                *current_color = Color::RGBA8([new_val, new_val, new_val, 255])
-                            }
-                            ColorType::RGB16 => *current_color = Color::RGB16([color, color, color]),
-                            ColorType::RGBA16 => *current_color = Color::RGBA16([color, color, color, 65535]),
-                        }
-                    }
-                    Color::GRAYA16(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(color[0])),
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1])]),
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(color[0]),
-                            ColorType::GRAYA16 => {} // do nothing (same type)
-                            ColorType::RGB8 => {
-                                let new_val = val_u16_to_u8(color[0]);
-                                *current_color = Color::RGB8([new_val, new_val, new_val])
-                            }
-                            ColorType::RGBA8 => {
-                                let new_val = val_u16_to_u8(color[0]);
-                                *current_color = Color::RGBA8([new_val, new_val, new_val, val_u16_to_u8(color[1])])
-                            }
-                            ColorType::RGB16 => *current_color = Color::RGB16([color[0], color[0], color[0]]),
-                            ColorType::RGBA16 => *current_color = Color::RGBA16([color[0], color[0], color[0], color[1]]),
-                        }
-                    }
-                    Color::RGB8(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(average(&color as &[u8])),
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([average(&color as &[u8]), 255]),
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(average(&color as &[u8]))),
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(average(&color as &[u8])), 65535]),
-                            ColorType::RGB8 => {} // do nothing (same type)
-                            ColorType::RGBA8 => *current_color = Color::RGBA8([color[0], color[1], color[2], 255]),
-                            ColorType::RGB16 => *current_color = Color::RGB16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1]), val_u8_to_u16(color[2])]),
-                            ColorType::RGBA16 => {
-                                *current_color = Color::RGBA16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1]), val_u8_to_u16(color[2]), 65535])
-                            }
-                        }
-                    }
-                    Color::RGBA8(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(average(&color[..3])),
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([average(&color[..3]), color[3]]),
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(average(&color[..3]))),
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(average(&color[..3])), val_u8_to_u16(color[3])]),
-                            ColorType::RGB8 => *current_color = Color::RGB8([color[0], color[1], color[2]]),
-                            ColorType::RGBA8 => {} // do nothing (same type)
-                            ColorType::RGB16 => *current_color = Color::RGB16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1]), val_u8_to_u16(color[2])]),
-                            ColorType::RGBA16 => {
-                                *current_color = Color::RGBA16([
-                                    val_u8_to_u16(color[0]),
-                                    val_u8_to_u16(color[1]),
-                                    val_u8_to_u16(color[2]),
-                                    val_u8_to_u16(color[3]),
-                                ])
-                            }
-                        }
-                    }
-                    Color::RGB16(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(average(&color as &[u16]))),
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(average(&color as &[u16])), 255]),
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(average(&color as &[u16])),
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([average(&color as &[u16]), 65535]),
-                            ColorType::RGB8 => *current_color = Color::RGB8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1]), val_u16_to_u8(color[2])]),
-                            ColorType::RGBA8 => {
-                                *current_color = Color::RGBA8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1]), val_u16_to_u8(color[2]), 255])
-                            }
-                            ColorType::RGB16 => {} // do nothing (same type)
-                            ColorType::RGBA16 => *current_color = Color::RGBA16([color[0], color[1], color[2], 65535]),
-                        }
-                    }
-                    Color::RGBA16(color) => {
-                        match color_type {
-                            ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(average(&color[..3]))),
-                            ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(average(&color[..3])), val_u16_to_u8(color[3])]),
-                            ColorType::GRAY16 => *current_color = Color::GRAY16(average(&color[..3])),
-                            ColorType::GRAYA16 => *current_color = Color::GRAYA16([average(&color[..3]), color[3]]),
-                            ColorType::RGB8 => *current_color = Color::RGB8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1]), val_u16_to_u8(color[2])]),
-                            ColorType::RGBA8 => {
-                                *current_color = Color::RGBA8([
-                                    val_u16_to_u8(color[0]),
-                                    val_u16_to_u8(color[1]),
-                                    val_u16_to_u8(color[2]),
-                                    val_u16_to_u8(color[3]),
-                                ])
-                            }
-                            ColorType::RGB16 => *current_color = Color::RGB16([color[0], color[1], color[2]]),
-                            ColorType::RGBA16 => {} // do nothing (same type)
+                Color::RGB8(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(average(&color as &[u8])),
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([average(&color as &[u8]), 255]),
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(average(&color as &[u8]))),
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(average(&color as &[u8])), 65535]),
+                        ColorType::RGB8 => {} // do nothing (same type)
+                        ColorType::RGBA8 => *current_color = Color::RGBA8([color[0], color[1], color[2], 255]),
+                        ColorType::RGB16 => *current_color = Color::RGB16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1]), val_u8_to_u16(color[2])]),
+                        ColorType::RGBA16 => {
+                            *current_color = Color::RGBA16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1]), val_u8_to_u16(color[2]), 65535])
                         }
+                        _ => unreachable!(),
                     }
                 }
+                Color::RGBA8(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(average(&color[..3])),
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([average(&color[..3]), color[3]]),
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(val_u8_to_u16(average(&color[..3]))),
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([val_u8_to_u16(average(&color[..3])), val_u8_to_u16(color[3])]),
+                        ColorType::RGB8 => *current_color = Color::RGB8([color[0], color[1], color[2]]),
+                        ColorType::RGBA8 => {} // do nothing (same type)
+                        ColorType::RGB16 => *current_color = Color::RGB16([val_u8_to_u16(color[0]), val_u8_to_u16(color[1]), val_u8_to_u16(color[2])]),
+                        ColorType::RGBA16 => {
+                            *current_color = Color::RGBA16([
+                                val_u8_to_u16(color[0]),
+                                val_u8_to_u16(color[1]),
+                                val_u8_to_u16(color[2]),
+                                val_u8_to_u16(color[3]),
+                            ])
+                        }
+                        _ => unreachable!(),
+                    }
+                }
+                Color::RGB16(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(average(&color as &[u16]))),
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(average(&color as &[u16])), 255]),
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(average(&color as &[u16])),
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([average(&color as &[u16]), 65535]),
+                        ColorType::RGB8 => *current_color = Color::RGB8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1]), val_u16_to_u8(color[2])]),
+                        ColorType::RGBA8 => {
+                            *current_color = Color::RGBA8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1]), val_u16_to_u8(color[2]), 255])
+                        }
+                        ColorType::RGB16 => {} // do nothing (same type)
+                        ColorType::RGBA16 => *current_color = Color::RGBA16([color[0], color[1], color[2], 65535]),
+                        _ => unreachable!(),
+                    }
+                }
+                Color::RGBA16(color) => {
+                    match color_type {
+                        ColorType::GRAY8 => *current_color = Color::GRAY8(val_u16_to_u8(average(&color[..3]))),
+                        ColorType::GRAYA8 => *current_color = Color::GRAYA8([val_u16_to_u8(average(&color[..3])), val_u16_to_u8(color[3])]),
+                        ColorType::GRAY16 => *current_color = Color::GRAY16(average(&color[..3])),
+                        ColorType::GRAYA16 => *current_color = Color::GRAYA16([average(&color[..3]), color[3]]),
+                        ColorType::RGB8 => *current_color = Color::RGB8([val_u16_to_u8(color[0]), val_u16_to_u8(color[1]), val_u16_to_u8(color[2])]),
+                        ColorType::RGBA8 => {
+                            *current_color = Color::RGBA8([
+                                val_u16_to_u8(color[0]),
+                                val_u16_to_u8(color[1]),
+                                val_u16_to_u8(color[2]),
+                                val_u16_to_u8(color[3]),
+                            ])
+                        }
+                        ColorType::RGB16 => *current_color = Color::RGB16([color[0], color[1], color[2]]),
+                        ColorType::RGBA16 => {} // do nothing (same type)
+                        _ => unreachable!(),
+                    }
+                }
+                _ => unreachable!(),
             }
+        }
 
-            // change image type
-            self.color_type = color_type;
+        // change image type
+        self.color_type = color_type;
+    }
+
+    /// Returns the pixels of the image as normalized ```[red, green, blue, alpha]``` values with premultiplied alpha.
+    /// The pixels are in the order of the pixel data, starting with the top row.
+    /// # Returns
+    /// * The normalized, premultiplied pixels.
+    pub(crate) fn to_rgba_f64_premultiplied(&self) -> Vec<[f64; 4]> {
+        // the pixels are decoded one by one, so packed and indexed pixels aren't converted as a whole
+        self.pixels()
+            .map(|color| match &self.palette {
+                Some(palette) => palette.resolve(color),
+                None => color,
+            })
+            .map(|color| premultiply(color.to_rgba_f64()))
+            .collect()
+    }
+
+    /// Creates a new image from normalized ```[red, green, blue, alpha]``` pixels with premultiplied alpha.
+    /// Values out of the valid range are clamped.
+    /// Indexed images get a palette of their colors (see [Image::convert()]).
+    /// # Arguments
+    /// * ```width``` - The width of the image.
+    /// * ```height``` - The height of the image.
+    /// * ```color_type``` - The color type of the image.
+    /// * ```alpha_mode``` - The alpha mode of the image.
+    /// * ```pixels``` - The pixels in the order of the pixel data, starting with the top row.
+    /// # Returns
+    /// * The new image.
+    pub(crate) fn from_rgba_f64_premultiplied(width: usize, height: usize, color_type: ColorType, alpha_mode: AlphaMode, pixels: &[[f64; 4]]) -> Self {
+        if color_type.bits_per_pixel() < 8 || color_type == ColorType::INDEXED8 {
+            let detour = if color_type == ColorType::INDEXED8 { ColorType::RGBA8 } else { ColorType::GRAY8 };
+            let mut image = Self::from_rgba_f64_premultiplied(width, height, detour, alpha_mode, pixels);
+            image.convert(color_type);
+            return image;
+        }
+        let mut image = Self {
+            data: Vec::with_capacity(width * height * color_type.bytes_per_pixel()),
+            width,
+            height,
+            color_type,
+            background_color: None,
+            alpha_mode,
+            palette: None,
+        };
+        for pixel in pixels {
+            // alpha left over from rounding errors of the filters is dropped, as it would give random colors
+            let alpha = if pixel[3] <= f64::EPSILON { 0.0 } else { pixel[3].min(1.0) };
+            // floating point colors keep their high dynamic range
+            let max = if color_type.is_float() { f64::INFINITY } else { alpha };
+            let premultiplied = [pixel[0].clamp(0.0, max), pixel[1].clamp(0.0, max), pixel[2].clamp(0.0, max), alpha];
+            let mut straight = unpremultiply(premultiplied);
+            if !color_type.has_alpha() {
+                // flatten onto black, as the image can't store transparency
+                straight = premultiplied;
+                straight[3] = 1.0;
+            }
+            let color = image.encode_color(Color::from_rgba_f64(color_type, straight));
+            image.data.extend_from_slice(color.as_bytes());
+        }
+        image
+    }
+
+    /// Creates a new image with the color type, the alpha mode and the palette of this image
+    /// from normalized ```[red, green, blue, alpha]``` pixels with premultiplied alpha.
+    /// The pixels of indexed images are snapped to the nearest colors of the palette.
+    /// # Arguments
+    /// * ```width``` - The width of the new image.
+    /// * ```height``` - The height of the new image.
+    /// * ```pixels``` - The pixels in the order of the pixel data, starting with the top row.
+    /// # Returns
+    /// * The new image.
+    pub(crate) fn derive_from_rgba_f64_premultiplied(&self, width: usize, height: usize, pixels: &[[f64; 4]]) -> Self {
+        match &self.palette {
+            Some(palette) => {
+                let mut image = Self::from_rgba_f64_premultiplied(width, height, ColorType::RGBA8, self.alpha_mode, pixels);
+                image.convert_with_palette(palette.clone());
+                image
+            }
+            None => Self::from_rgba_f64_premultiplied(width, height, self.color_type, self.alpha_mode, pixels),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::colors::{Color, ColorType};
+    use crate::colors::{AlphaMode, Color, ColorType};
     use crate::image::Image;
+    use crate::palette::Palette;
 
     fn conversion_test(img1_colors: (Color, Color), img2_colors: (Color, Color)) {
         assert_eq!(ColorType::from(img1_colors.0), ColorType::from(img1_colors.1));
@@ -1227,6 +1422,141 @@ mod tests {
         assert_eq!(img1.color_type, new_color_type);
     }
 
+    #[test]
+    fn premultiplied_conversion() {
+        let mut image = Image::new(10, 10, Color::RGBA8([200, 100, 0, 128]));
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        image.convert(ColorType::GRAYA16);
+        assert_eq!(image.alpha_mode(), AlphaMode::Premultiplied);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAYA16([25_699, 32_896]));
+        image.convert(ColorType::RGB8);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGB8([100, 100, 100]));
+    }
+
+    #[test]
+    fn float_conversion() {
+        assert!(Image::is_lossless_conversion(ColorType::RGBA16, ColorType::RGBA32F));
+        assert!(Image::is_lossless_conversion(ColorType::GRAY8, ColorType::RGB32F));
+        assert!(!Image::is_lossless_conversion(ColorType::RGB32F, ColorType::RGB16));
+        assert!(!Image::is_lossless_conversion(ColorType::RGBA32F, ColorType::RGB32F));
+        assert!(!Image::is_lossless_conversion(ColorType::RGB32F, ColorType::GRAY32F));
+
+        // integer values survive the round trip through floating point
+        conversion_test((Color::RGBA8([0, 51, 255, 102]), Color::RGBA8([10, 20, 30, 40])), (Color::RGBA32F([0.0, 0.2, 1.0, 0.4]), Color::RGBA32F([10.0 / 255.0, 20.0 / 255.0, 30.0 / 255.0, 40.0 / 255.0])));
+        let mut image = Image::new(10, 10, Color::RGBA16([1, 2_000, 65_535, 30_000]));
+        image.convert(ColorType::RGBA32F);
+        image.convert(ColorType::RGBA16);
+        assert_eq!(image.get((5, 5)).unwrap(), Color::RGBA16([1, 2_000, 65_535, 30_000]));
+
+        // values out of the integer range are clamped
+        let mut image = Image::new(10, 10, Color::RGB32F([2.5, 0.5, 0.0]));
+        image.convert(ColorType::GRAY32F);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAY32F(1.0));
+        image.convert(ColorType::GRAY8);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAY8(255));
+    }
+
+    #[test]
+    fn channel_order_conversion() {
+        assert!(Image::is_lossless_conversion(ColorType::RGBA8, ColorType::BGRA8));
+        assert!(Image::is_lossless_conversion(ColorType::ARGB8, ColorType::RGBA16));
+        assert!(Image::is_lossless_conversion(ColorType::GRAY8, ColorType::BGR8));
+        assert!(!Image::is_lossless_conversion(ColorType::ARGB8, ColorType::BGR8));
+        assert!(!Image::is_lossless_conversion(ColorType::BGR16, ColorType::RGB8));
+
+        conversion_test((Color::RGBA8([1, 2, 3, 4]), Color::RGBA8([5, 6, 7, 8])), (Color::BGRA8([3, 2, 1, 4]), Color::BGRA8([7, 6, 5, 8])));
+        conversion_test((Color::RGBA8([1, 2, 3, 4]), Color::RGBA8([5, 6, 7, 8])), (Color::ARGB8([4, 1, 2, 3]), Color::ARGB8([8, 5, 6, 7])));
+        conversion_test((Color::BGRA8([3, 2, 1, 4]), Color::BGRA8([7, 6, 5, 8])), (Color::ARGB8([4, 1, 2, 3]), Color::ARGB8([8, 5, 6, 7])));
+        conversion_test((Color::ARGB8([4, 1, 2, 3]), Color::ARGB8([8, 5, 6, 7])), (Color::RGB8([1, 2, 3]), Color::RGB8([5, 6, 7])));
+        conversion_test((Color::GRAY8(120), Color::GRAY8(140)), (Color::BGR16([30_840, 30_840, 30_840]), Color::BGR16([35_980, 35_980, 35_980])));
+        conversion_test(
+            (Color::BGR16([1, 2, 3]), Color::BGR16([4, 5, 6])),
+            (Color::ARGB16([65_535, 3, 2, 1]), Color::ARGB16([65_535, 6, 5, 4])),
+        );
+        conversion_test((Color::ARGB16([65_535, 0, 65_535, 0]), Color::ARGB16([0, 0, 0, 65_535])), (Color::BGRA8([0, 255, 0, 255]), Color::BGRA8([255, 0, 0, 0])));
+
+        // the bytes are ready for consumers expecting the channel order
+        let mut image = Image::new(2, 1, Color::RGBA8([10, 20, 30, 255]));
+        image.convert(ColorType::BGRA8);
+        assert_eq!(image.as_bytes(), &[30, 20, 10, 255, 30, 20, 10, 255]);
+        image.convert(ColorType::RGBA32F);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA32F([10.0 / 255.0, 20.0 / 255.0, 30.0 / 255.0, 1.0]));
+    }
+
+    #[test]
+    fn packed_conversion() {
+        assert!(Image::is_lossless_conversion(ColorType::GRAY1, ColorType::GRAY4));
+        assert!(Image::is_lossless_conversion(ColorType::RGB332, ColorType::RGB565));
+        assert!(Image::is_lossless_conversion(ColorType::RGB565, ColorType::RGB8));
+        assert!(!Image::is_lossless_conversion(ColorType::RGB8, ColorType::RGB565));
+        assert!(!Image::is_lossless_conversion(ColorType::GRAY4, ColorType::GRAY1));
+        assert!(!Image::is_lossless_conversion(ColorType::RGB565, ColorType::GRAY4));
+
+        conversion_test((Color::RGB8([255, 0, 0]), Color::RGB8([0, 130, 255])), (Color::RGB565(0xF800), Color::RGB565(0x041F)));
+        conversion_test((Color::RGB8([255, 0, 0]), Color::RGB8([0, 130, 255])), (Color::RGB332(0xE0), Color::RGB332(0x13)));
+        conversion_test((Color::RGB565(0xF800), Color::RGB565(0x07E0)), (Color::RGBA8([255, 0, 0, 255]), Color::RGBA8([0, 255, 0, 255])));
+        conversion_test((Color::GRAY8(0), Color::GRAY8(200)), (Color::GRAY1(0), Color::GRAY1(1)));
+        conversion_test((Color::GRAY1(1), Color::GRAY1(0)), (Color::GRAY4(15), Color::GRAY4(0)));
+        conversion_test((Color::GRAY4(5), Color::GRAY4(15)), (Color::RGB16([21_845, 21_845, 21_845]), Color::RGB16([65_535, 65_535, 65_535])));
+        conversion_test((Color::RGBA8([255, 255, 255, 0]), Color::RGBA8([17, 17, 17, 255])), (Color::GRAY4(15), Color::GRAY4(1)));
+
+        // packed images convert to the bytes of the framebuffer
+        let mut image = Image::new(3, 2, Color::RGB8([0, 0, 0]));
+        image.set((1, 1), Color::RGB8([255, 255, 255])).unwrap();
+        image.convert(ColorType::GRAY1);
+        assert_eq!(image.as_bytes(), &[0b0100_0000, 0]);
+        image.convert(ColorType::GRAY8);
+        assert_eq!(image.as_bytes(), &[0, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn indexed_conversion() {
+        assert!(Image::is_lossless_conversion(ColorType::INDEXED8, ColorType::RGBA8));
+        assert!(Image::is_lossless_conversion(ColorType::INDEXED8, ColorType::RGBA16));
+        assert!(!Image::is_lossless_conversion(ColorType::INDEXED8, ColorType::RGB8));
+        assert!(!Image::is_lossless_conversion(ColorType::GRAY1, ColorType::INDEXED8));
+
+        // the palette is made of the colors of the image
+        let mut image = Image::new(4, 3, Color::RGB8([10, 20, 30]));
+        image.set((1, 2), Color::RGB8([200, 0, 0])).unwrap();
+        let original = image.clone();
+        image.convert(ColorType::INDEXED8);
+        assert_eq!(image.palette().unwrap().len(), 2);
+        assert_eq!(image.palette().unwrap().get(1), Some(Color::RGBA8([200, 0, 0, 255])));
+        assert_eq!(image.get((1, 2)).unwrap(), Color::INDEXED8(1));
+        assert_eq!(image.background_color(), Some(Color::INDEXED8(0)));
+        image.convert(ColorType::RGB8);
+        assert_eq!(image, original);
+
+        // too many colors get the colors of RGB332
+        let mut image = Image::from_fn(20, 20, ColorType::RGB8, |x, y| Color::RGB8([x as u8 * 12, y as u8 * 12, 0])).unwrap();
+        image.convert(ColorType::INDEXED8);
+        assert_eq!(image.palette().unwrap().len(), 256);
+        assert_eq!(image.palette().unwrap().get(0x1C), Some(Color::RGBA8([0, 255, 0, 255])));
+
+        // the image is snapped to the given palette, indexed images through their colors
+        let palette = Palette::new(&[Color::GRAY8(0), Color::GRAY8(255)]).unwrap();
+        image.convert_with_palette(palette.clone());
+        assert_eq!(image.palette(), Some(&palette));
+        assert_eq!(image.get((0, 0)).unwrap(), Color::INDEXED8(0));
+        assert_eq!(image.get((19, 19)).unwrap(), Color::INDEXED8(1));
+
+        // images without pixels get a palette with their background color, or opaque black without one
+        let mut image = Image::new(0, 3, Color::RGB8([10, 20, 30]));
+        image.convert(ColorType::INDEXED8);
+        assert_eq!(image.palette().unwrap().as_bytes(), &[10, 20, 30, 255]);
+        assert_eq!(image.background_color(), Some(Color::INDEXED8(0)));
+        let mut image = Image::from_fn(3, 0, ColorType::GRAYA8, |_, _| Color::GRAYA8([0, 0])).unwrap();
+        image.convert(ColorType::INDEXED8);
+        assert_eq!(image.palette().unwrap().as_bytes(), &[0, 0, 0, 255]);
+        assert!(!image.palette().unwrap().is_empty());
+
+        // indices without a palette are gray levels
+        let mut image = Image::new(2, 2, Color::INDEXED8(7));
+        image.convert(ColorType::GRAY16);
+        assert_eq!(image.get((1, 1)).unwrap(), Color::GRAY16(7 * 257));
+    }
+
     #[test]
     fn gray8_to_gray8() {
         conversion_test((Color::GRAY8(120), Color::GRAY8(140)), (Color::GRAY8(120), Color::GRAY8(140)));
@@ -1,6 +1,9 @@
+use crate::blending::Blend;
 use crate::colors::{Color, ColorType};
 use crate::error::Error;
 use crate::image::Image;
+use crate::sdf::{self, SdfShape, Shadow};
+use crate::target::{fill, DrawTarget};
 use std::cmp::{max, min};
 
 /*
@@ -1083,26 +1086,26 @@ impl Image<[u8; 3]> {
 */
 
 impl Image {
-    /// Draws a circle on the image. If the circle is not fully contained in the image, it will be clipped.
-    /// # Arguments
-    /// * ```center``` - The coordinates of the center of the circle.
-    /// * ```radius``` - The radius of the circle.
-    /// * ```color``` - The color of the circle.
-    /// * ```thickness``` - The thickness of the circle. If the thickness is 0, the circle will be filled.
-    /// * ```opacity``` - The opacity of the circle.
-    fn draw_circle(&mut self, center: (usize, usize), radius: usize, color: Color, thickness: usize, opacity: f64) -> Result<(), Error> {
-        todo!()
+    /// Draws a circle on the image, see [DrawTarget::draw_circle()].
+    pub fn draw_circle<B: Into<Blend>>(&mut self, center: (usize, usize), radius: usize, color: Color, thickness: usize, opacity: f64, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_circle(self, center, radius, color, thickness, opacity, blend)
     }
 
-    /// Draws an ellipse on the image. If the ellipse is not fully contained in the image, it will be clipped.
-    /// # Arguments
-    /// * ```center``` - The coordinates of the center of the ellipse.
-    /// * ```axes``` - The lengths of the axes of the ellipse.
-    /// * ```color``` - The color of the ellipse.
-    /// * ```thickness``` - The thickness of the ellipse. If the thickness is 0, the ellipse will be filled.
-    /// * ```opacity``` - The opacity of the ellipse.
-    fn draw_ellipse(&mut self, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64) -> Result<(), Error> {
-        todo!()
+    /// Draws an ellipse on the image, see [DrawTarget::draw_ellipse()].
+    pub fn draw_ellipse<B: Into<Blend>>(&mut self, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_ellipse(self, center, axes, color, thickness, opacity, blend)
+    }
+
+    /// Draws a circle with its shadow underneath on the image, see [DrawTarget::draw_circle_with_shadow()].
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_circle_with_shadow<B: Into<Blend>>(&mut self, center: (usize, usize), radius: usize, color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_circle_with_shadow(self, center, radius, color, thickness, opacity, shadow, blend)
+    }
+
+    /// Draws an ellipse with its shadow underneath on the image, see [DrawTarget::draw_ellipse_with_shadow()].
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_ellipse_with_shadow<B: Into<Blend>>(&mut self, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_ellipse_with_shadow(self, center, axes, color, thickness, opacity, shadow, blend)
     }
 
     /// Draws a line on the image. If the line is not fully contained in the image, it will be clipped.
@@ -1116,163 +1119,438 @@ impl Image {
         todo!()
     }
 
-    /// Draws a rectangle on the image. If the rectangle is not fully contained in the image, it will be clipped.
-    /// # Arguments
-    /// * ```point1``` - The coordinates of the first point of the rectangle.
-    /// * ```point2``` - The coordinates of the second point of the rectangle.
-    /// * ```color``` - The color of the rectangle.
-    /// * ```thickness``` - The thickness of the rectangle. If the thickness is 0, the rectangle will be filled.
-    /// * ```opacity``` - The opacity of the rectangle.
-    fn draw_rectangle(&mut self, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64) -> Result<(), Error> {
-        // check if color is valid for this image type
-        if ColorType::from(color) != self.color_type {
-            return Err(Error::WrongColor);
-        }
+    /// Draws a rectangle on the image, see [DrawTarget::draw_rectangle()].
+    pub fn draw_rectangle<B: Into<Blend>>(&mut self, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_rectangle(self, point1, point2, color, thickness, opacity, blend)
+    }
 
-        // if opacity is less than 0.0, bigger than 1.0, or NaN, return error
-        if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
-            return Err(Error::InvalidOpacity);
-        }
+    /// Draws a rectangle with its shadow underneath on the image, see [DrawTarget::draw_rectangle_with_shadow()].
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_rectangle_with_shadow<B: Into<Blend>>(&mut self, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_rectangle_with_shadow(self, point1, point2, color, thickness, opacity, shadow, blend)
+    }
 
-        // if opacity is 0.0, nothing is to be drawn
-        if opacity == 0.0 {
-            return Ok(());
-        }
+    /// Draws the source image onto the image, see [DrawTarget::draw_image()].
+    pub fn draw_image<B: Into<Blend>>(&mut self, source: &Image, position: (isize, isize), opacity: f64, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_image(self, source, position, opacity, blend)
+    }
+}
 
-        // find corners
-        let mut smaller_x = min(point1.0, point2.0);
-        let mut bigger_x = max(point1.0, point2.0);
-        let mut smaller_y = min(point1.1, point2.1);
-        let mut bigger_y = max(point1.1, point2.1);
-        if smaller_x >= self.width || smaller_y >= self.height {
-            return Ok(()); // rectangle is out of image, nothing is to be drawn.
-        }
+/// Draws an ellipse on the target, see [DrawTarget::draw_ellipse()].
+pub(crate) fn draw_ellipse<T: DrawTarget + ?Sized>(target: &mut T, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64, blend: Blend) -> Result<(), Error> {
+    let (shape, thickness) = ellipse_shape(center, axes, thickness);
+    sdf::draw_sdf(target, &shape, color, thickness, 1.0, opacity, blend)
+}
 
-        if thickness == 0 {
-            if bigger_x >= self.width {
-                bigger_x = self.width - 1;
-            }
-            if bigger_y >= self.height {
-                bigger_y = self.height - 1;
-            }
+/// Draws an ellipse with its shadow underneath on the target, see [DrawTarget::draw_ellipse_with_shadow()].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_ellipse_with_shadow<T: DrawTarget + ?Sized>(target: &mut T, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: Blend) -> Result<(), Error> {
+    let (shape, thickness) = ellipse_shape(center, axes, thickness);
+    sdf::draw_sdf_with_shadow(target, &shape, color, thickness, 1.0, opacity, shadow, blend)
+}
 
-            if opacity >= 1.0 {
-                // Draw filled, solid rectangle.
-                self.set_unchecked((smaller_x..(bigger_x + 1), smaller_y..(bigger_y + 1)), color);
-            } else {
-                // Draw filled, transparent rectangle.
-                self.set_transparent_unchecked((smaller_x..(bigger_x + 1), smaller_y..(bigger_y + 1)), color, opacity);
-            }
-        } else {
-            // new thickness variable, as it will be modified
-            let mut used_thickness = thickness;
-            // find maximum possible thickness
-            let limit_x = (bigger_x - smaller_x) / 2 + 1;
-            let limit_y = (bigger_y - smaller_y) / 2 + 1;
-            if (thickness > limit_x) || (thickness > limit_y) {
-                used_thickness = min(limit_x, limit_y);
-            }
-            used_thickness = min(used_thickness, self.width - smaller_x);
-            used_thickness = min(used_thickness, self.height - smaller_y);
+/// Returns the shape of an ellipse and the thickness of its outline, as drawn by [draw_ellipse()].
+fn ellipse_shape(center: (usize, usize), axes: (usize, usize), thickness: usize) -> (SdfShape, f64) {
+    // outline is centered on the edge of the shape, so the shape is shrunk by half of the thickness
+    // if the outline reaches the center, the ellipse is filled
+    let thickness = if thickness >= min(axes.0, axes.1) { 0.0 } else { thickness as f64 };
+    let shape = SdfShape::Ellipse {
+        center: (center.0 as f64, center.1 as f64),
+        axes: (axes.0 as f64 - thickness / 2.0, axes.1 as f64 - thickness / 2.0),
+    };
+    (shape, thickness)
+}
 
-            // draw smaller and smaller rectangles until given thickness is achieved
-            if opacity >= 1.0 {
-                // Draw rectangle, solid
+/// Draws a rectangle on the target, see [DrawTarget::draw_rectangle()].
+pub(crate) fn draw_rectangle<T: DrawTarget + ?Sized>(target: &mut T, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64, blend: Blend) -> Result<(), Error> {
+    let width = target.width();
+    let height = target.height();
 
-                while used_thickness > 0 {
-                    if bigger_x == smaller_x {
-                        if bigger_y == smaller_y {
-                            self.set_unchecked((smaller_x, smaller_y), color);
-                        } else {
-                            self.set_unchecked((smaller_x..(smaller_x + 1), smaller_y..min(self.height, bigger_y + 1)), color);
-                        }
-                    } else if bigger_y == smaller_y {
-                        self.set_unchecked((smaller_x..min(self.width, bigger_x + 1), smaller_y..(smaller_y + 1)), color);
-                    } else {
-                        self.set_unchecked((smaller_x..min(self.width, bigger_x + 1), smaller_y..(smaller_y + 1)), color); // bottom
-                        if bigger_y < self.height {
-                            self.set_unchecked((smaller_x..min(self.width, bigger_x + 1), bigger_y..(bigger_y + 1)), color);
-                            // top
-                        }
-                        self.set_unchecked((smaller_x..(smaller_x + 1), (smaller_y + 1)..min(bigger_y, self.height)), color); // left
-                        if bigger_x < self.width {
-                            self.set_unchecked((bigger_x..(bigger_x + 1), (smaller_y + 1)..min(bigger_y, self.height)), color);
-                            // right
-                        }
-                    }
+    // check if color is valid for this image type
+    if ColorType::from(color) != target.color_type() {
+        return Err(Error::WrongColor);
+    }
+
+    // if opacity is less than 0.0, bigger than 1.0, or NaN, return error
+    if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
+        return Err(Error::InvalidOpacity);
+    }
+
+    // if opacity is 0.0 and the operator keeps the destination, nothing is to be drawn
+    if opacity == 0.0 && blend.composite_op.keeps_destination() {
+        return Ok(());
+    }
 
-                    smaller_x += 1;
-                    smaller_y += 1;
-                    bigger_x -= 1;
-                    bigger_y -= 1;
+    // find corners
+    let mut smaller_x = min(point1.0, point2.0);
+    let mut bigger_x = max(point1.0, point2.0);
+    let mut smaller_y = min(point1.1, point2.1);
+    let mut bigger_y = max(point1.1, point2.1);
+    if smaller_x >= width || smaller_y >= height {
+        return Ok(()); // rectangle is out of image, nothing is to be drawn.
+    }
+
+    if thickness == 0 {
+        if bigger_x >= width {
+            bigger_x = width - 1;
+        }
+        if bigger_y >= height {
+            bigger_y = height - 1;
+        }
 
-                    used_thickness -= 1;
+        // Draw filled rectangle.
+        fill(target, smaller_x..(bigger_x + 1), smaller_y..(bigger_y + 1), color, opacity, blend);
+    } else {
+        // new thickness variable, as it will be modified
+        let mut used_thickness = thickness;
+        // find maximum possible thickness
+        let limit_x = (bigger_x - smaller_x) / 2 + 1;
+        let limit_y = (bigger_y - smaller_y) / 2 + 1;
+        if (thickness > limit_x) || (thickness > limit_y) {
+            used_thickness = min(limit_x, limit_y);
+        }
+        used_thickness = min(used_thickness, width - smaller_x);
+        used_thickness = min(used_thickness, height - smaller_y);
+
+        // draw smaller and smaller rectangles until given thickness is achieved
+        while used_thickness > 0 {
+            if bigger_x == smaller_x {
+                if bigger_y == smaller_y {
+                    fill(target, smaller_x..(smaller_x + 1), smaller_y..(smaller_y + 1), color, opacity, blend);
+                } else {
+                    fill(target, smaller_x..(smaller_x + 1), smaller_y..min(height, bigger_y + 1), color, opacity, blend);
                 }
+            } else if bigger_y == smaller_y {
+                fill(target, smaller_x..min(width, bigger_x + 1), smaller_y..(smaller_y + 1), color, opacity, blend);
             } else {
-                // Draw rectangle, transparent
+                fill(target, smaller_x..min(width, bigger_x + 1), smaller_y..(smaller_y + 1), color, opacity, blend); // bottom
+                if bigger_y < height {
+                    fill(target, smaller_x..min(width, bigger_x + 1), bigger_y..(bigger_y + 1), color, opacity, blend);
+                    // top
+                }
+                fill(target, smaller_x..(smaller_x + 1), (smaller_y + 1)..min(bigger_y, height), color, opacity, blend); // left
+                if bigger_x < width {
+                    fill(target, bigger_x..(bigger_x + 1), (smaller_y + 1)..min(bigger_y, height), color, opacity, blend);
+                    // right
+                }
+            }
 
-                while used_thickness > 0 {
-                    if bigger_x == smaller_x {
-                        if bigger_y == smaller_y {
-                            self.set_transparent_unchecked((smaller_x, smaller_y), color, opacity);
-                        } else {
-                            self.set_transparent_unchecked((smaller_x..(smaller_x + 1), smaller_y..min(self.height, bigger_y + 1)), color, opacity);
-                        }
-                    } else if bigger_y == smaller_y {
-                        self.set_transparent_unchecked((smaller_x..min(self.width, bigger_x + 1), smaller_y..(smaller_y + 1)), color, opacity);
-                    } else {
-                        self.set_transparent_unchecked((smaller_x..min(self.width, bigger_x + 1), smaller_y..(smaller_y + 1)), color, opacity); // bottom
-                        if bigger_y < self.height {
-                            self.set_transparent_unchecked((smaller_x..min(self.width, bigger_x + 1), bigger_y..(bigger_y + 1)), color, opacity);
-                            // top
-                        }
-                        self.set_transparent_unchecked((smaller_x..(smaller_x + 1), (smaller_y + 1)..min(bigger_y, self.height)), color, opacity); // left
-                        if bigger_x < self.width {
-                            self.set_transparent_unchecked((bigger_x..(bigger_x + 1), (smaller_y + 1)..min(bigger_y, self.height)), color, opacity);
-                            // right
-                        }
-                    }
+            smaller_x += 1;
+            smaller_y += 1;
+            bigger_x -= 1;
+            bigger_y -= 1;
+
+            used_thickness -= 1;
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws a rectangle with its shadow underneath on the target, see [DrawTarget::draw_rectangle_with_shadow()].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_rectangle_with_shadow<T: DrawTarget + ?Sized>(target: &mut T, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: Blend) -> Result<(), Error> {
+    // check all arguments before anything is drawn
+    if ColorType::from(color) != target.color_type() {
+        return Err(Error::WrongColor);
+    }
+    if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
+        return Err(Error::InvalidOpacity);
+    }
+    sdf::check_shadow_arguments(target.color_type(), thickness as f64, shadow)?;
+
+    // the shadow is cast by the pixels of the rectangle, so its edges lie half a pixel outside of the outermost pixel centers
+    // the outline is centered on the edge of the shape, so the shape is shrunk by half of the thickness
+    // if the outline reaches the center, the rectangle is filled
+    let smaller_x = min(point1.0, point2.0);
+    let bigger_x = max(point1.0, point2.0);
+    let smaller_y = min(point1.1, point2.1);
+    let bigger_y = max(point1.1, point2.1);
+    let limit = min((bigger_x - smaller_x) / 2 + 1, (bigger_y - smaller_y) / 2 + 1);
+    let shadow_thickness = if thickness >= limit { 0.0 } else { thickness as f64 };
+    let shape = SdfShape::RoundedRectangle {
+        point1: (smaller_x as f64 - 0.5 + shadow_thickness / 2.0, smaller_y as f64 - 0.5 + shadow_thickness / 2.0),
+        point2: (bigger_x as f64 + 0.5 - shadow_thickness / 2.0, bigger_y as f64 + 0.5 - shadow_thickness / 2.0),
+        radius: 0.0,
+    };
+    sdf::draw_shadow(target, &shape, shadow_thickness, shadow, blend)?;
+    draw_rectangle(target, point1, point2, color, thickness, opacity, blend)
+}
+
+/// Draws the source image onto the target, see [DrawTarget::draw_image()].
+pub(crate) fn draw_image<T: DrawTarget + ?Sized>(target: &mut T, source: &Image, position: (isize, isize), opacity: f64, blend: Blend) -> Result<(), Error> {
+    // if opacity is less than 0.0, bigger than 1.0, or NaN, return error
+    if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
+        return Err(Error::InvalidOpacity);
+    }
 
-                    smaller_x += 1;
-                    smaller_y += 1;
-                    bigger_x -= 1;
-                    bigger_y -= 1;
+    // if opacity is 0.0 and the operator keeps the destination, nothing is to be drawn
+    if opacity == 0.0 && blend.composite_op.keeps_destination() {
+        return Ok(());
+    }
+
+    // find the part of the source image which is inside of the target
+    let min_x = max(0, position.0.saturating_neg()) as usize;
+    let min_y = max(0, position.1.saturating_neg()) as usize;
+    let max_x = min(source.width as isize, (target.width() as isize).saturating_sub(position.0));
+    let max_y = min(source.height as isize, (target.height() as isize).saturating_sub(position.1));
+    if max_x <= min_x as isize || max_y <= min_y as isize {
+        return Ok(()); // source image is out of target, nothing is to be drawn.
+    }
 
-                    used_thickness -= 1;
+    // convert the visible part of the source image to the color type of the target, the alpha channel is kept for blending
+    // indexed targets get the colors of the source, which are snapped to the nearest colors of their palette
+    let color_type = if source.color_type.has_alpha() || target.color_type() == ColorType::INDEXED8 { target.color_type().with_alpha() } else { target.color_type() };
+    let (min_x, max_x, min_y, max_y) = (min_x, max_x as usize, min_y, max_y as usize);
+    let converted;
+    let (source, origin) = if source.color_type == color_type {
+        (source, (0, 0))
+    } else {
+        let mut image = source.crop((min_x, min_y), (max_x - min_x, max_y - min_y))?;
+        image.convert(color_type);
+        converted = image;
+        (&converted, (min_x, min_y))
+    };
+
+    // runs of equal pixels in a row are drawn as one span
+    for y in min_y..max_y {
+        let target_y = (y as isize + position.1) as usize;
+        let mut run_start = min_x;
+        let mut run_color = source.get_unchecked((min_x - origin.0, y - origin.1));
+        for x in (min_x + 1)..=max_x {
+            let color = if x < max_x { Some(source.get_unchecked((x - origin.0, y - origin.1))) } else { None };
+            if color != Some(run_color) {
+                let target_x = (run_start as isize + position.0) as usize;
+                fill(target, target_x..(target_x + x - run_start), target_y..(target_y + 1), run_color, opacity, blend);
+                if let Some(color) = color {
+                    run_start = x;
+                    run_color = color;
                 }
             }
         }
-
-        Ok(())
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Color, Image};
+    use crate::{Blend, BlendMode, Color, CompositeOp, Error, Image, SdfShape, Shadow};
 
     #[test]
     fn drawing_rectangle() {
         let mut image = Image::new(100, 100, Color::RGB8([255, 255, 255]));
 
         // test errors
-        if image.draw_rectangle((0, 0), (10, 10), Color::RGBA8([0, 0, 0, 0]), 1, 1.0).is_ok() {
+        if image.draw_rectangle((0, 0), (10, 10), Color::RGBA8([0, 0, 0, 0]), 1, 1.0, CompositeOp::SourceOver).is_ok() {
             panic!("Should fail!")
         }
-        if image.draw_rectangle((0, 0), (10, 10), Color::RGB8([0, 0, 0]), 0, 1.1).is_ok() {
+        if image.draw_rectangle((0, 0), (10, 10), Color::RGB8([0, 0, 0]), 0, 1.1, CompositeOp::SourceOver).is_ok() {
             panic!("Should fail!")
         }
 
         // test drawing
-        image.draw_rectangle((0, 0), (10, 10), Color::RGB8([0, 0, 0]), 1, 1.0).unwrap();
-        image.draw_rectangle((20, 20), (31, 31), Color::RGB8([0, 0, 0]), 1, 0.5).unwrap();
-        image.draw_rectangle((40, 40), (50, 50), Color::RGB8([0, 0, 0]), 3, 1.0).unwrap();
-        image.draw_rectangle((60, 60), (70, 70), Color::RGB8([0, 0, 0]), 3, 0.5).unwrap();
-        image.draw_rectangle((80, 80), (90, 90), Color::RGB8([0, 0, 0]), 0, 1.0).unwrap();
-        image.draw_rectangle((10, 90), (20, 80), Color::RGB8([0, 0, 0]), 0, 0.5).unwrap();
-        image.draw_rectangle((30, 70), (40, 60), Color::RGB8([0, 0, 0]), 1000000, 1.0).unwrap();
-        image.draw_rectangle((80, 10), (90, 30), Color::RGB8([0, 0, 0]), 1000000, 0.5).unwrap();
+        image.draw_rectangle((0, 0), (10, 10), Color::RGB8([0, 0, 0]), 1, 1.0, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((20, 20), (31, 31), Color::RGB8([0, 0, 0]), 1, 0.5, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((40, 40), (50, 50), Color::RGB8([0, 0, 0]), 3, 1.0, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((60, 60), (70, 70), Color::RGB8([0, 0, 0]), 3, 0.5, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((80, 80), (90, 90), Color::RGB8([0, 0, 0]), 0, 1.0, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((10, 90), (20, 80), Color::RGB8([0, 0, 0]), 0, 0.5, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((30, 70), (40, 60), Color::RGB8([0, 0, 0]), 1000000, 1.0, CompositeOp::SourceOver).unwrap();
+        image.draw_rectangle((80, 10), (90, 30), Color::RGB8([0, 0, 0]), 1000000, 0.5, CompositeOp::SourceOver).unwrap();
 
         // image.to_file("test_drawing_rectangle.png", true).unwrap();
     }
+
+    #[test]
+    fn drawing_rectangle_composite() {
+        let mut image = Image::new(10, 10, Color::RGBA8([0, 0, 255, 128]));
+
+        image.draw_rectangle((0, 0), (4, 9), Color::RGBA8([255, 0, 0, 255]), 0, 1.0, CompositeOp::SourceIn).unwrap();
+        assert_eq!(image.get((2, 2)).unwrap(), Color::RGBA8([255, 0, 0, 128]));
+
+        image.draw_rectangle((5, 0), (9, 9), Color::RGBA8([255, 0, 0, 255]), 0, 0.0, CompositeOp::Copy).unwrap();
+        assert_eq!(image.get((7, 7)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+
+        image.draw_rectangle((0, 0), (9, 9), Color::RGBA8([0, 255, 0, 255]), 0, 1.0, CompositeOp::DestinationOver).unwrap();
+        assert_eq!(image.get((2, 2)).unwrap(), Color::RGBA8([128, 127, 0, 255]));
+        assert_eq!(image.get((7, 7)).unwrap(), Color::RGBA8([0, 255, 0, 255]));
+
+        let mut image = Image::new(10, 10, Color::RGB8([255, 255, 255]));
+        image.draw_rectangle((0, 0), (9, 9), Color::RGB8([0, 0, 0]), 0, 1.0, CompositeOp::Clear).unwrap();
+        assert_eq!(image.as_bytes(), Image::new(10, 10, Color::RGB8([0, 0, 0])).as_bytes());
+    }
+
+    #[test]
+    fn drawing_rectangle_blend_mode() {
+        let mut image = Image::new(10, 10, Color::RGB8([255, 128, 0]));
+
+        image.draw_rectangle((0, 0), (4, 9), Color::RGB8([128, 128, 128]), 0, 1.0, BlendMode::Multiply).unwrap();
+        assert_eq!(image.get((2, 2)).unwrap(), Color::RGB8([128, 64, 0]));
+
+        image.draw_rectangle((5, 0), (9, 9), Color::RGB8([128, 128, 128]), 0, 1.0, BlendMode::Screen).unwrap();
+        assert_eq!(image.get((7, 7)).unwrap(), Color::RGB8([255, 192, 128]));
+
+        let mut image = Image::new(10, 10, Color::RGBA8([255, 128, 0, 0]));
+        image.draw_rectangle((0, 0), (9, 9), Color::RGBA8([128, 128, 128, 255]), 0, 1.0, Blend::new(BlendMode::Multiply, CompositeOp::SourceOver)).unwrap();
+        assert_eq!(image.get((2, 2)).unwrap(), Color::RGBA8([128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn drawing_circle() {
+        let mut image = Image::new(100, 100, Color::RGB8([255, 255, 255]));
+
+        // test errors
+        assert_eq!(image.draw_circle((50, 50), 10, Color::RGBA8([0, 0, 0, 0]), 0, 1.0, Blend::default()), Err(crate::Error::WrongColor));
+        assert_eq!(image.draw_circle((50, 50), 10, Color::RGB8([0, 0, 0]), 0, f64::NAN, Blend::default()), Err(crate::Error::InvalidOpacity));
+        assert!(image.as_bytes().iter().all(|&value| value == 255));
+
+        // test filled circle, the edge is anti-aliased
+        image.draw_circle((20, 20), 10, Color::RGB8([0, 0, 0]), 0, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((20, 20)).unwrap(), Color::RGB8([0, 0, 0]));
+        assert_eq!(image.get((29, 20)).unwrap(), Color::RGB8([0, 0, 0]));
+        assert_eq!(image.get((30, 20)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((20, 10)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((31, 20)).unwrap(), Color::RGB8([255, 255, 255]));
+        assert_eq!(image.get((28, 28)).unwrap(), Color::RGB8([255, 255, 255]));
+
+        // test outline, measured inwards from the radius
+        image.draw_circle((60, 20), 10, Color::RGB8([0, 0, 0]), 2, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((60, 20)).unwrap(), Color::RGB8([255, 255, 255]));
+        assert_eq!(image.get((69, 20)).unwrap(), Color::RGB8([0, 0, 0]));
+        assert_eq!(image.get((70, 20)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((68, 20)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((67, 20)).unwrap(), Color::RGB8([255, 255, 255]));
+
+        // an outline reaching the center fills the circle
+        let mut outlined = Image::new(30, 30, Color::GRAY8(0));
+        let mut filled = Image::new(30, 30, Color::GRAY8(0));
+        outlined.draw_circle((15, 15), 8, Color::GRAY8(255), 8, 1.0, Blend::default()).unwrap();
+        filled.draw_circle((15, 15), 8, Color::GRAY8(255), 0, 1.0, Blend::default()).unwrap();
+        assert_eq!(outlined, filled);
+
+        // test clipping at the edges of the image
+        let mut image = Image::new(10, 10, Color::GRAY8(0));
+        image.draw_circle((0, 0), 20, Color::GRAY8(255), 0, 1.0, Blend::default()).unwrap();
+        image.draw_circle((usize::MAX, usize::MAX), 5, Color::GRAY8(100), 0, 1.0, Blend::default()).unwrap();
+        assert!(image.as_bytes().iter().all(|&value| value == 255));
+    }
+
+    #[test]
+    fn drawing_ellipse() {
+        let mut image = Image::new(100, 100, Color::RGB8([255, 255, 255]));
+
+        // test errors
+        assert_eq!(image.draw_ellipse((50, 50), (10, 5), Color::GRAY8(0), 0, 1.0, Blend::default()), Err(crate::Error::WrongColor));
+        assert_eq!(image.draw_ellipse((50, 50), (10, 5), Color::RGB8([0, 0, 0]), 0, -0.1, Blend::default()), Err(crate::Error::InvalidOpacity));
+
+        // test filled ellipse with opacity, the axes go along x and y
+        image.draw_ellipse((50, 70), (30, 10), Color::RGB8([0, 0, 0]), 0, 0.5, Blend::default()).unwrap();
+        assert_eq!(image.get((50, 70)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((75, 70)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((50, 79)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(image.get((50, 82)).unwrap(), Color::RGB8([255, 255, 255]));
+        assert_eq!(image.get((82, 70)).unwrap(), Color::RGB8([255, 255, 255]));
+
+        // the ellipse with equal axes is the circle
+        let mut ellipse = Image::new(30, 30, Color::GRAY8(0));
+        let mut circle = Image::new(30, 30, Color::GRAY8(0));
+        ellipse.draw_ellipse((15, 15), (9, 9), Color::GRAY8(255), 3, 1.0, Blend::default()).unwrap();
+        circle.draw_circle((15, 15), 9, Color::GRAY8(255), 3, 1.0, Blend::default()).unwrap();
+        assert_eq!(ellipse, circle);
+
+        // test outline, the outline reaching the shorter axis fills the ellipse
+        let mut image = Image::new(100, 100, Color::GRAY8(0));
+        image.draw_ellipse((50, 50), (30, 10), Color::GRAY8(255), 2, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((50, 50)).unwrap(), Color::GRAY8(0));
+        assert_eq!(image.get((50, 59)).unwrap(), Color::GRAY8(255));
+        assert_eq!(image.get((79, 50)).unwrap(), Color::GRAY8(255));
+        image.draw_ellipse((0, 99), (30, 10), Color::GRAY8(100), 1000, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((0, 99)).unwrap(), Color::GRAY8(100));
+        assert_eq!(image.get((0, 90)).unwrap(), Color::GRAY8(100));
+    }
+
+    #[test]
+    fn drawing_with_shadow() {
+        let mut image = Image::new(20, 20, Color::GRAY8(0));
+        let shadow = Shadow::new((6.0, 0.0), 0.0, Color::GRAY8(128));
+
+        // test errors, nothing is drawn
+        assert_eq!(image.draw_rectangle_with_shadow((2, 2), (5, 5), Color::GRAY8(255), 0, 1.0, &Shadow::new((0.0, 0.0), 0.0, Color::RGB8([0, 0, 0])), Blend::default()), Err(Error::WrongColor));
+        assert_eq!(image.draw_rectangle_with_shadow((2, 2), (5, 5), Color::GRAY8(255), 0, 2.0, &shadow, Blend::default()), Err(Error::InvalidOpacity));
+        assert_eq!(image.draw_circle_with_shadow((10, 10), 4, Color::GRAY8(255), 0, 1.0, &Shadow::new((0.0, 0.0), f64::NAN, Color::GRAY8(0)), Blend::default()), Err(Error::InvalidBlur));
+        assert_eq!(image, Image::new(20, 20, Color::GRAY8(0)));
+
+        // unblurred shadow of a rectangle covers exactly the moved pixels of the rectangle, underneath the rectangle
+        image.draw_rectangle_with_shadow((2, 2), (7, 5), Color::GRAY8(255), 0, 1.0, &shadow, Blend::default()).unwrap();
+        assert_eq!(image.get((7, 3)).unwrap(), Color::GRAY8(255));
+        assert_eq!(image.get((8, 3)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((13, 5)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((14, 5)).unwrap(), Color::GRAY8(0));
+        assert_eq!(image.get((10, 6)).unwrap(), Color::GRAY8(0));
+
+        // shadow of an outline is an outline
+        let mut image = Image::new(20, 20, Color::GRAY8(0));
+        image.draw_rectangle_with_shadow((2, 2), (10, 10), Color::GRAY8(255), 2, 1.0, &Shadow::new((0.0, 8.0), 0.0, Color::GRAY8(128)), Blend::default()).unwrap();
+        assert_eq!(image.get((3, 17)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((4, 16)).unwrap(), Color::GRAY8(0));
+        assert_eq!(image.get((6, 6)).unwrap(), Color::GRAY8(0));
+
+        // the ellipse is drawn as the shadow followed by the shape
+        let shadow = Shadow::new((3.0, -2.0), 4.0, Color::RGBA8([0, 0, 0, 128]));
+        let mut image = Image::new(30, 30, Color::RGBA8([255, 255, 255, 255]));
+        let mut expected = image.clone();
+        image.draw_ellipse_with_shadow((15, 15), (8, 5), Color::RGBA8([255, 0, 0, 255]), 2, 1.0, &shadow, Blend::default()).unwrap();
+        let shape = SdfShape::Ellipse { center: (15.0, 15.0), axes: (7.0, 4.0) };
+        expected.draw_shadow(&shape, 2.0, &shadow, Blend::default()).unwrap();
+        expected.draw_ellipse((15, 15), (8, 5), Color::RGBA8([255, 0, 0, 255]), 2, 1.0, Blend::default()).unwrap();
+        assert_eq!(image, expected);
+    }
+
+    #[test]
+    fn drawing_image() {
+        let mut canvas = Image::new(10, 10, Color::RGB8([0, 0, 255]));
+        let mut sprite = Image::new(3, 2, Color::RGBA8([255, 0, 0, 255]));
+        sprite.set((0, 0), Color::RGBA8([0, 255, 0, 0])).unwrap();
+        sprite.set((1, 1), Color::RGBA8([0, 255, 0, 128])).unwrap();
+
+        // test errors
+        assert_eq!(canvas.draw_image(&sprite, (0, 0), 1.5, CompositeOp::SourceOver), Err(Error::InvalidOpacity));
+
+        // source alpha is respected
+        canvas.draw_image(&sprite, (4, 5), 1.0, CompositeOp::SourceOver).unwrap();
+        assert_eq!(canvas.get((4, 5)).unwrap(), Color::RGB8([0, 0, 255]));
+        assert_eq!(canvas.get((5, 5)).unwrap(), Color::RGB8([255, 0, 0]));
+        assert_eq!(canvas.get((5, 6)).unwrap(), Color::RGB8([0, 128, 127]));
+        assert_eq!(canvas.get((7, 5)).unwrap(), Color::RGB8([0, 0, 255]));
+        assert_eq!(canvas.get((4, 7)).unwrap(), Color::RGB8([0, 0, 255]));
+
+        // clipping at all edges
+        let mut canvas = Image::new(4, 3, Color::RGBA8([0, 0, 0, 0]));
+        canvas.draw_image(&sprite, (-1, -1), 1.0, CompositeOp::Copy).unwrap();
+        assert_eq!(canvas.get((0, 0)).unwrap(), Color::RGBA8([0, 255, 0, 128]));
+        assert_eq!(canvas.get((1, 0)).unwrap(), Color::RGBA8([255, 0, 0, 255]));
+        assert_eq!(canvas.get((2, 0)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+        assert_eq!(canvas.get((0, 1)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+        canvas.draw_image(&sprite, (3, 2), 0.5, CompositeOp::SourceOver).unwrap();
+        assert_eq!(canvas.get((3, 2)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+        canvas.draw_image(&sprite, (2, 2), 1.0, CompositeOp::SourceOver).unwrap();
+        assert_eq!(canvas.get((3, 2)).unwrap(), Color::RGBA8([255, 0, 0, 255]));
+        canvas.draw_image(&sprite, (-3, 0), 1.0, CompositeOp::SourceOver).unwrap();
+        canvas.draw_image(&sprite, (4, 0), 1.0, CompositeOp::SourceOver).unwrap();
+        canvas.draw_image(&sprite, (isize::MIN, isize::MAX), 1.0, CompositeOp::SourceOver).unwrap();
+
+        // different color types
+        let mut logo = Image::new(2, 2, Color::GRAY8(255));
+        logo.set((0, 0), Color::GRAY8(0)).unwrap();
+        let mut canvas = Image::new(3, 3, Color::RGBA16([0, 0, 65_535, 65_535]));
+        canvas.draw_image(&logo, (1, 1), 1.0, CompositeOp::SourceOver).unwrap();
+        assert_eq!(canvas.get((1, 1)).unwrap(), Color::RGBA16([0, 0, 0, 65_535]));
+        assert_eq!(canvas.get((2, 2)).unwrap(), Color::RGBA16([65_535, 65_535, 65_535, 65_535]));
+        assert_eq!(canvas.get((0, 0)).unwrap(), Color::RGBA16([0, 0, 65_535, 65_535]));
+        let mut canvas = Image::new(3, 3, Color::GRAY8(0));
+        canvas.draw_image(&sprite, (0, 0), 1.0, BlendMode::Screen).unwrap();
+        assert_eq!(canvas.get((1, 0)).unwrap(), Color::GRAY8(85));
+        assert_eq!(canvas.get((0, 0)).unwrap(), Color::GRAY8(0));
+    }
 }
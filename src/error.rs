@@ -7,8 +7,20 @@ pub enum Error {
     FileExists,
     /// The invalid opacity value
     InvalidOpacity,
+    /// The invalid thickness value
+    InvalidThickness,
+    /// The invalid falloff value
+    InvalidFalloff,
+    /// The invalid blur value
+    InvalidBlur,
     /// The invalid size of the image
     InvalidSize,
+    /// The invalid stride of the image rows
+    InvalidStride,
+    /// The transform can't be inverted
+    InvalidTransform,
+    /// The invalid number of palette colors
+    InvalidPalette,
     /// The index is out of bounds
     IndexOutOfBounds,
     /// The given color is wrong
@@ -22,7 +34,13 @@ impl std::fmt::Display for Error {
         match self {
             Error::FileExists => write!(f, "Error: File already exists!"),
             Error::InvalidOpacity => write!(f, "Error: Invalid opacity value!"),
+            Error::InvalidThickness => write!(f, "Error: Invalid thickness value!"),
+            Error::InvalidFalloff => write!(f, "Error: Invalid falloff value!"),
+            Error::InvalidBlur => write!(f, "Error: Invalid blur value!"),
             Error::InvalidSize => write!(f, "Error: The size of the image is invalid!"),
+            Error::InvalidStride => write!(f, "Error: Invalid stride value!"),
+            Error::InvalidTransform => write!(f, "Error: Invalid transform!"),
+            Error::InvalidPalette => write!(f, "Error: Invalid palette!"),
             Error::IndexOutOfBounds => write!(f, "Error: Index out of bounds!"),
             Error::WrongColor => write!(f, "Error: Wrong color!"),
         }
@@ -0,0 +1,339 @@
+//! A module containing convolution filters (blurring, sharpening and edge detection).
+//!
+//! As for resampling, images are filtered with premultiplied alpha in floating point,
+//! so colors of transparent pixels don't bleed into their neighbours and every color type is supported.
+
+use crate::error::Error;
+use crate::image::Image;
+use crate::mask::gaussian_kernel;
+
+/// An enum that represents how the pixels outside of the image are sampled by the filters
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EdgeMode {
+    /// The nearest edge pixel is used
+    #[default]
+    Clamp,
+    /// The image is mirrored over its edges (without repeating the edge pixels)
+    Mirror,
+    /// The image is repeated, pixels past the right edge are taken from the left edge and so on
+    Wrap,
+    /// The pixels outside are transparent (black for color types without alpha)
+    Transparent,
+}
+impl EdgeMode {
+    /// Maps the index along one axis to the index of the sampled pixel.
+    /// # Arguments
+    /// * ```index``` - The index, possibly outside of the image.
+    /// * ```len``` - The number of pixels along the axis.
+    /// # Returns
+    /// * The index of the sampled pixel, [None] if the pixel is transparent.
+    fn index(&self, index: isize, len: usize) -> Option<usize> {
+        if index >= 0 && (index as usize) < len {
+            return Some(index as usize);
+        }
+        let len = len as isize;
+        match self {
+            EdgeMode::Clamp => Some(index.clamp(0, len - 1) as usize),
+            EdgeMode::Mirror => {
+                if len == 1 {
+                    return Some(0);
+                }
+                let period = 2 * (len - 1);
+                let index = index.rem_euclid(period);
+                Some(if index < len { index } else { period - index } as usize)
+            }
+            EdgeMode::Wrap => Some(index.rem_euclid(len) as usize),
+            EdgeMode::Transparent => None,
+        }
+    }
+}
+
+/// A struct that holds the weights of a convolution kernel.
+/// The weights are stored row by row from the top, as the kernel is usually written down,
+/// and the center of the kernel is placed on the filtered pixel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    /// The width of the kernel
+    width: usize,
+    /// The height of the kernel
+    height: usize,
+    /// The weights of the kernel
+    weights: Vec<f64>,
+}
+impl Kernel {
+    /// Creates a new kernel from the weights.
+    /// # Arguments
+    /// * ```width``` - The width of the kernel, must be odd.
+    /// * ```height``` - The height of the kernel, must be odd.
+    /// * ```weights``` - The weights of the kernel row by row from the top.
+    /// # Returns
+    /// * [Result] which holds the new [Kernel] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height is even, or the number of weights doesn't match them.
+    pub fn new(width: usize, height: usize, weights: &[f64]) -> Result<Self, Error> {
+        if width.is_multiple_of(2) || height.is_multiple_of(2) || weights.len() != width * height {
+            return Err(Error::InvalidSize);
+        }
+        Ok(Self {
+            width,
+            height,
+            weights: weights.to_vec(),
+        })
+    }
+
+    /// Creates the 3x3 kernel which sharpens the image.
+    /// # Returns
+    /// * The sharpening kernel.
+    pub fn sharpen() -> Self {
+        Self::new(3, 3, &[0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0]).unwrap()
+    }
+
+    /// Creates the 3x3 Laplacian kernel which detects edges in all directions.
+    /// Uniform areas become black.
+    /// # Returns
+    /// * The edge detection kernel.
+    pub fn edge_detection() -> Self {
+        Self::new(3, 3, &[-1.0, -1.0, -1.0, -1.0, 8.0, -1.0, -1.0, -1.0, -1.0]).unwrap()
+    }
+
+    /// Creates the 3x3 Sobel kernel which detects vertical edges, where the image gets brighter from the left to the right.
+    /// # Returns
+    /// * The horizontal Sobel kernel.
+    pub fn sobel_horizontal() -> Self {
+        Self::new(3, 3, &[-1.0, 0.0, 1.0, -2.0, 0.0, 2.0, -1.0, 0.0, 1.0]).unwrap()
+    }
+
+    /// Creates the 3x3 Sobel kernel which detects horizontal edges, where the image gets brighter from the bottom to the top.
+    /// # Returns
+    /// * The vertical Sobel kernel.
+    pub fn sobel_vertical() -> Self {
+        Self::new(3, 3, &[1.0, 2.0, 1.0, 0.0, 0.0, 0.0, -1.0, -2.0, -1.0]).unwrap()
+    }
+
+    /// Returns the width of the kernel.
+    /// # Returns
+    /// * The width of the kernel.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the kernel.
+    /// # Returns
+    /// * The height of the kernel.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the weights of the kernel.
+    /// # Returns
+    /// * The weights of the kernel row by row from the top.
+    #[inline]
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+
+/// Convolves the premultiplied pixels with the kernel.
+/// # Arguments
+/// * ```pixels``` - The normalized, premultiplied pixels in the order of the pixel data.
+/// * ```size``` - The width and the height of the image.
+/// * ```kernel``` - The width and the height of the kernel and its weights row by row from the top.
+/// * ```edge_mode``` - The sampling of the pixels outside of the image.
+/// * ```channels``` - The number of filtered channels, the remaining channels are kept.
+/// # Returns
+/// * The filtered pixels.
+fn convolve_pixels(pixels: &[[f64; 4]], size: (usize, usize), kernel: (usize, usize, &[f64]), edge_mode: EdgeMode, channels: usize) -> Vec<[f64; 4]> {
+    let (kernel_width, kernel_height, weights) = kernel;
+    let (radius_x, radius_y) = ((kernel_width / 2) as isize, (kernel_height / 2) as isize);
+    let mut result = pixels.to_vec();
+    for row in 0..size.1 {
+        for column in 0..size.0 {
+            let mut sum = [0.0; 4];
+            for (i, kernel_row) in weights.chunks_exact(kernel_width).enumerate() {
+                let Some(source_row) = edge_mode.index(row as isize + i as isize - radius_y, size.1) else {
+                    continue;
+                };
+                for (j, weight) in kernel_row.iter().enumerate() {
+                    let Some(source_column) = edge_mode.index(column as isize + j as isize - radius_x, size.0) else {
+                        continue;
+                    };
+                    let pixel = &pixels[source_row * size.0 + source_column];
+                    for channel in 0..channels {
+                        sum[channel] += pixel[channel] * weight;
+                    }
+                }
+            }
+            result[row * size.0 + column][..channels].copy_from_slice(&sum[..channels]);
+        }
+    }
+    result
+}
+
+impl Image {
+    /// Applies the separable kernel, first along the rows and then along the columns.
+    /// # Arguments
+    /// * ```kernel``` - The one-dimensional kernel.
+    /// * ```edge_mode``` - The sampling of the pixels outside of the image.
+    /// # Returns
+    /// * The filtered image.
+    fn convolve_separable(&self, kernel: &[f64], edge_mode: EdgeMode) -> Image {
+        let channels = if self.color_type.has_alpha() { 4 } else { 3 };
+        let size = (self.width, self.height);
+        let pixels = self.to_rgba_f64_premultiplied();
+        let pixels = convolve_pixels(&pixels, size, (kernel.len(), 1, kernel), edge_mode, channels);
+        let pixels = convolve_pixels(&pixels, size, (1, kernel.len(), kernel), edge_mode, channels);
+        let mut image = self.derive_from_rgba_f64_premultiplied(self.width, self.height, &pixels);
+        image.background_color = self.background_color;
+        image
+    }
+
+    /// Returns the image convolved with the kernel.
+    /// For color types with alpha, the alpha is filtered too, so kernels whose weights don't sum to 1 change the opacity.
+    /// Results outside of the range of the color type are clamped.
+    /// # Arguments
+    /// * ```kernel``` - The convolution kernel.
+    /// * ```edge_mode``` - The sampling of the pixels outside of the image.
+    /// # Returns
+    /// * The filtered image with the same color type and alpha mode.
+    pub fn convolve(&self, kernel: &Kernel, edge_mode: EdgeMode) -> Image {
+        let channels = if self.color_type.has_alpha() { 4 } else { 3 };
+        let pixels = self.to_rgba_f64_premultiplied();
+        let pixels = convolve_pixels(&pixels, (self.width, self.height), (kernel.width, kernel.height, &kernel.weights), edge_mode, channels);
+        let mut image = self.derive_from_rgba_f64_premultiplied(self.width, self.height, &pixels);
+        image.background_color = self.background_color;
+        image
+    }
+
+    /// Returns the image blurred with the gaussian kernel, the edge pixels are extended.
+    /// # Arguments
+    /// * ```sigma``` - The standard deviation of the gaussian kernel in pixels.
+    /// # Returns
+    /// * [Result] which holds the blurred image with the same color type and alpha mode, or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidBlur] - If the standard deviation is NaN, infinite or negative.
+    pub fn gaussian_blur(&self, sigma: f64) -> Result<Image, Error> {
+        if !sigma.is_finite() || sigma < 0.0 {
+            return Err(Error::InvalidBlur);
+        }
+        Ok(self.convolve_separable(&gaussian_kernel(sigma, usize::MAX), EdgeMode::Clamp))
+    }
+
+    /// Returns the image blurred with the box kernel (every pixel becomes the average of the square around it), the edge pixels are extended.
+    /// # Arguments
+    /// * ```radius``` - The distance from the pixel to the edge of the square. If the radius is 0, the image is not changed.
+    /// # Returns
+    /// * The blurred image with the same color type and alpha mode.
+    pub fn box_blur(&self, radius: usize) -> Image {
+        let len = 2 * radius + 1;
+        self.convolve_separable(&vec![1.0 / len as f64; len], EdgeMode::Clamp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::{Color, ColorType};
+
+    #[test]
+    fn test_edge_mode() {
+        let indices = |mode: EdgeMode, len| (-4..8).map(|i| mode.index(i, len)).collect::<Vec<_>>();
+        let some = |indices: &[usize]| indices.iter().map(|&i| Some(i)).collect::<Vec<_>>();
+        assert_eq!(indices(EdgeMode::Clamp, 4), some(&[0, 0, 0, 0, 0, 1, 2, 3, 3, 3, 3, 3]));
+        assert_eq!(indices(EdgeMode::Mirror, 4), some(&[2, 3, 2, 1, 0, 1, 2, 3, 2, 1, 0, 1]));
+        assert_eq!(indices(EdgeMode::Mirror, 1), some(&[0; 12]));
+        assert_eq!(indices(EdgeMode::Wrap, 4), some(&[0, 1, 2, 3, 0, 1, 2, 3, 0, 1, 2, 3]));
+        let transparent = indices(EdgeMode::Transparent, 4);
+        assert_eq!(&transparent[3..9], &[None, Some(0), Some(1), Some(2), Some(3), None]);
+    }
+
+    #[test]
+    fn test_kernel() {
+        assert_eq!(Kernel::new(2, 1, &[0.5, 0.5]), Err(Error::InvalidSize));
+        assert_eq!(Kernel::new(3, 1, &[0.5, 0.5]), Err(Error::InvalidSize));
+        let kernel = Kernel::new(3, 1, &[0.25, 0.5, 0.25]).unwrap();
+        assert_eq!((kernel.width(), kernel.height(), kernel.weights()), (3, 1, &[0.25, 0.5, 0.25][..]));
+        for kernel in [Kernel::sharpen(), Kernel::edge_detection(), Kernel::sobel_horizontal(), Kernel::sobel_vertical()] {
+            assert_eq!((kernel.width(), kernel.height()), (3, 3));
+        }
+    }
+
+    #[test]
+    fn test_convolve() {
+        let row = Image::from_bytes(5, 1, ColorType::GRAY8, &[0, 0, 100, 200, 200]).unwrap();
+        let kernel = Kernel::new(3, 1, &[0.25, 0.5, 0.25]).unwrap();
+        assert_eq!(row.convolve(&kernel, EdgeMode::Clamp).as_bytes(), &[0, 25, 100, 175, 200]);
+        assert_eq!(row.convolve(&kernel, EdgeMode::Mirror).as_bytes(), &[0, 25, 100, 175, 200]);
+        assert_eq!(row.convolve(&kernel, EdgeMode::Wrap).as_bytes(), &[50, 25, 100, 175, 150]);
+        assert_eq!(row.convolve(&kernel, EdgeMode::Transparent).as_bytes(), &[0, 25, 100, 175, 150]);
+
+        // the kernel is placed as written, its top row samples the row above the pixel
+        let mut image = Image::new(3, 3, Color::GRAY8(0));
+        image.set((1, 2), Color::GRAY8(100)).unwrap();
+        let shift_down = Kernel::new(1, 3, &[1.0, 0.0, 0.0]).unwrap();
+        assert_eq!(image.convolve(&shift_down, EdgeMode::Transparent).get((1, 1)).unwrap(), Color::GRAY8(100));
+
+        // uniform images stay uniform when sharpened and become black when edge detected
+        let uniform = Image::new(4, 4, Color::RGB16([1000, 20_000, 65_535]));
+        assert_eq!(uniform.convolve(&Kernel::sharpen(), EdgeMode::Clamp), uniform);
+        let edges = uniform.convolve(&Kernel::edge_detection(), EdgeMode::Clamp);
+        assert!(edges.as_bytes().iter().all(|&byte| byte == 0));
+
+        // sobel kernels detect edges in their direction
+        let mut step = Image::new(4, 4, Color::GRAY8(0));
+        for y in 0..4 {
+            step.set((2, y), Color::GRAY8(50)).unwrap();
+            step.set((3, y), Color::GRAY8(50)).unwrap();
+        }
+        let horizontal = step.convolve(&Kernel::sobel_horizontal(), EdgeMode::Clamp);
+        assert_eq!(horizontal.as_bytes()[..4], [0, 200, 200, 0]);
+        let vertical = step.convolve(&Kernel::sobel_vertical(), EdgeMode::Clamp);
+        assert!(vertical.as_bytes().iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn test_blur() {
+        let image = Image::new(3, 3, Color::GRAY8(0));
+        assert_eq!(image.gaussian_blur(-1.0), Err(Error::InvalidBlur));
+        assert_eq!(image.gaussian_blur(f64::NAN), Err(Error::InvalidBlur));
+        assert_eq!(image.gaussian_blur(0.0).unwrap(), image);
+        assert_eq!(image.box_blur(0), image);
+
+        // box blur averages the square around the pixel
+        let mut image = Image::new(5, 5, Color::GRAY8(0));
+        image.set((2, 2), Color::GRAY8(90)).unwrap();
+        let blurred = image.box_blur(1);
+        assert_eq!(blurred.get((2, 2)).unwrap(), Color::GRAY8(10));
+        assert_eq!(blurred.get((1, 3)).unwrap(), Color::GRAY8(10));
+        assert_eq!(blurred.get((0, 2)).unwrap(), Color::GRAY8(0));
+
+        // gaussian blur is symmetric and decreases with the distance
+        let mut image = Image::new(9, 9, Color::RGB16([0, 0, 0]));
+        image.set((4, 4), Color::RGB16([65_535, 65_535, 65_535])).unwrap();
+        let blurred = image.gaussian_blur(1.0).unwrap();
+        let value = |x, y| match blurred.get((x, y)).unwrap() {
+            Color::RGB16([red, _, _]) => red,
+            _ => panic!(),
+        };
+        assert_eq!(value(3, 4), value(5, 4));
+        assert_eq!(value(4, 3), value(4, 5));
+        assert_eq!(value(3, 4), value(4, 3));
+        assert!(value(4, 4) > value(3, 4) && value(3, 4) > value(3, 3) && value(3, 3) > value(2, 2) && value(2, 2) > 0);
+
+        // uniform images stay uniform
+        let uniform = Image::new(6, 4, Color::RGBA16([12_345, 54_321, 1, 40_000]));
+        assert_eq!(uniform.gaussian_blur(1.5).unwrap(), uniform);
+        assert_eq!(uniform.box_blur(2), uniform);
+    }
+
+    #[test]
+    fn test_blur_alpha() {
+        // colors of transparent pixels don't bleed
+        let image = Image::from_bytes(3, 1, ColorType::RGBA8, &[0, 255, 0, 0, 255, 0, 0, 255, 0, 255, 0, 0]).unwrap();
+        let blurred = image.box_blur(1);
+        assert_eq!(blurred.get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 85]));
+        assert_eq!(blurred.get((1, 0)).unwrap(), Color::RGBA8([255, 0, 0, 85]));
+        assert_eq!(blurred.get((2, 0)).unwrap(), Color::RGBA8([255, 0, 0, 85]));
+    }
+}
@@ -1,8 +1,9 @@
 //! A module that contains the [Image] struct and related functions.
 
 // standard library imports
-use crate::colors::{Color, ColorType};
+use crate::colors::{AlphaMode, Color, ColorType};
 use crate::error::Error;
+use crate::palette::Palette;
 use std::fmt::Display;
 
 /// A struct that holds an image
@@ -18,16 +19,21 @@ pub struct Image {
     pub(crate) color_type: ColorType,
     /// The background color of the image, None if not set
     pub(crate) background_color: Option<Color>,
+    /// The way the alpha channel is stored in the pixel data
+    pub(crate) alpha_mode: AlphaMode,
+    /// The palette of the indexed image, None if not set or the image is not indexed
+    pub(crate) palette: Option<Palette>,
 }
 impl Display for Image {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Image {{ width: {}, height: {}, color_type: {}, background_color: {}, data: {:?} }}",
+            "Image {{ width: {}, height: {}, color_type: {}, background_color: {}, alpha_mode: {}, data: {:?} }}",
             self.width,
             self.height,
             self.color_type,
             self.background_color.map(|c| c.to_string()).unwrap_or_else(|| "None".to_string()),
+            self.alpha_mode,
             self.data
         )
     }
@@ -44,7 +50,7 @@ impl Image {
     #[allow(clippy::uninit_vec)]
     pub fn new(width: usize, height: usize, background_color: Color) -> Self {
         // create uninitialized data vector
-        let len = width * height * background_color.bytes_per_pixel();
+        let len = ColorType::from(background_color).row_len(width) * height;
         let mut data = Vec::with_capacity(len);
         unsafe {
             data.set_len(len);
@@ -57,6 +63,8 @@ impl Image {
             height,
             color_type: ColorType::from(background_color),
             background_color: Some(background_color),
+            alpha_mode: AlphaMode::Straight,
+            palette: None,
         };
 
         // call clear to fill the image with the background color (initialize data)
@@ -66,6 +74,53 @@ impl Image {
         image
     }
 
+    /// Creates a new image with the colors of the pixels given by the function.
+    /// The background color of the new image is not set.
+    /// # Arguments
+    /// * ```width``` - The width of the image.
+    /// * ```height``` - The height of the image.
+    /// * ```color_type``` - The color type of the image.
+    /// * ```f``` - The function which returns the color (with straight alpha) of the pixel at the coordinates ```(x, y)```.
+    /// # Returns
+    /// * [Result] which holds the new image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the function returns a color of other type than the color type.
+    pub fn from_fn<F: FnMut(usize, usize) -> Color>(width: usize, height: usize, color_type: ColorType, mut f: F) -> Result<Self, Error> {
+        if color_type.bits_per_pixel() < 8 {
+            let mut image = Self {
+                data: vec![0; color_type.row_len(width) * height],
+                width,
+                height,
+                color_type,
+                background_color: None,
+                alpha_mode: AlphaMode::Straight,
+                palette: None,
+            };
+            image.map_pixels(|x, y, _| f(x, y))?;
+            return Ok(image);
+        }
+        let mut data = Vec::with_capacity(width * height * color_type.bytes_per_pixel());
+        // the pixel data starts with the top row
+        for y in (0..height).rev() {
+            for x in 0..width {
+                let color = f(x, y);
+                if ColorType::from(color) != color_type {
+                    return Err(Error::WrongColor);
+                }
+                data.extend_from_slice(color.as_bytes());
+            }
+        }
+        Ok(Self {
+            data,
+            width,
+            height,
+            color_type,
+            background_color: None,
+            alpha_mode: AlphaMode::Straight,
+            palette: None,
+        })
+    }
+
     /// Returns the width of the image.
     /// # Returns
     /// * The width of the image.
@@ -90,6 +145,55 @@ impl Image {
         self.color_type
     }
 
+    /// Returns the way the alpha channel is stored in the pixel data.
+    /// # Returns
+    /// * The alpha mode of the image.
+    #[inline]
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    /// Changes the way the alpha channel is stored in the pixel data, converting the pixel data.
+    /// Colors passed to and returned from the image are always with straight alpha,
+    /// only the pixel data (see [Image::as_bytes()]) is affected.
+    /// Premultiplied storage keeps the compositing of many translucent layers free of dark fringes.
+    /// Converting to straight alpha loses precision in the color channels of very transparent pixels.
+    /// For color types without an alpha channel, only the mode is changed.
+    /// # Arguments
+    /// * `alpha_mode` - The new alpha mode.
+    pub fn set_alpha_mode(&mut self, alpha_mode: AlphaMode) {
+        if alpha_mode != self.alpha_mode && self.color_type.has_alpha() {
+            let bytes_per_pixel = self.color_type.bytes_per_pixel();
+            for i in (0..self.data.len()).step_by(bytes_per_pixel) {
+                let color = Color::from_bytes(self.color_type, &self.data[i..(i + bytes_per_pixel)]);
+                let new_color = match alpha_mode {
+                    AlphaMode::Straight => color.unpremultiplied(),
+                    AlphaMode::Premultiplied => color.premultiplied(),
+                };
+                self.data[i..(i + bytes_per_pixel)].copy_from_slice(new_color.as_bytes());
+            }
+        }
+        self.alpha_mode = alpha_mode;
+    }
+
+    /// Converts the color with straight alpha to the color as stored in the pixel data.
+    #[inline]
+    pub(crate) fn encode_color(&self, color: Color) -> Color {
+        match self.alpha_mode {
+            AlphaMode::Straight => color,
+            AlphaMode::Premultiplied => color.premultiplied(),
+        }
+    }
+
+    /// Converts the color as stored in the pixel data to the color with straight alpha.
+    #[inline]
+    pub(crate) fn decode_color(&self, color: Color) -> Color {
+        match self.alpha_mode {
+            AlphaMode::Straight => color,
+            AlphaMode::Premultiplied => color.unpremultiplied(),
+        }
+    }
+
     /// Returns the bytes of the image as a native endian slice.
     /// # Returns
     /// * The bytes of the image.
@@ -136,10 +240,7 @@ impl Image {
     /// If the background color is not set, this is a no-op.
     pub fn clear(&mut self) {
         if let Some(color) = self.background_color {
-            let color_slice = color.as_bytes();
-            for i in (0..self.data.len()).step_by(color_slice.len()) {
-                self.data[i..(color_slice.len() + i)].copy_from_slice(color_slice);
-            }
+            self.fill_data(color);
         }
     }
 
@@ -153,15 +254,76 @@ impl Image {
     /// * [Error::WrongColor] if the color is not compatible with the image type.
     pub fn fill_image(&mut self, color: Color) -> Result<(), Error> {
         if ColorType::from(color) == self.color_type {
-            let color_slice = color.as_bytes();
-            for i in (0..self.data.len()).step_by(color_slice.len()) {
-                self.data[i..(color_slice.len() + i)].copy_from_slice(color_slice);
-            }
+            self.fill_data(color);
             Ok(())
         } else {
             Err(Error::WrongColor)
         }
     }
+
+    /// Fills the pixel data with the color of the image type.
+    /// Packed pixels are filled a byte at a time, leaving the padding bits at the ends of the rows zero.
+    /// # Arguments
+    /// * `color` - The color to fill the image with.
+    fn fill_data(&mut self, color: Color) {
+        let color = self.encode_color(color);
+        let color_slice = color.as_bytes();
+        let bits = self.color_type.bits_per_pixel();
+        if bits < 8 {
+            // only the lowest bits of the value are stored, as by setting a single pixel
+            let value = color_slice[0] & ((1 << bits) - 1);
+            let pattern = (0..8 / bits).fold(0, |pattern, _| (pattern << bits) | value);
+            let row_len = self.color_type.row_len(self.width);
+            let padding = row_len * 8 - self.width * bits;
+            for row in self.data.chunks_exact_mut(row_len.max(1)) {
+                row.fill(pattern);
+                if let Some(last) = row.last_mut() {
+                    *last &= 0xFF << padding;
+                }
+            }
+        } else {
+            for i in (0..self.data.len()).step_by(color_slice.len()) {
+                self.data[i..(color_slice.len() + i)].copy_from_slice(color_slice);
+            }
+        }
+    }
+
+    /// Replaces the color of every pixel with the color returned by the function.
+    /// # Arguments
+    /// * ```f``` - The function which returns the new color (with straight alpha) of the pixel from its coordinates ```(x, y)``` and its current color.
+    /// # Returns
+    /// * [Ok] if all pixels were mapped successfully.
+    /// * [Err] if there was an error.
+    /// # Errors
+    /// * [Error::WrongColor] - If the function returns a color of other type than the image type.
+    ///   The pixels mapped before the wrong color keep their new colors.
+    pub fn map_pixels<F: FnMut(usize, usize, Color) -> Color>(&mut self, mut f: F) -> Result<(), Error> {
+        if self.color_type.bits_per_pixel() < 8 {
+            for y in (0..self.height).rev() {
+                for x in 0..self.width {
+                    let color = f(x, y, self.get_unchecked((x, y)));
+                    if ColorType::from(color) != self.color_type {
+                        return Err(Error::WrongColor);
+                    }
+                    self.set_unchecked((x, y), color);
+                }
+            }
+            return Ok(());
+        }
+        let bytes_per_pixel = self.color_type.bytes_per_pixel();
+        for y in (0..self.height).rev() {
+            for x in 0..self.width {
+                let i = self.index_unchecked((x, y));
+                let color = f(x, y, self.decode_color(Color::from_bytes(self.color_type, &self.data[i..i + bytes_per_pixel])));
+                if ColorType::from(color) != self.color_type {
+                    return Err(Error::WrongColor);
+                }
+                let color = self.encode_color(color);
+                self.data[i..i + bytes_per_pixel].copy_from_slice(color.as_bytes());
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -178,9 +340,41 @@ mod tests {
         assert_eq!(image.height, height);
         assert_eq!(image.color_type, ColorType::GRAY8);
         assert_eq!(image.background_color, Some(Color::GRAY8(255)));
+        assert_eq!(image.alpha_mode, AlphaMode::Straight);
         assert_eq!(image.data, vec![255; width * height]);
     }
 
+    #[test]
+    fn test_from_fn() {
+        let image = Image::from_fn(3, 2, ColorType::RGB8, |x, y| Color::RGB8([x as u8, y as u8, 7])).unwrap();
+        assert_eq!(image.background_color, None);
+        assert_eq!(image.get((2, 0)).unwrap(), Color::RGB8([2, 0, 7]));
+        assert_eq!(image.get((0, 1)).unwrap(), Color::RGB8([0, 1, 7]));
+        // the first row of the data is the top row
+        assert_eq!(image.data, vec![0, 1, 7, 1, 1, 7, 2, 1, 7, 0, 0, 7, 1, 0, 7, 2, 0, 7]);
+        assert_eq!(Image::from_fn(3, 2, ColorType::RGB8, |_, _| Color::GRAY8(0)), Err(Error::WrongColor));
+    }
+
+    #[test]
+    fn test_map_pixels() {
+        let mut image = Image::from_fn(3, 2, ColorType::GRAYA8, |x, y| Color::GRAYA8([(x * 10 + y) as u8, 255])).unwrap();
+        image
+            .map_pixels(|x, y, color| match color {
+                Color::GRAYA8([gray, _]) => Color::GRAYA8([gray + (x * 100 + y) as u8, 128]),
+                _ => panic!(),
+            })
+            .unwrap();
+        assert_eq!(image.get((2, 1)).unwrap(), Color::GRAYA8([222, 128]));
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAYA8([0, 128]));
+
+        // colors are passed and returned with straight alpha
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        assert_eq!(image.as_bytes()[4..6], [111, 128]);
+        image.map_pixels(|_, _, color| Color::GRAYA8([color.as_bytes()[0], 255])).unwrap();
+        assert_eq!(image.as_bytes()[4..6], [221, 255]);
+        assert_eq!(image.map_pixels(|_, _, _| Color::GRAY8(0)), Err(Error::WrongColor));
+    }
+
     #[test]
     fn test_getters() {
         let width = 100;
@@ -229,6 +423,55 @@ mod tests {
         assert_eq!(image.data, vec![0; 100 * 100]);
     }
 
+    #[test]
+    fn test_alpha_mode() {
+        let mut image = Image::new(2, 2, Color::RGBA8([255, 128, 0, 128]));
+        assert_eq!(image.alpha_mode(), AlphaMode::Straight);
+
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        assert_eq!(image.alpha_mode(), AlphaMode::Premultiplied);
+        assert_eq!(image.as_bytes(), &[128, 64, 0, 128].repeat(4));
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA8([255, 128, 0, 128]));
+
+        image.fill_image(Color::RGBA8([0, 0, 255, 51])).unwrap();
+        assert_eq!(image.as_bytes(), &[0, 0, 51, 51].repeat(4));
+        image.clear();
+        assert_eq!(image.as_bytes(), &[128, 64, 0, 128].repeat(4));
+
+        image.set((1, 1), Color::RGBA8([255, 255, 255, 0])).unwrap();
+        assert_eq!(image.get((1, 1)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+
+        image.set_alpha_mode(AlphaMode::Straight);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA8([255, 128, 0, 128]));
+        assert_eq!(image.get((1, 1)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+
+        // no alpha channel, only the mode changes
+        let mut image = Image::new(2, 2, Color::RGB8([10, 20, 30]));
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        assert_eq!(image.as_bytes(), &[10, 20, 30].repeat(4));
+    }
+
+    #[test]
+    fn test_premultiplied_layers() {
+        // compositing translucent layers gives the same result in both modes
+        let mut straight = Image::new(4, 4, Color::RGBA16([0, 0, 0, 0]));
+        let mut premultiplied = Image::new(4, 4, Color::RGBA16([0, 0, 0, 0]));
+        premultiplied.set_alpha_mode(AlphaMode::Premultiplied);
+        for (i, color) in [[65_535, 0, 0, 20_000], [0, 65_535, 0, 30_000], [0, 0, 65_535, 40_000]].into_iter().enumerate() {
+            straight.set_transparent((i.., ..), Color::RGBA16(color), 0.8).unwrap();
+            premultiplied.set_transparent((i.., ..), Color::RGBA16(color), 0.8).unwrap();
+        }
+        premultiplied.set_alpha_mode(AlphaMode::Straight);
+        for x in 0..4 {
+            let (Color::RGBA16(a), Color::RGBA16(b)) = (straight.get((x, 0)).unwrap(), premultiplied.get((x, 0)).unwrap()) else {
+                panic!("wrong color type");
+            };
+            for channel in 0..4 {
+                assert!(a[channel].abs_diff(b[channel]) <= 2, "{:?} != {:?}", a, b);
+            }
+        }
+    }
+
     #[test]
     fn test_fill() {
         let mut image = Image::new(100, 100, Color::GRAY8(255));
@@ -237,5 +480,15 @@ mod tests {
         image.fill_image(Color::GRAY8(255)).unwrap();
         assert_eq!(image.data, vec![255; 100 * 100]);
         assert_eq!(image.fill_image(Color::RGB8([255, 0, 0])), Err(Error::WrongColor));
+
+        // packed values out of range keep their lowest bits, whether filled or set
+        let filled = Image::new(8, 1, Color::GRAY1(2));
+        let mut set = Image::new(8, 1, Color::GRAY1(1));
+        set.set((.., 0), Color::GRAY1(2)).unwrap();
+        assert_eq!(filled.as_bytes(), &[0]);
+        assert_eq!(filled.as_bytes(), set.as_bytes());
+        let mut image = Image::new(3, 1, Color::GRAY4(0));
+        image.fill_image(Color::GRAY4(0x1A)).unwrap();
+        assert_eq!(image.as_bytes(), &[0xAA, 0xA0]);
     }
 }
@@ -1,7 +1,8 @@
 //! Indexing functions for the [Image] struct.
 
 use std::ops::{Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive};
-use crate::colors::{Color, ColorType};
+use crate::blending::Blend;
+use crate::colors::Color;
 use crate::error::Error;
 use crate::image::Image;
 
@@ -71,6 +72,7 @@ impl ImageSetIndex for RangeToInclusive<usize> {
 
 impl Image {
     /// Returns the index of the first byte of the pixel at the given coordinates.
+    /// For the color types with less than 8 bits per pixel, it is the index of the byte holding the pixel.
     /// # Arguments
     /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
     /// # Returns
@@ -87,13 +89,14 @@ impl Image {
     }
 
     /// Returns the index of the first byte of the pixel at the given coordinates without performing checks.
+    /// For the color types with less than 8 bits per pixel, it is the index of the byte holding the pixel.
     /// # Arguments
     /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
     /// # Returns
     /// * The index of the first byte of the pixel.
     #[inline]
     pub fn index_unchecked(&self, index: (usize, usize)) -> usize {
-        ((self.height - index.1 - 1) * self.width + index.0) * self.color_type.bytes_per_pixel()
+        (self.height - index.1 - 1) * self.color_type.row_len(self.width) + index.0 * self.color_type.bits_per_pixel() / 8
     }
 
     /// Returns the value of the pixel at the given coordinates.
@@ -117,9 +120,9 @@ impl Image {
     /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
     /// # Returns
     /// * The value of the pixel.
+    #[inline]
     pub fn get_unchecked(&self, index: (usize, usize)) -> Color {
-        let index_temp = self.index_unchecked(index);
-        Color::from_bytes(self.color_type, &self.data[index_temp..index_temp + self.color_type.bytes_per_pixel()])
+        self.view().get_unchecked(index)
     }
 
     /// Sets the color of the pixels at the given locations.
@@ -133,57 +136,24 @@ impl Image {
     /// * [Error::WrongColor] - If the color type of the image does not match the color type of the color.
     #[inline]
     pub fn set<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color) -> Result<(), Error> {
-        if index.0.start() >= self.width || index.1.start() >= self.height {
-            return Err(Error::IndexOutOfBounds);
-        } else if let Some(x) = index.0.end() {
-            if x > self.width {
-                return Err(Error::IndexOutOfBounds);
-            }
-        } else if let Some(y) = index.1.end() {
-            if y > self.height {
-                return Err(Error::IndexOutOfBounds);
-            }
-        } else if ColorType::from(color) != self.color_type {
-            return Err(Error::WrongColor);
-        }
-
-        self.set_unchecked(index, color);
-
-        Ok(())
+        self.view_mut().set(index, color)
     }
 
     /// Sets the color of the pixels at the given locations without performing checks.
     /// # Arguments
     /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
     /// * ```color``` - The color to set.
+    #[inline]
     pub fn set_unchecked<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color) {
-        let x_index_low = index.0.start();  // inclusive
-        let x_index_high = match index.0.end() {  // exclusive
-            Some(x) => x,
-            None => self.width,
-        };
-        let y_index_low = index.1.start();  // inclusive
-        let y_index_high = match index.1.end() {  // exclusive
-            Some(y) => y,
-            None => self.height,
-        };
-        
-        let bytes = color.as_bytes();
-        let x_offset = (x_index_high - x_index_low) * bytes.len();
-        for y in y_index_low..y_index_high {
-            let index_low = self.index_unchecked((x_index_low, y));
-            let index_high = index_low + x_offset;
-            for x in (index_low..index_high).step_by(bytes.len()) {
-                self.data[x..x + bytes.len()].copy_from_slice(bytes);
-            }
-        }
+        self.view_mut().set_unchecked(index, color);
     }
 
     /// Sets the value of the pixels at the given locations by blending the color with the current value at the given coordinates.
+    /// The color is placed over the current value (source-over compositing) with respect to the alpha channels of both colors.
     /// # Arguments
     /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
     /// * ```color``` - The color to set.
-    /// * ```opacity``` - The opacity for blending.
+    /// * ```opacity``` - The opacity for blending. It multiplies the alpha channel of the color.
     /// # Returns
     /// * [Result] which holds [Ok] or [Err] with [Error].
     /// # Errors
@@ -192,112 +162,126 @@ impl Image {
     /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
     #[inline]
     pub fn set_transparent<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color, opacity: f64) -> Result<(), Error> {
-        if index.0.start() >= self.width || index.1.start() >= self.height {
-            return Err(Error::IndexOutOfBounds);
-        }
-        if let Some(x) = index.0.end() {
-            if x > self.width {
-                return Err(Error::IndexOutOfBounds);
-            }
-        }
-        if let Some(y) = index.1.end() {
-            if y > self.height {
-                return Err(Error::IndexOutOfBounds);
-            }
-        }
-        if ColorType::from(color) != self.color_type {
-            return Err(Error::WrongColor);
-        }
-        if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
-            return Err(Error::InvalidOpacity);
-        }
-
-        self.set_transparent_unchecked(index, color, opacity);
-        
-        Ok(())
+        self.view_mut().set_transparent(index, color, opacity)
     }
 
     /// Sets the value of the pixels at the given locations by blending the color with the current value at the given coordinates without performing checks.
+    /// The color is placed over the current value (source-over compositing) with respect to the alpha channels of both colors.
     /// # Arguments
     /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
     /// * ```color``` - The color to set.
-    /// * ```opacity``` - The opacity for blending.
+    /// * ```opacity``` - The opacity for blending. It multiplies the alpha channel of the color.
+    #[inline]
     pub fn set_transparent_unchecked<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color, opacity: f64) {
-        let x_index_low = index.0.start();  // inclusive
-        let x_index_high = match index.0.end() {  // exclusive
-            Some(x) => x,
-            None => self.width,
-        };
-        let y_index_low = index.1.start();  // inclusive
-        let y_index_high = match index.1.end() {  // exclusive
-            Some(y) => y,
-            None => self.height,
-        };
+        self.set_composite_unchecked(index, color, opacity, Blend::default());
+    }
 
-        // background color aware
-        // ===> color = color + (new_color - color) * color_percentage
-        // ===> color = color * (1 - color_percentage) + new_color * color_percentage
-        
-        let bytes = color.as_bytes();
-        let x_offset = (x_index_high - x_index_low) * bytes.len();
-        for y in y_index_low..y_index_high {
-            let index_low = self.index_unchecked((x_index_low, y));
-            let index_high = index_low + x_offset;
-            for x in (index_low..index_high).step_by(bytes.len()) {
-                let current_color = Color::from_bytes(self.color_type, &self.data[x..x + bytes.len()]);
-                let new_color = match (current_color, color) {
-                    (Color::GRAY8(curr_val), Color::GRAY8(new_val)) => {
-                        Color::GRAY8((curr_val as f64 * (1.0 - opacity) + new_val as f64 * opacity).round() as u8)
-                    }
-                    (Color::GRAYA8(curr_val), Color::GRAYA8(new_val)) => {
-                        Color::GRAYA8([
-                            (curr_val[0] as f64 * (1.0 - opacity) + new_val[0] as f64 * opacity).round() as u8,
-                            (curr_val[1] as f64 * (1.0 - opacity) + new_val[1] as f64 * opacity).round() as u8,
-                        ])
-                    }
-                    (Color::GRAY16(curr_val), Color::GRAY16(new_val)) => {
-                        Color::GRAY16((curr_val as f64 * (1.0 - opacity) + new_val as f64 * opacity).round() as u16)
-                    }
-                    (Color::GRAYA16(curr_val), Color::GRAYA16(new_val)) => {
-                        Color::GRAYA16([
-                            (curr_val[0] as f64 * (1.0 - opacity) + new_val[0] as f64 * opacity).round() as u16,
-                            (curr_val[1] as f64 * (1.0 - opacity) + new_val[1] as f64 * opacity).round() as u16,
-                        ])
-                    }
-                    (Color::RGB8(curr_val), Color::RGB8(new_val)) => {
-                        Color::RGB8([
-                            (curr_val[0] as f64 * (1.0 - opacity) + new_val[0] as f64 * opacity).round() as u8,
-                            (curr_val[1] as f64 * (1.0 - opacity) + new_val[1] as f64 * opacity).round() as u8,
-                            (curr_val[2] as f64 * (1.0 - opacity) + new_val[2] as f64 * opacity).round() as u8,
-                        ])
-                    }
-                    (Color::RGBA8(curr_val), Color::RGBA8(new_val)) => {
-                        Color::RGBA8([
-                            (curr_val[0] as f64 * (1.0 - opacity) + new_val[0] as f64 * opacity).round() as u8,
-                            (curr_val[1] as f64 * (1.0 - opacity) + new_val[1] as f64 * opacity).round() as u8,
-                            (curr_val[2] as f64 * (1.0 - opacity) + new_val[2] as f64 * opacity).round() as u8,
-                            (curr_val[3] as f64 * (1.0 - opacity) + new_val[3] as f64 * opacity).round() as u8,
-                        ])
-                    }
-                    (Color::RGB16(curr_val), Color::RGB16(new_val)) => {
-                        Color::RGB16([
-                            (curr_val[0] as f64 * (1.0 - opacity) + new_val[0] as f64 * opacity).round() as u16,
-                            (curr_val[1] as f64 * (1.0 - opacity) + new_val[1] as f64 * opacity).round() as u16,
-                            (curr_val[2] as f64 * (1.0 - opacity) + new_val[2] as f64 * opacity).round() as u16,
-                        ])
-                    }
-                    (Color::RGBA16(curr_val), Color::RGBA16(new_val)) => {
-                        Color::RGBA16([
-                            (curr_val[0] as f64 * (1.0 - opacity) + new_val[0] as f64 * opacity).round() as u16,
-                            (curr_val[1] as f64 * (1.0 - opacity) + new_val[1] as f64 * opacity).round() as u16,
-                            (curr_val[2] as f64 * (1.0 - opacity) + new_val[2] as f64 * opacity).round() as u16,
-                            (curr_val[3] as f64 * (1.0 - opacity) + new_val[3] as f64 * opacity).round() as u16,
-                        ])
-                    }
-                    _ => panic!("color must match the color type of the image"),
-                };
-                self.data[x..x + bytes.len()].copy_from_slice(new_color.as_bytes());
-            }
-        }
+    /// Sets the value of the pixels at the given locations by blending and compositing the color with the current value at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// * ```opacity``` - The opacity of the color. It multiplies the alpha channel of the color.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the color with the current value.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    /// * [Error::WrongColor] - If the color type of the image does not match the color type of the color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    #[inline]
+    pub fn set_composite<RX: ImageSetIndex, RY: ImageSetIndex, B: Into<Blend>>(&mut self, index: (RX, RY), color: Color, opacity: f64, blend: B) -> Result<(), Error> {
+        self.view_mut().set_composite(index, color, opacity, blend)
+    }
+
+    /// Sets the value of the pixels at the given locations by blending and compositing the color with the current value at the given coordinates without performing checks.
+    /// Indexed images with a palette blend the colors of the palette and store the index of the nearest palette color to the result,
+    /// the color can also be a direct color (e.g. one converted from another image).
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// * ```opacity``` - The opacity of the color. It multiplies the alpha channel of the color.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the color with the current value.
+    #[inline]
+    pub fn set_composite_unchecked<RX: ImageSetIndex, RY: ImageSetIndex, B: Into<Blend>>(&mut self, index: (RX, RY), color: Color, opacity: f64, blend: B) {
+        self.view_mut().set_composite_unchecked(index, color, opacity, blend);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blending::Blend;
+    use crate::colors::Color;
+    use crate::error::Error;
+    use crate::image::Image;
+
+    #[test]
+    fn test_packed_pixels() {
+        // rows of 1-bit pixels are padded to whole bytes and start with the most significant bit
+        let mut image = Image::new(10, 2, Color::GRAY1(0));
+        assert_eq!(image.as_bytes(), &[0, 0, 0, 0]);
+        image.set((0, 1), Color::GRAY1(1)).unwrap();
+        image.set((9, 1), Color::GRAY1(1)).unwrap();
+        image.set((2..5, 0), Color::GRAY1(1)).unwrap();
+        assert_eq!(image.as_bytes(), &[0b1000_0000, 0b0100_0000, 0b0011_1000, 0]);
+        assert_eq!(image.set((0, 0), Color::GRAY8(255)), Err(Error::WrongColor));
+        assert_eq!(image.set_composite((0, 0), Color::GRAY1(1), 1.0, Blend::default()), Ok(()));
+        assert_eq!(image.index((9, 1)).unwrap(), 1);
+        assert_eq!(image.get((9, 1)).unwrap(), Color::GRAY1(1));
+        assert_eq!(image.get((8, 1)).unwrap(), Color::GRAY1(0));
+        image.fill_image(Color::GRAY1(1)).unwrap();
+        assert_eq!(image.as_bytes(), &[0xFF, 0b1100_0000, 0xFF, 0b1100_0000]);
+
+        let mut image = Image::new(3, 1, Color::GRAY4(0x0A));
+        assert_eq!(image.as_bytes(), &[0xAA, 0xA0]);
+        image.set((1, 0), Color::GRAY4(3)).unwrap();
+        assert_eq!(image.as_bytes(), &[0xA3, 0xA0]);
+
+        // blending works with the normalized values of the pixels
+        image.set_transparent((1, 0), Color::GRAY4(15), 0.5).unwrap();
+        assert_eq!(image.get((1, 0)).unwrap(), Color::GRAY4(9));
+
+        // drawing doesn't touch the neighbouring pixels in the same bytes
+        let mut image = Image::new(12, 3, Color::GRAY1(0));
+        image.draw_rectangle((3, 0), (8, 2), Color::GRAY1(1), 0, 1.0, crate::CompositeOp::SourceOver).unwrap();
+        assert_eq!(image.as_bytes(), &[0b0001_1111, 0b1000_0000, 0b0001_1111, 0b1000_0000, 0b0001_1111, 0b1000_0000]);
+
+        let mut image = Image::new(2, 1, Color::RGB565(0));
+        image.set((1, 0), Color::RGB565(0xF800)).unwrap();
+        assert_eq!(image.as_bytes(), &[0, 0, 0xF800u16.to_ne_bytes()[0], 0xF800u16.to_ne_bytes()[1]]);
+    }
+
+    #[test]
+    fn test_set_transparent_opaque_image() {
+        let mut image = Image::new(10, 10, Color::RGB8([0, 0, 0]));
+        image.set_transparent((0..5, ..), Color::RGB8([200, 100, 50]), 0.5).unwrap();
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGB8([100, 50, 25]));
+        assert_eq!(image.get((5, 0)).unwrap(), Color::RGB8([0, 0, 0]));
+
+        let mut image = Image::new(10, 10, Color::GRAY16(0));
+        image.set_transparent((.., ..), Color::GRAY16(65_535), 0.25).unwrap();
+        assert_eq!(image.get((9, 9)).unwrap(), Color::GRAY16(16_384));
+    }
+
+    #[test]
+    fn test_set_transparent_alpha() {
+        // half transparent color onto transparent canvas keeps the color
+        let mut image = Image::new(10, 10, Color::RGBA8([0, 0, 0, 0]));
+        image.set_transparent((.., ..), Color::RGBA8([255, 0, 0, 128]), 1.0).unwrap();
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 128]));
+
+        // alpha of the color and the opacity are combined
+        let mut image = Image::new(10, 10, Color::RGBA8([0, 0, 0, 0]));
+        image.set_transparent((.., ..), Color::RGBA8([255, 0, 0, 128]), 0.5).unwrap();
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 64]));
+
+        // half transparent color onto half transparent canvas
+        let mut image = Image::new(10, 10, Color::RGBA16([0, 0, 65_535, 32_768]));
+        image.set_transparent((.., ..), Color::RGBA16([65_535, 0, 0, 32_768]), 1.0).unwrap();
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA16([43_690, 0, 21_845, 49_152]));
+
+        // transparent color doesn't change the image
+        let mut image = Image::new(10, 10, Color::GRAYA8([100, 200]));
+        image.set_transparent((.., ..), Color::GRAYA8([255, 0]), 1.0).unwrap();
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAYA8([100, 200]));
     }
 }
@@ -1,17 +1,23 @@
 //! IO functions for images.
 
-use crate::colors::{Color, ColorType};
+use crate::colors::{AlphaMode, Color, ColorType};
 use crate::error::Error;
 use crate::image::Image;
+#[cfg(feature = "image")]
+use crate::palette::Palette;
 
 #[cfg(feature = "image")]
-use std::{fs::remove_file, path::Path};
+use std::{
+    fs::{remove_file, write},
+    path::Path,
+};
 
 #[cfg(feature = "image")]
-use image::{io::Reader as ImageReader, save_buffer, ColorType as ImageColorType, DynamicImage};
+use image::{io::Reader as ImageReader, save_buffer, ColorType as ImageColorType, DynamicImage, ImageFormat};
 
 impl Image {
     /// Creates a new image from the given bytes.
+    /// The bytes are expected to hold colors with straight alpha.
     /// # Arguments
     /// * ```width``` - The width of the image.
     /// * ```height``` - The height of the image.
@@ -20,12 +26,29 @@ impl Image {
     /// # Returns
     /// * [Result] which holds new [Image] or [Err] with [Error].
     /// # Errors
-    /// * [Error::InvalidSize] - If the size of the bytes is not equal to width * height * bytes per pixel.
+    /// * [Error::InvalidSize] - If the size of the bytes is not equal to height * row length (see [ColorType::row_len()]).
     pub fn from_bytes(width: usize, height: usize, color_type: ColorType, bytes: &[u8]) -> Result<Image, Error> {
         // check for valid size
-        if bytes.len() != width * height * color_type.bytes_per_pixel() || bytes.is_empty() {
+        if bytes.len() != color_type.row_len(width) * height || bytes.is_empty() {
             return Err(Error::InvalidSize);
         }
+        if color_type.bits_per_pixel() < 8 {
+            // packed pixels are compared one by one, as a byte holds more of them
+            let mut image = Self {
+                width,
+                height,
+                color_type,
+                data: bytes.to_vec(),
+                background_color: None,
+                alpha_mode: AlphaMode::Straight,
+                palette: None,
+            };
+            let first = image.get_unchecked((0, 0));
+            if (0..height).all(|y| (0..width).all(|x| image.get_unchecked((x, y)) == first)) {
+                image.background_color = Some(first);
+            }
+            return Ok(image);
+        }
 
         // check if all pixels have the same color
         let mut same_data = true;
@@ -52,6 +75,8 @@ impl Image {
             color_type,
             data: bytes.to_vec(),
             background_color,
+            alpha_mode: AlphaMode::Straight,
+            palette: None,
         })
     }
 
@@ -68,6 +93,13 @@ impl Image {
 
     /// Writes the image to the given file. Requires the ```image``` feature.
     /// File format is determined by the file extension.
+    /// Images with premultiplied alpha are written with straight alpha.
+    /// Floating point images need a format which supports them (e.g. OpenEXR), grayscale ones are written as RGB.
+    /// Images with the channels in other than the RGB order are written as RGB(A).
+    /// Images with packed pixels are written as 8-bit grayscale or RGB.
+    /// Indexed images with a palette are written as PNG and GIF with their palette and indices,
+    /// GIF only if at most one color of the palette is transparent and no color is translucent.
+    /// Otherwise, indexed images are written with the colors of their palette as RGBA, formats like GIF index them again when encoding.
     /// # Arguments
     /// * ```path``` - The path to the file.
     /// * ```overwrite``` - Whether to overwrite the file if it already exists.
@@ -81,9 +113,84 @@ impl Image {
                 return Err(Box::new(Error::FileExists));
             }
         }
-        save_buffer(path, self.as_bytes(), self.width as u32, self.height as u32, ImageColorType::from(self.color_type))?;
+        if let (ColorType::INDEXED8, Some(palette)) = (self.color_type, &self.palette) {
+            if self.save_indexed(path, palette)? {
+                return Ok(());
+            }
+        }
+        // floating point grayscale images, channel orders other than RGB, packed and indexed pixels are not supported by the image crate
+        let saved_color_type = match self.color_type {
+            ColorType::GRAY32F => ColorType::RGB32F,
+            ColorType::GRAYA32F => ColorType::RGBA32F,
+            ColorType::BGR8 => ColorType::RGB8,
+            ColorType::BGRA8 | ColorType::ARGB8 => ColorType::RGBA8,
+            ColorType::BGR16 => ColorType::RGB16,
+            ColorType::BGRA16 | ColorType::ARGB16 => ColorType::RGBA16,
+            ColorType::GRAY1 | ColorType::GRAY4 => ColorType::GRAY8,
+            ColorType::RGB565 | ColorType::RGB332 => ColorType::RGB8,
+            ColorType::INDEXED8 => ColorType::RGBA8,
+            color_type => color_type,
+        };
+        if saved_color_type != self.color_type {
+            let mut image = self.clone();
+            image.set_alpha_mode(AlphaMode::Straight);
+            image.convert(saved_color_type);
+            save_buffer(path, image.as_bytes(), self.width as u32, self.height as u32, ImageColorType::from(image.color_type))?;
+        } else if self.alpha_mode == AlphaMode::Premultiplied && self.color_type.has_alpha() {
+            let mut image = self.clone();
+            image.set_alpha_mode(AlphaMode::Straight);
+            save_buffer(path, image.as_bytes(), self.width as u32, self.height as u32, ImageColorType::from(self.color_type))?;
+        } else {
+            save_buffer(path, self.as_bytes(), self.width as u32, self.height as u32, ImageColorType::from(self.color_type))?;
+        }
         Ok(())
     }
+
+    /// Writes the indexed image with its palette, if the file format supports palettes (PNG and GIF).
+    /// Indices without a color in the palette are written with opaque black color, as they are resolved.
+    /// # Arguments
+    /// * ```path``` - The path to the file.
+    /// * ```palette``` - The palette of the image.
+    /// # Returns
+    /// * [Result] which holds ```true``` if the image was written, ```false``` if the format can't hold the palette, or [Err].
+    #[cfg(feature = "image")]
+    fn save_indexed(&self, path: &Path, palette: &Palette) -> Result<bool, Box<dyn std::error::Error>> {
+        // the palette is extended to cover all indices of the image
+        let len = palette.len().max(self.data.iter().max().map_or(0, |&index| index as usize + 1));
+        let colors: Vec<u8> = (0..len).flat_map(|index| palette.resolve(Color::INDEXED8(index as u8)).as_bytes().to_vec()).collect();
+        let rgb: Vec<u8> = colors.chunks_exact(4).flat_map(|color| &color[..3]).copied().collect();
+        let alpha: Vec<u8> = colors.chunks_exact(4).map(|color| color[3]).collect();
+
+        let mut bytes = Vec::new();
+        match ImageFormat::from_path(path) {
+            Ok(ImageFormat::Png) => {
+                let mut encoder = png::Encoder::new(&mut bytes, self.width as u32, self.height as u32);
+                encoder.set_color(png::ColorType::Indexed);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_palette(rgb);
+                encoder.set_trns(alpha);
+                let mut writer = encoder.write_header()?;
+                writer.write_image_data(&self.data)?;
+                writer.finish()?;
+            }
+            Ok(ImageFormat::Gif) => {
+                // GIF has at most one fully transparent color
+                let mut transparent = alpha.iter().enumerate().filter(|(_, &alpha)| alpha != u8::MAX);
+                let transparent_index = transparent.next().map(|(index, _)| index as u8);
+                let translucent = alpha.iter().any(|&alpha| alpha != 0 && alpha != u8::MAX);
+                if transparent.next().is_some() || translucent || self.width > u16::MAX as usize || self.height > u16::MAX as usize {
+                    return Ok(false);
+                }
+                let (width, height) = (self.width as u16, self.height as u16);
+                let mut encoder = gif::Encoder::new(&mut bytes, width, height, &[])?;
+                encoder.write_frame(&gif::Frame::from_palette_pixels(width, height, self.data.as_slice(), rgb, transparent_index))?;
+                encoder.into_inner()?;
+            }
+            _ => return Ok(false),
+        }
+        write(path, bytes)?;
+        Ok(true)
+    }
 }
 
 #[cfg(test)]
@@ -91,6 +198,10 @@ mod tests {
     use crate::colors::{Color, ColorType};
     use crate::image::Image;
     #[cfg(feature = "image")]
+    use crate::palette::Palette;
+    #[cfg(feature = "image")]
+    use std::io::Cursor;
+    #[cfg(feature = "image")]
     use tempfile::tempdir;
 
     #[test]
@@ -160,4 +271,91 @@ mod tests {
     fn io_rgba16() {
         test_file_io(Color::RGBA16([u16::MAX - 1, u16::MAX - 2, u16::MAX - 3, u16::MAX - 4]));
     }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn io_channel_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("img.png");
+
+        let image = Image::new(10, 10, Color::ARGB8([200, 10, 20, 30]));
+        image.to_file(&path, true).unwrap();
+        assert_eq!(Image::from_file(&path).unwrap(), Image::new(10, 10, Color::RGBA8([10, 20, 30, 200])));
+
+        let image = Image::new(10, 10, Color::BGR16([1, 2, 3]));
+        image.to_file(&path, true).unwrap();
+        assert_eq!(Image::from_file(&path).unwrap(), Image::new(10, 10, Color::RGB16([3, 2, 1])));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn io_packed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("img.png");
+
+        let mut image = Image::new(10, 10, Color::GRAY1(0));
+        image.set((3, 4), Color::GRAY1(1)).unwrap();
+        image.to_file(&path, true).unwrap();
+        image.convert(ColorType::GRAY8);
+        assert_eq!(Image::from_file(&path).unwrap().as_bytes(), image.as_bytes());
+
+        let image = Image::new(10, 10, Color::RGB565(0xF800));
+        image.to_file(&path, true).unwrap();
+        assert_eq!(Image::from_file(&path).unwrap(), Image::new(10, 10, Color::RGB8([255, 0, 0])));
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn io_indexed() {
+        let dir = tempdir().unwrap();
+
+        let mut image = Image::new(10, 10, Color::RGBA8([255, 128, 0, 255]));
+        image.set((2..5, 3), Color::RGBA8([0, 0, 255, 255])).unwrap();
+        image.convert(ColorType::INDEXED8);
+        let mut expected = image.clone();
+        expected.convert(ColorType::RGBA8);
+        for name in ["img.png", "img.gif"] {
+            let path = dir.path().join(name);
+            image.to_file(&path, true).unwrap();
+            assert_eq!(Image::from_file(&path).unwrap().as_bytes(), expected.as_bytes());
+        }
+
+        // the palette and the indices are kept, even with unused and missing colors
+        let palette = Palette::new(&[Color::RGBA8([0, 0, 255, 0]), Color::RGB8([255, 0, 0]), Color::RGB8([0, 255, 0])]).unwrap();
+        image.set_palette(palette).unwrap();
+        image.set((0, 0), Color::INDEXED8(3)).unwrap();
+        let path = dir.path().join("img.png");
+        image.to_file(&path, true).unwrap();
+        let mut reader = png::Decoder::new(Cursor::new(std::fs::read(&path).unwrap())).read_info().unwrap();
+        assert_eq!(reader.info().palette.as_deref(), Some(&[0, 0, 255, 255, 0, 0, 0, 255, 0, 0, 0, 0][..]));
+        assert_eq!(reader.info().trns.as_deref(), Some(&[0, 255, 255, 255][..]));
+        let mut data = vec![0; reader.output_buffer_size().unwrap()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!(data, image.as_bytes());
+
+        let path = dir.path().join("img.gif");
+        image.to_file(&path, true).unwrap();
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(std::fs::File::open(&path).unwrap()).unwrap();
+        let frame = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(frame.palette.as_deref(), Some(&[0, 0, 255, 255, 0, 0, 0, 255, 0, 0, 0, 0][..]));
+        assert_eq!(frame.transparent, Some(0));
+        assert_eq!(&*frame.buffer, image.as_bytes());
+    }
+
+    #[test]
+    #[cfg(feature = "image")]
+    fn io_float() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("img.exr");
+
+        let image = Image::new(10, 10, Color::RGBA32F([0.25, 4.0, 0.0, 0.5]));
+        image.to_file(&path, true).unwrap();
+        assert_eq!(Image::from_file(&path).unwrap(), image);
+
+        let gray = Image::new(10, 10, Color::GRAY32F(2.5));
+        gray.to_file(&path, true).unwrap();
+        assert_eq!(Image::from_file(&path).unwrap().get((3, 3)).unwrap(), Color::RGB32F([2.5, 2.5, 2.5]));
+    }
 }
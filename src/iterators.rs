@@ -0,0 +1,579 @@
+//! A module containing the iterators over the pixels and rows of the [Image].
+//!
+//! The iterators go through the pixel data in the order it is stored, row by row from the top (the largest y coordinate) to the bottom,
+//! and every row from the left to the right. Colors are returned with straight alpha, as by [Image::get()].
+//! Packed pixels with less than 8 bits don't have bytes of their own, so they can be read, but not borrowed mutably one by one.
+
+use crate::colors::{AlphaMode, Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use crate::typed::Pixel;
+use crate::view::ImageView;
+use std::iter::FusedIterator;
+use std::ops::Range;
+use std::slice::{ChunksExact, ChunksExactMut};
+
+/// Converts the bytes of the pixel to the color with straight alpha.
+/// # Arguments
+/// * ```bytes``` - The bytes of the pixel.
+/// * ```color_type``` - The color type of the image.
+/// * ```alpha_mode``` - The alpha mode of the image.
+/// # Returns
+/// * The color of the pixel.
+#[inline]
+fn decode(bytes: &[u8], color_type: ColorType, alpha_mode: AlphaMode) -> Color {
+    let color = Color::from_bytes(color_type, bytes);
+    match alpha_mode {
+        AlphaMode::Straight => color,
+        AlphaMode::Premultiplied => color.unpremultiplied(),
+    }
+}
+
+/// Returns the color of the packed pixel at the position in the pixel data.
+/// # Arguments
+/// * ```view``` - The view of the image.
+/// * ```position``` - The number of the pixels before the pixel, counted row by row from the top.
+/// # Returns
+/// * The color of the pixel.
+#[inline]
+fn packed_color(view: &ImageView, position: usize) -> Color {
+    let width = view.width();
+    view.get_unchecked((position % width, view.height() - position / width - 1))
+}
+
+/// The source of the colors of the [Pixels]
+#[derive(Debug, Clone)]
+enum PixelSource<'a> {
+    /// The bytes of the pixels with at least 8 bits
+    Bytes(ChunksExact<'a, u8>),
+    /// The view of the packed pixels, which don't have bytes of their own
+    Packed {
+        /// The view of the image
+        view: ImageView<'a>,
+        /// The position of the next pixel in the pixel data
+        front: usize,
+        /// The position after the last pixel in the pixel data
+        back: usize,
+    },
+}
+
+/// An iterator over the colors of the pixels, created by [Image::pixels()]
+#[derive(Debug, Clone)]
+pub struct Pixels<'a> {
+    /// The source of the colors
+    source: PixelSource<'a>,
+    /// The color type of the image
+    color_type: ColorType,
+    /// The alpha mode of the image
+    alpha_mode: AlphaMode,
+}
+impl<'a> Pixels<'a> {
+    /// Creates a new iterator over the pixels of the rows of the image.
+    /// # Arguments
+    /// * ```image``` - The image.
+    /// * ```rows``` - The rows counted from the top.
+    /// # Returns
+    /// * The new iterator.
+    fn new(image: &'a Image, rows: Range<usize>) -> Self {
+        let source = if image.color_type.bits_per_pixel() >= 8 {
+            let row_len = image.color_type.row_len(image.width);
+            PixelSource::Bytes(image.data[rows.start * row_len..rows.end * row_len].chunks_exact(image.color_type.bytes_per_pixel()))
+        } else {
+            PixelSource::Packed {
+                view: image.view(),
+                front: rows.start * image.width,
+                back: rows.end * image.width,
+            }
+        };
+        Self {
+            source,
+            color_type: image.color_type,
+            alpha_mode: image.alpha_mode,
+        }
+    }
+}
+impl Iterator for Pixels<'_> {
+    type Item = Color;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            PixelSource::Bytes(chunks) => chunks.next().map(|bytes| decode(bytes, self.color_type, self.alpha_mode)),
+            PixelSource::Packed { view, front, back } => {
+                if front == back {
+                    return None;
+                }
+                *front += 1;
+                Some(packed_color(view, *front - 1))
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.source {
+            PixelSource::Bytes(chunks) => chunks.size_hint(),
+            PixelSource::Packed { front, back, .. } => (back - front, Some(back - front)),
+        }
+    }
+}
+impl DoubleEndedIterator for Pixels<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            PixelSource::Bytes(chunks) => chunks.next_back().map(|bytes| decode(bytes, self.color_type, self.alpha_mode)),
+            PixelSource::Packed { view, front, back } => {
+                if front == back {
+                    return None;
+                }
+                *back -= 1;
+                Some(packed_color(view, *back))
+            }
+        }
+    }
+}
+impl ExactSizeIterator for Pixels<'_> {}
+impl FusedIterator for Pixels<'_> {}
+
+/// An iterator over the coordinates ```(x, y)``` and the colors of the pixels, created by [Image::enumerate_pixels()]
+#[derive(Debug, Clone)]
+pub struct EnumeratePixels<'a> {
+    /// The colors of the pixels
+    pixels: Pixels<'a>,
+    /// The width of the image
+    width: usize,
+    /// The height of the image
+    height: usize,
+    /// The position of the next pixel in the pixel data
+    position: usize,
+}
+impl Iterator for EnumeratePixels<'_> {
+    type Item = ((usize, usize), Color);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let color = self.pixels.next()?;
+        let index = (self.position % self.width, self.height - self.position / self.width - 1);
+        self.position += 1;
+        Some((index, color))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.pixels.size_hint()
+    }
+}
+impl DoubleEndedIterator for EnumeratePixels<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let color = self.pixels.next_back()?;
+        // the pixels left before the taken one are the ones still in the iterator
+        let position = self.position + self.pixels.len();
+        Some(((position % self.width, self.height - position / self.width - 1), color))
+    }
+}
+impl ExactSizeIterator for EnumeratePixels<'_> {}
+impl FusedIterator for EnumeratePixels<'_> {}
+
+/// A mutable reference to one pixel of the image, yielded by [Image::pixels_mut()]
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub struct PixelMut<'a> {
+    /// The bytes of the pixel
+    bytes: &'a mut [u8],
+    /// The color type of the image
+    color_type: ColorType,
+    /// The alpha mode of the image
+    alpha_mode: AlphaMode,
+}
+impl PixelMut<'_> {
+    /// Returns the color of the pixel.
+    /// # Returns
+    /// * The color of the pixel with straight alpha.
+    #[inline]
+    pub fn get(&self) -> Color {
+        decode(self.bytes, self.color_type, self.alpha_mode)
+    }
+
+    /// Sets the color of the pixel.
+    /// # Arguments
+    /// * ```color``` - The color with straight alpha.
+    /// # Returns
+    /// * [Ok] if the color was set successfully.
+    /// * [Err] if there was an error.
+    /// # Errors
+    /// * [Error::WrongColor] - If the color is not compatible with the image type.
+    #[inline]
+    pub fn set(&mut self, color: Color) -> Result<(), Error> {
+        if ColorType::from(color) != self.color_type {
+            return Err(Error::WrongColor);
+        }
+        let color = match self.alpha_mode {
+            AlphaMode::Straight => color,
+            AlphaMode::Premultiplied => color.premultiplied(),
+        };
+        self.bytes.copy_from_slice(color.as_bytes());
+        Ok(())
+    }
+
+    /// Returns the pixel as the typed pixel.
+    /// # Returns
+    /// * The pixel with straight alpha, [None] if the pixel type doesn't match the color type of the image.
+    #[inline]
+    pub fn get_pixel<P: Pixel>(&self) -> Option<P> {
+        P::from_color(self.get())
+    }
+
+    /// Sets the pixel from the typed pixel.
+    /// The pixel data is not aligned for the channels of the pixel types, so the pixel can't be borrowed as ```&mut P```;
+    /// use [crate::typed::TypedImage::as_mut_slice()] for the typed references to the pixels.
+    /// # Arguments
+    /// * ```pixel``` - The pixel with straight alpha.
+    /// # Returns
+    /// * [Ok] if the pixel was set successfully.
+    /// * [Err] if there was an error.
+    /// # Errors
+    /// * [Error::WrongColor] - If the pixel type doesn't match the color type of the image.
+    #[inline]
+    pub fn set_pixel<P: Pixel>(&mut self, pixel: P) -> Result<(), Error> {
+        self.set(pixel.to_color())
+    }
+
+    /// Returns the bytes of the pixel as stored in the pixel data.
+    /// # Returns
+    /// * The native endian bytes of the pixel.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes
+    }
+
+    /// Returns the bytes of the pixel as stored in the pixel data as mutable.
+    /// # Returns
+    /// * The mutable native endian bytes of the pixel.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.bytes
+    }
+}
+
+/// An iterator over the mutable references to the pixels, created by [Image::pixels_mut()]
+#[derive(Debug)]
+pub struct PixelsMut<'a> {
+    /// The bytes of the pixels
+    chunks: ChunksExactMut<'a, u8>,
+    /// The color type of the image
+    color_type: ColorType,
+    /// The alpha mode of the image
+    alpha_mode: AlphaMode,
+}
+impl<'a> Iterator for PixelsMut<'a> {
+    type Item = PixelMut<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(|bytes| PixelMut {
+            bytes,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+impl DoubleEndedIterator for PixelsMut<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(|bytes| PixelMut {
+            bytes,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+        })
+    }
+}
+impl ExactSizeIterator for PixelsMut<'_> {}
+impl FusedIterator for PixelsMut<'_> {}
+
+/// An iterator over the y coordinates and the pixels of the rows, created by [Image::rows()]
+#[derive(Debug, Clone)]
+pub struct Rows<'a> {
+    /// The image
+    image: &'a Image,
+    /// The y coordinates of the rows left
+    rows: Range<usize>,
+}
+impl<'a> Rows<'a> {
+    /// Returns the y coordinate and the pixels of the row.
+    /// # Arguments
+    /// * ```y``` - The y coordinate of the row.
+    /// # Returns
+    /// * The y coordinate and the iterator over the pixels of the row.
+    #[inline]
+    fn row(&self, y: usize) -> (usize, Pixels<'a>) {
+        let row = self.image.height - y - 1;
+        (y, Pixels::new(self.image, row..(row + 1)))
+    }
+}
+impl<'a> Iterator for Rows<'a> {
+    type Item = (usize, Pixels<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // the rows go from the top, so the front is the largest y coordinate
+        self.rows.next_back().map(|y| self.row(y))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+impl DoubleEndedIterator for Rows<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|y| self.row(y))
+    }
+}
+impl ExactSizeIterator for Rows<'_> {}
+impl FusedIterator for Rows<'_> {}
+
+/// An iterator over the y coordinates and the mutable pixels of the rows, created by [Image::rows_mut()]
+#[derive(Debug)]
+pub struct RowsMut<'a> {
+    /// The bytes of the rows
+    chunks: ChunksExactMut<'a, u8>,
+    /// The color type of the image
+    color_type: ColorType,
+    /// The alpha mode of the image
+    alpha_mode: AlphaMode,
+    /// The number of the rows taken from the bottom
+    below: usize,
+}
+impl<'a> RowsMut<'a> {
+    /// Returns the y coordinate and the pixels of the row.
+    /// # Arguments
+    /// * ```y``` - The y coordinate of the row.
+    /// * ```row``` - The bytes of the row.
+    /// # Returns
+    /// * The y coordinate and the iterator over the mutable pixels of the row.
+    #[inline]
+    fn row(&self, y: usize, row: &'a mut [u8]) -> (usize, PixelsMut<'a>) {
+        (
+            y,
+            PixelsMut {
+                chunks: row.chunks_exact_mut(self.color_type.bytes_per_pixel()),
+                color_type: self.color_type,
+                alpha_mode: self.alpha_mode,
+            },
+        )
+    }
+}
+impl<'a> Iterator for RowsMut<'a> {
+    type Item = (usize, PixelsMut<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = self.chunks.next()?;
+        // the y coordinate of the row is the number of the rows below it
+        Some(self.row(self.chunks.len() + self.below, row))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+impl DoubleEndedIterator for RowsMut<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let row = self.chunks.next_back()?;
+        self.below += 1;
+        Some(self.row(self.below - 1, row))
+    }
+}
+impl ExactSizeIterator for RowsMut<'_> {}
+impl FusedIterator for RowsMut<'_> {}
+
+impl Image {
+    /// Returns the iterator over the colors of the pixels, row by row from the top.
+    /// # Returns
+    /// * The iterator over the colors with straight alpha.
+    pub fn pixels(&self) -> Pixels<'_> {
+        Pixels::new(self, 0..self.height)
+    }
+
+    /// Returns the iterator over the coordinates and the colors of the pixels, row by row from the top.
+    /// # Returns
+    /// * The iterator over the coordinates ```(x, y)``` and the colors with straight alpha.
+    pub fn enumerate_pixels(&self) -> EnumeratePixels<'_> {
+        EnumeratePixels {
+            pixels: self.pixels(),
+            width: self.width,
+            height: self.height,
+            position: 0,
+        }
+    }
+
+    /// Returns the iterator over the mutable references to the pixels, row by row from the top.
+    /// # Returns
+    /// * [Result] which holds the iterator over the pixels or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image has less than 8 bits per pixel, packed pixels share their bytes.
+    pub fn pixels_mut(&mut self) -> Result<PixelsMut<'_>, Error> {
+        if self.color_type.bits_per_pixel() < 8 {
+            return Err(Error::WrongColor);
+        }
+        Ok(PixelsMut {
+            chunks: self.data.chunks_exact_mut(self.color_type.bytes_per_pixel()),
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+        })
+    }
+
+    /// Returns the iterator over the rows of the image, from the top.
+    /// # Returns
+    /// * The iterator over the y coordinates of the rows and the iterators over their pixels.
+    pub fn rows(&self) -> Rows<'_> {
+        // images without pixels have no rows
+        Rows {
+            image: self,
+            rows: 0..if self.width == 0 { 0 } else { self.height },
+        }
+    }
+
+    /// Returns the iterator over the mutable rows of the image, from the top.
+    /// # Returns
+    /// * [Result] which holds the iterator over the y coordinates of the rows and the iterators over their mutable pixels, or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image has less than 8 bits per pixel, packed pixels share their bytes.
+    pub fn rows_mut(&mut self) -> Result<RowsMut<'_>, Error> {
+        if self.color_type.bits_per_pixel() < 8 {
+            return Err(Error::WrongColor);
+        }
+        // images without pixels have no rows
+        let row_len = (self.width * self.color_type.bytes_per_pixel()).max(1);
+        Ok(RowsMut {
+            chunks: self.data.chunks_exact_mut(row_len),
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            below: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typed::{Bgr8, Rgb8, Rgba8};
+
+    #[test]
+    fn test_pixels() {
+        let image = Image::from_fn(3, 2, ColorType::RGB16, |x, y| Color::RGB16([x as u16, y as u16, 1_000])).unwrap();
+        let pixels: Vec<Color> = image.pixels().collect();
+        assert_eq!(pixels.len(), 6);
+        assert_eq!(image.pixels().len(), 6);
+        assert_eq!(pixels[0], Color::RGB16([0, 1, 1_000]));
+        assert_eq!(pixels[5], Color::RGB16([2, 0, 1_000]));
+        assert_eq!(image.pixels().next_back(), Some(Color::RGB16([2, 0, 1_000])));
+
+        for ((x, y), color) in image.enumerate_pixels() {
+            assert_eq!(image.get((x, y)).unwrap(), color);
+        }
+        assert_eq!(image.enumerate_pixels().count(), 6);
+        assert_eq!(image.enumerate_pixels().nth(4), Some(((1, 0), Color::RGB16([1, 0, 1_000]))));
+        let mut enumerated: Vec<((usize, usize), Color)> = image.enumerate_pixels().rev().collect();
+        enumerated.reverse();
+        assert_eq!(enumerated, image.enumerate_pixels().collect::<Vec<_>>());
+        let mut enumerate = image.enumerate_pixels();
+        assert_eq!(enumerate.next_back(), Some(((2, 0), Color::RGB16([2, 0, 1_000]))));
+        assert_eq!(enumerate.next(), Some(((0, 1), Color::RGB16([0, 1, 1_000]))));
+        assert_eq!(enumerate.next_back(), Some(((1, 0), Color::RGB16([1, 0, 1_000]))));
+        assert_eq!(enumerate.len(), 3);
+
+        // colors are returned with straight alpha
+        let mut image = Image::new(2, 2, Color::RGBA8([255, 0, 0, 128]));
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        assert!(image.pixels().all(|color| color == Color::RGBA8([255, 0, 0, 128])));
+    }
+
+    #[test]
+    fn test_pixels_mut() {
+        let mut image = Image::new(2, 2, Color::GRAYA8([10, 255]));
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        for (i, mut pixel) in image.pixels_mut().unwrap().enumerate() {
+            assert_eq!(pixel.get(), Color::GRAYA8([10, 255]));
+            assert_eq!(pixel.set(Color::GRAY8(0)), Err(Error::WrongColor));
+            pixel.set(Color::GRAYA8([200, i as u8 * 50])).unwrap();
+        }
+        assert_eq!(image.as_bytes(), &[0, 0, 39, 50, 78, 100, 118, 150]);
+
+        let mut last = image.pixels_mut().unwrap().next_back().unwrap();
+        assert_eq!(last.as_bytes(), &[118, 150]);
+        last.as_bytes_mut()[0] = 150;
+        assert_eq!(image.get((1, 0)).unwrap(), Color::GRAYA8([255, 150]));
+
+        // typed access to the pixels
+        let mut image = Image::new(2, 1, Color::RGB8([1, 2, 3]));
+        for mut pixel in image.pixels_mut().unwrap() {
+            assert_eq!(pixel.get_pixel::<Rgb8>(), Some(Rgb8([1, 2, 3])));
+            assert_eq!(pixel.get_pixel::<Bgr8>(), None);
+            assert_eq!(pixel.set_pixel(Rgba8([0, 0, 0, 0])), Err(Error::WrongColor));
+            pixel.set_pixel(Rgb8([4, 5, 6])).unwrap();
+        }
+        assert_eq!(image.as_bytes(), &[4, 5, 6, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_rows() {
+        let mut image = Image::from_fn(2, 3, ColorType::GRAY16, |x, y| Color::GRAY16((y * 10 + x) as u16)).unwrap();
+        let rows: Vec<(usize, Vec<Color>)> = image.rows().map(|(y, row)| (y, row.collect())).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (2, vec![Color::GRAY16(20), Color::GRAY16(21)]),
+                (1, vec![Color::GRAY16(10), Color::GRAY16(11)]),
+                (0, vec![Color::GRAY16(0), Color::GRAY16(1)]),
+            ]
+        );
+        assert_eq!(image.rows().len(), 3);
+        let mut rows = image.rows();
+        assert_eq!(rows.next_back().map(|(y, row)| (y, row.collect::<Vec<_>>())), Some((0, vec![Color::GRAY16(0), Color::GRAY16(1)])));
+        assert_eq!(rows.next().map(|(y, _)| y), Some(2));
+        assert_eq!(rows.next_back().map(|(y, _)| y), Some(1));
+        assert!(rows.next().is_none());
+
+        for (y, row) in image.rows_mut().unwrap() {
+            for (x, mut pixel) in row.enumerate() {
+                pixel.set(Color::GRAY16((x * 100 + y) as u16)).unwrap();
+            }
+        }
+        assert_eq!(image.get((1, 2)).unwrap(), Color::GRAY16(102));
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAY16(0));
+        let mut rows = image.rows_mut().unwrap();
+        assert_eq!(rows.next_back().map(|(y, _)| y), Some(0));
+        assert_eq!(rows.next_back().map(|(y, _)| y), Some(1));
+        assert_eq!(rows.next().map(|(y, _)| y), Some(2));
+        assert!(rows.next().is_none());
+
+        // empty images have no rows
+        let empty = Image::new(0, 3, Color::GRAY8(0));
+        assert_eq!(empty.rows().count(), 0);
+        assert_eq!(empty.pixels().count(), 0);
+    }
+
+    #[test]
+    fn test_packed() {
+        let mut image = Image::from_bytes(3, 2, ColorType::GRAY4, &[0x12, 0x30, 0x45, 0x60]).unwrap();
+        let pixels: Vec<Color> = image.pixels().collect();
+        assert_eq!(pixels, [1, 2, 3, 4, 5, 6].map(Color::GRAY4));
+        assert_eq!(image.pixels().next_back(), Some(Color::GRAY4(6)));
+        assert_eq!(image.enumerate_pixels().nth(4), Some(((1, 0), Color::GRAY4(5))));
+        let rows: Vec<(usize, Vec<Color>)> = image.rows().map(|(y, row)| (y, row.collect())).collect();
+        assert_eq!(rows, vec![(1, [1, 2, 3].map(Color::GRAY4).to_vec()), (0, [4, 5, 6].map(Color::GRAY4).to_vec())]);
+
+        // packed pixels can't be borrowed mutably one by one
+        assert_eq!(image.pixels_mut().unwrap_err(), Error::WrongColor);
+        assert_eq!(image.rows_mut().unwrap_err(), Error::WrongColor);
+    }
+}
@@ -29,13 +29,30 @@
 //! **Colorspaces:** RGB8
 //! Coordinates origin is in the bottom left corner of the image.
 
+pub mod blending;
 pub mod colors;
 mod conversions;
 mod drawing;
 pub mod error;
+pub mod filtering;
 pub mod image;
 mod indexing;
 mod io;
+pub mod iterators;
+mod mask;
+pub mod morphology;
+pub mod palette;
+pub mod resampling;
+pub mod sdf;
+pub mod supersampling;
+pub mod target;
+mod transforms;
+pub mod typed;
+pub mod view;
+pub mod warping;
+
+#[doc(inline)]
+pub use blending::*;
 
 #[doc(inline)]
 pub use colors::*;
@@ -43,5 +60,38 @@ pub use colors::*;
 #[doc(inline)]
 pub use error::*;
 
+#[doc(inline)]
+pub use filtering::*;
+
 #[doc(inline)]
 pub use image::*;
+
+#[doc(inline)]
+pub use iterators::*;
+
+#[doc(inline)]
+pub use morphology::*;
+
+#[doc(inline)]
+pub use palette::*;
+
+#[doc(inline)]
+pub use resampling::*;
+
+#[doc(inline)]
+pub use sdf::*;
+
+#[doc(inline)]
+pub use supersampling::*;
+
+#[doc(inline)]
+pub use target::*;
+
+#[doc(inline)]
+pub use typed::*;
+
+#[doc(inline)]
+pub use view::*;
+
+#[doc(inline)]
+pub use warping::*;
@@ -0,0 +1,208 @@
+//! Coverage masks used for effects which need the coverage of a whole shape at once.
+
+use crate::blending::Blend;
+use crate::colors::Color;
+use crate::sdf::SdfShape;
+use crate::target::{fill, DrawTarget};
+
+/// A rectangular grid of coverage values placed on the image
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Mask {
+    /// The image coordinates of the bottom left value of the mask
+    pub(crate) origin: (isize, isize),
+    /// The width of the mask
+    pub(crate) width: usize,
+    /// The height of the mask
+    pub(crate) height: usize,
+    /// The coverage values in range ```[0.0, 1.0]```, stored row by row from the bottom
+    pub(crate) data: Vec<f64>,
+}
+impl Mask {
+    /// Renders the coverage of the shape into a new mask.
+    /// # Arguments
+    /// * ```shape``` - The shape to render.
+    /// * ```thickness``` - The thickness of the outline. If the thickness is 0, the shape is filled.
+    /// * ```falloff``` - The width of the edge of the shape.
+    /// * ```padding``` - The number of empty values added around the shape, for example to make room for the blur.
+    /// * ```min``` - The smallest image coordinates the mask is allowed to cover.
+    /// * ```max``` - The largest image coordinates the mask is allowed to cover.
+    /// # Returns
+    /// * The new mask, [None] if the shape doesn't cover any of the allowed coordinates.
+    pub(crate) fn from_shape(shape: &SdfShape, thickness: f64, falloff: f64, padding: isize, min: (isize, isize), max: (isize, isize)) -> Option<Self> {
+        let ((min_x, min_y), (max_x, max_y)) = shape.bounds();
+        let margin = thickness / 2.0 + falloff / 2.0 + padding as f64;
+        let min_x = ((min_x - margin).floor() as isize).max(min.0);
+        let min_y = ((min_y - margin).floor() as isize).max(min.1);
+        let max_x = ((max_x + margin).ceil() as isize).min(max.0);
+        let max_y = ((max_y + margin).ceil() as isize).min(max.1);
+        if min_x > max_x || min_y > max_y {
+            return None;
+        }
+
+        let width = (max_x - min_x + 1) as usize;
+        let height = (max_y - min_y + 1) as usize;
+        let mut data = Vec::with_capacity(width * height);
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                data.push(shape.coverage((x as f64, y as f64), thickness, falloff));
+            }
+        }
+
+        Some(Self {
+            origin: (min_x, min_y),
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Blurs the mask with the gaussian kernel. Values outside of the mask are treated as 0.
+    /// # Arguments
+    /// * ```sigma``` - The standard deviation of the gaussian kernel.
+    pub(crate) fn gaussian_blur(&mut self, sigma: f64) {
+        // values farther than the size of the mask can't reach any value of the mask
+        let kernel = gaussian_kernel(sigma, self.width.max(self.height));
+        if kernel.len() == 1 {
+            return;
+        }
+        let radius = (kernel.len() / 2) as isize;
+        let mut temp = vec![0.0; self.data.len()];
+
+        // horizontal pass
+        for y in 0..self.height {
+            let row = y * self.width;
+            for x in 0..self.width {
+                let mut sum = 0.0;
+                for (i, weight) in kernel.iter().enumerate() {
+                    let source_x = x as isize + i as isize - radius;
+                    if source_x >= 0 && (source_x as usize) < self.width {
+                        sum += self.data[row + source_x as usize] * weight;
+                    }
+                }
+                temp[row + x] = sum;
+            }
+        }
+
+        // vertical pass
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut sum = 0.0;
+                for (i, weight) in kernel.iter().enumerate() {
+                    let source_y = y as isize + i as isize - radius;
+                    if source_y >= 0 && (source_y as usize) < self.height {
+                        sum += temp[source_y as usize * self.width + x] * weight;
+                    }
+                }
+                self.data[y * self.width + x] = sum.min(1.0);
+            }
+        }
+    }
+}
+
+/// Returns the normalized, one-dimensional gaussian kernel.
+/// The kernel covers three standard deviations on each side, but at most ```max_radius``` values.
+/// A cut kernel keeps the weights of the full one, so the weight of the cut values is lost.
+/// # Arguments
+/// * ```sigma``` - The standard deviation.
+/// * ```max_radius``` - The largest number of values on each side of the center.
+/// # Returns
+/// * The kernel with odd length, ```[1.0]``` if the standard deviation is 0.
+pub(crate) fn gaussian_kernel(sigma: f64, max_radius: usize) -> Vec<f64> {
+    if sigma <= 0.0 {
+        return vec![1.0];
+    }
+    let full_radius = (3.0 * sigma).ceil();
+    let radius = full_radius.min(max_radius as f64) as isize;
+    let mut kernel: Vec<f64> = (-radius..=radius).map(|i| (-((i as f64).powi(2)) / (2.0 * sigma * sigma)).exp()).collect();
+    let sum: f64 = if (radius as f64) < full_radius {
+        // the sum of the full kernel is close to the integral of the gaussian
+        sigma * (2.0 * std::f64::consts::PI).sqrt()
+    } else {
+        kernel.iter().sum()
+    };
+    for weight in &mut kernel {
+        *weight /= sum;
+    }
+    kernel
+}
+
+/// Fills the pixels of the target covered by the mask with the color without performing checks.
+/// Parts of the mask outside of the target are ignored.
+/// # Arguments
+/// * ```target``` - The target to draw onto.
+/// * ```mask``` - The mask.
+/// * ```color``` - The color to fill with.
+/// * ```opacity``` - The opacity of the color.
+/// * ```blend``` - The blend used to combine the color with the target.
+pub(crate) fn fill_mask<T: DrawTarget + ?Sized>(target: &mut T, mask: &Mask, color: Color, opacity: f64, blend: Blend) {
+    let width = target.width() as isize;
+    let height = target.height() as isize;
+    for mask_y in 0..mask.height {
+        let y = mask.origin.1 + mask_y as isize;
+        if y < 0 || y >= height {
+            continue;
+        }
+        for mask_x in 0..mask.width {
+            let x = mask.origin.0 + mask_x as isize;
+            if x < 0 || x >= width {
+                continue;
+            }
+            let coverage = mask.data[mask_y * mask.width + mask_x];
+            if coverage > 0.0 {
+                let (x, y) = (x as usize, y as usize);
+                fill(target, x..(x + 1), y..(y + 1), color, opacity * coverage, blend);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaussian_kernel() {
+        assert_eq!(gaussian_kernel(0.0, usize::MAX), vec![1.0]);
+        let kernel = gaussian_kernel(1.0, usize::MAX);
+        assert_eq!(kernel.len(), 7);
+        assert!((kernel.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert_eq!(kernel[2], kernel[4]);
+        assert!(kernel[3] > kernel[2] && kernel[2] > kernel[1] && kernel[1] > kernel[0]);
+        assert_eq!(gaussian_kernel(2.5, usize::MAX).len(), 17);
+
+        // cut kernels keep the weights of the full kernel
+        let cut = gaussian_kernel(2.5, 3);
+        assert_eq!(cut.len(), 7);
+        let full = gaussian_kernel(2.5, usize::MAX);
+        assert!(cut.iter().zip(&full[5..12]).all(|(a, b)| (a / b - 1.0).abs() < 0.01));
+        assert!(gaussian_kernel(1e30, 2).iter().all(|&weight| weight < 1e-29));
+    }
+
+    #[test]
+    fn test_mask() {
+        let shape = SdfShape::Circle { center: (0.0, 0.0), radius: 2.0 };
+        let mask = Mask::from_shape(&shape, 0.0, 1.0, 0, (-100, -100), (100, 100)).unwrap();
+        assert_eq!(mask.origin, (-3, -3));
+        assert_eq!((mask.width, mask.height), (7, 7));
+        assert_eq!(mask.data[3 * 7 + 3], 1.0);
+        assert_eq!(mask.data[0], 0.0);
+
+        let clipped = Mask::from_shape(&shape, 0.0, 1.0, 0, (0, 1), (100, 100)).unwrap();
+        assert_eq!(clipped.origin, (0, 1));
+        assert_eq!((clipped.width, clipped.height), (4, 3));
+        assert!(Mask::from_shape(&shape, 0.0, 1.0, 0, (10, 10), (100, 100)).is_none());
+
+        // blurring keeps the total coverage
+        let mut blurred = Mask::from_shape(&shape, 0.0, 1.0, 0, (-100, -100), (100, 100)).unwrap();
+        let mut wide = Mask::from_shape(&shape, 0.0, 1.0, 7, (-100, -100), (100, 100)).unwrap();
+        assert_eq!(wide.origin, (-10, -10));
+        assert_eq!((wide.width, wide.height), (21, 21));
+        let total: f64 = wide.data.iter().sum();
+        wide.gaussian_blur(1.5);
+        assert!((wide.data.iter().sum::<f64>() - total).abs() < 1e-9);
+        assert!(wide.data[10 * 21 + 10] < 1.0);
+        assert!(wide.data[10 * 21 + 16] > 0.0);
+        blurred.gaussian_blur(0.0);
+        assert_eq!(blurred, mask);
+    }
+}
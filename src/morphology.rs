@@ -0,0 +1,199 @@
+//! A module containing the morphological operations (dilation, erosion, opening and closing) of grayscale images.
+//!
+//! Dilation replaces every pixel with the largest value under the structuring element placed on it, erosion with the smallest one.
+//! Pixels outside of the image are ignored, so the edges of the image neither grow nor shrink the shapes.
+
+use crate::colors::ColorType;
+use crate::error::Error;
+use crate::image::Image;
+
+/// An enum that represents the shape of the neighbourhood used by the morphological operations.
+/// The neighbourhood is centered on the pixel and extends by the radii ```(x, y)``` in each direction.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum StructuringElement {
+    /// The rectangle of size ```(2 * x + 1, 2 * y + 1)``` pixels
+    Rectangle(usize, usize),
+    /// The ellipse inscribed in the rectangle of the same radii
+    Ellipse(usize, usize),
+}
+impl StructuringElement {
+    /// Returns the horizontal extent of the element for every row.
+    /// # Returns
+    /// * The half widths of the rows of the element, from the top.
+    fn half_widths(&self) -> Vec<usize> {
+        match *self {
+            StructuringElement::Rectangle(radius_x, radius_y) => vec![radius_x; 2 * radius_y + 1],
+            StructuringElement::Ellipse(radius_x, radius_y) => {
+                // the radii are enlarged by half of a pixel, so the ends of the axes aren't single pixels
+                let (a, b) = (radius_x as f64 + 0.5, radius_y as f64 + 0.5);
+                (-(radius_y as isize)..=radius_y as isize)
+                    .map(|dy| {
+                        let dy = dy as f64 / b;
+                        ((a * (1.0 - dy * dy).sqrt()).floor() as usize).min(radius_x)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl Image {
+    /// Applies the morphological operation to the image.
+    /// # Arguments
+    /// * ```element``` - The structuring element.
+    /// * ```dilate``` - If true, the largest value is taken, otherwise the smallest one.
+    /// # Returns
+    /// * [Result] which holds the new image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image is not [ColorType::GRAY8] or [ColorType::GRAY16].
+    fn morphology(&self, element: StructuringElement, dilate: bool) -> Result<Image, Error> {
+        let values: Vec<u16> = match self.color_type {
+            ColorType::GRAY8 => self.data.iter().map(|&value| value as u16).collect(),
+            ColorType::GRAY16 => self.data.chunks_exact(2).map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]])).collect(),
+            _ => return Err(Error::WrongColor),
+        };
+        let pick = |a: u16, b: u16| if dilate { a.max(b) } else { a.min(b) };
+
+        let half_widths = element.half_widths();
+        let radius_y = (half_widths.len() / 2) as isize;
+        let mut result = Vec::with_capacity(values.len());
+        for row in 0..self.height as isize {
+            for column in 0..self.width as isize {
+                let mut value = values[(row * self.width as isize + column) as usize];
+                for (i, &half_width) in half_widths.iter().enumerate() {
+                    let source_row = row + i as isize - radius_y;
+                    if source_row < 0 || source_row >= self.height as isize {
+                        continue;
+                    }
+                    let start = (column - half_width as isize).max(0) as usize;
+                    let end = ((column + half_width as isize + 1) as usize).min(self.width);
+                    let row_start = source_row as usize * self.width;
+                    value = values[row_start + start..row_start + end].iter().fold(value, |value, &other| pick(value, other));
+                }
+                result.push(value);
+            }
+        }
+
+        let data = match self.color_type {
+            ColorType::GRAY8 => result.into_iter().map(|value| value as u8).collect(),
+            _ => result.into_iter().flat_map(u16::to_ne_bytes).collect(),
+        };
+        Ok(Image { data, ..self.clone() })
+    }
+
+    /// Returns the image dilated with the structuring element, bright areas grow.
+    /// # Arguments
+    /// * ```element``` - The structuring element.
+    /// # Returns
+    /// * [Result] which holds the dilated image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image is not [ColorType::GRAY8] or [ColorType::GRAY16].
+    pub fn dilate(&self, element: StructuringElement) -> Result<Image, Error> {
+        self.morphology(element, true)
+    }
+
+    /// Returns the image eroded with the structuring element, bright areas shrink.
+    /// # Arguments
+    /// * ```element``` - The structuring element.
+    /// # Returns
+    /// * [Result] which holds the eroded image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image is not [ColorType::GRAY8] or [ColorType::GRAY16].
+    pub fn erode(&self, element: StructuringElement) -> Result<Image, Error> {
+        self.morphology(element, false)
+    }
+
+    /// Returns the image opened with the structuring element (eroded and then dilated).
+    /// Bright details smaller than the element are removed, larger areas keep their shape.
+    /// # Arguments
+    /// * ```element``` - The structuring element.
+    /// # Returns
+    /// * [Result] which holds the opened image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image is not [ColorType::GRAY8] or [ColorType::GRAY16].
+    pub fn open(&self, element: StructuringElement) -> Result<Image, Error> {
+        self.erode(element)?.dilate(element)
+    }
+
+    /// Returns the image closed with the structuring element (dilated and then eroded).
+    /// Dark holes and gaps smaller than the element are filled, larger areas keep their shape.
+    /// # Arguments
+    /// * ```element``` - The structuring element.
+    /// # Returns
+    /// * [Result] which holds the closed image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image is not [ColorType::GRAY8] or [ColorType::GRAY16].
+    pub fn close(&self, element: StructuringElement) -> Result<Image, Error> {
+        self.dilate(element)?.erode(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Color;
+
+    /// Returns the rows of the GRAY8 image from the top, with '#' for 255 and '.' for 0.
+    fn rows(image: &Image) -> Vec<String> {
+        image.as_bytes().chunks(image.width()).map(|row| row.iter().map(|&value| if value == 255 { '#' } else { '.' }).collect()).collect()
+    }
+
+    /// Creates a GRAY8 image from the rows.
+    fn image(rows: &[&str]) -> Image {
+        let data: Vec<u8> = rows.iter().flat_map(|row| row.chars().map(|c| if c == '#' { 255 } else { 0 })).collect();
+        Image::from_bytes(rows[0].len(), rows.len(), ColorType::GRAY8, &data).unwrap()
+    }
+
+    #[test]
+    fn test_structuring_element() {
+        assert_eq!(StructuringElement::Rectangle(2, 1).half_widths(), vec![2, 2, 2]);
+        assert_eq!(StructuringElement::Ellipse(0, 0).half_widths(), vec![0]);
+        assert_eq!(StructuringElement::Ellipse(1, 1).half_widths(), vec![1, 1, 1]);
+        assert_eq!(StructuringElement::Ellipse(2, 2).half_widths(), vec![1, 2, 2, 2, 1]);
+        assert_eq!(StructuringElement::Ellipse(3, 1).half_widths(), vec![2, 3, 2]);
+    }
+
+    #[test]
+    fn test_dilate_erode() {
+        let rgb = Image::new(3, 3, Color::RGB8([0, 0, 0]));
+        assert_eq!(rgb.dilate(StructuringElement::Rectangle(1, 1)), Err(Error::WrongColor));
+        assert_eq!(rgb.erode(StructuringElement::Rectangle(1, 1)), Err(Error::WrongColor));
+
+        let point = image(&[".......", ".......", ".......", "...#...", ".......", ".......", "......."]);
+        assert_eq!(point.dilate(StructuringElement::Rectangle(0, 0)).unwrap(), point);
+        assert_eq!(
+            rows(&point.dilate(StructuringElement::Rectangle(2, 1)).unwrap()),
+            [".......", ".......", ".#####.", ".#####.", ".#####.", ".......", "......."]
+        );
+        let disc = point.dilate(StructuringElement::Ellipse(2, 2)).unwrap();
+        assert_eq!(rows(&disc), [".......", "..###..", ".#####.", ".#####.", ".#####.", "..###..", "......."]);
+        assert_eq!(disc.erode(StructuringElement::Ellipse(2, 2)).unwrap(), point);
+        assert_eq!(rows(&disc.erode(StructuringElement::Rectangle(1, 1)).unwrap()), [".......", ".......", "...#...", "..###..", "...#...", ".......", "......."]);
+
+        // the edges of the image don't erode shapes touching them
+        let full = Image::new(4, 3, Color::GRAY8(255));
+        assert_eq!(full.erode(StructuringElement::Ellipse(2, 2)).unwrap(), full);
+
+        // 16-bit values are kept
+        let mut image = Image::new(3, 1, Color::GRAY16(1_000));
+        image.set((1, 0), Color::GRAY16(60_000)).unwrap();
+        let dilated = image.dilate(StructuringElement::Rectangle(1, 0)).unwrap();
+        assert!((0..3).all(|x| dilated.get((x, 0)).unwrap() == Color::GRAY16(60_000)));
+        let eroded = image.erode(StructuringElement::Rectangle(1, 0)).unwrap();
+        assert!((0..3).all(|x| eroded.get((x, 0)).unwrap() == Color::GRAY16(1_000)));
+    }
+
+    #[test]
+    fn test_open_close() {
+        // opening removes the small speck and keeps the square
+        let specks = image(&["#......", ".......", "...###.", "...###.", "...###.", "......."]);
+        let opened = specks.open(StructuringElement::Rectangle(1, 1)).unwrap();
+        assert_eq!(rows(&opened), [".......", ".......", "...###.", "...###.", "...###.", "......."]);
+
+        // closing fills the hole and keeps the outline
+        let hole = image(&["........", "........", "..####..", "..#..#..", "..####..", "........", "........"]);
+        let closed = hole.close(StructuringElement::Rectangle(1, 1)).unwrap();
+        assert_eq!(rows(&closed), ["........", "........", "..####..", "..####..", "..####..", "........", "........"]);
+        assert_eq!(Image::new(2, 2, Color::RGBA8([0, 0, 0, 0])).close(StructuringElement::Ellipse(1, 1)), Err(Error::WrongColor));
+    }
+}
@@ -0,0 +1,356 @@
+//! A module containing the [Palette] of the images with the [ColorType::INDEXED8] color type.
+//!
+//! The pixels of indexed images hold the indices into the palette of the image.
+//! Drawing with an indexed color writes the index directly, while blended pixels (anti-aliased edges, transparent colors)
+//! are snapped to the index of the nearest color of the palette.
+//! Swapping the palette of an image recolors it without touching the pixels.
+
+use crate::blending::Blend;
+use crate::colors::{AlphaMode, Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use std::collections::HashSet;
+
+/// The largest number of colors of a palette, as the indices are 8-bit.
+const MAX_COLORS: usize = 256;
+
+/// The color of the indices without a color in the palette.
+const MISSING_COLOR: [u8; 4] = [0, 0, 0, u8::MAX];
+
+/// A struct that holds up to 256 colors of an indexed image
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Palette {
+    /// The colors of the palette as RGBA8 with straight alpha
+    colors: Vec<[u8; 4]>,
+}
+impl Palette {
+    /// Creates a new palette with the given colors.
+    /// The colors are stored as [ColorType::RGBA8] with straight alpha.
+    /// # Arguments
+    /// * ```colors``` - The colors of the palette, the first color has the index 0.
+    /// # Returns
+    /// * [Result] which holds the new palette or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidPalette] - If there are no colors or more than 256 colors.
+    /// * [Error::WrongColor] - If any of the colors is an indexed color.
+    pub fn new(colors: &[Color]) -> Result<Self, Error> {
+        if colors.is_empty() || colors.len() > MAX_COLORS {
+            return Err(Error::InvalidPalette);
+        }
+        let colors = colors.iter().map(|&color| to_rgba8(color)).collect::<Result<_, _>>()?;
+        Ok(Self { colors })
+    }
+
+    /// Returns the number of colors of the palette.
+    /// # Returns
+    /// * The number of colors.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns whether the palette has no colors, which is never the case, as every palette has at least one color.
+    /// # Returns
+    /// * ```true``` if the palette has no colors, ```false``` otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+
+    /// Returns the color of the given index.
+    /// # Arguments
+    /// * ```index``` - The index of the color.
+    /// # Returns
+    /// * The [ColorType::RGBA8] color or [None] if the index is out of the palette.
+    #[inline]
+    pub fn get(&self, index: u8) -> Option<Color> {
+        self.colors.get(index as usize).map(|&color| Color::RGBA8(color))
+    }
+
+    /// Sets the color of the given index.
+    /// # Arguments
+    /// * ```index``` - The index of the color.
+    /// * ```color``` - The new color, stored as [ColorType::RGBA8].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of the palette.
+    /// * [Error::WrongColor] - If the color is an indexed color.
+    pub fn set(&mut self, index: u8, color: Color) -> Result<(), Error> {
+        let color = to_rgba8(color)?;
+        let entry = self.colors.get_mut(index as usize).ok_or(Error::IndexOutOfBounds)?;
+        *entry = color;
+        Ok(())
+    }
+
+    /// Returns the bytes of the colors of the palette, 4 bytes of RGBA with straight alpha per color.
+    /// # Returns
+    /// * The bytes of the palette.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.colors.as_flattened()
+    }
+
+    /// Returns the indexed color of the palette color nearest to the given color.
+    /// # Arguments
+    /// * ```color``` - The color with straight alpha. Indexed colors are returned unchanged.
+    /// # Returns
+    /// * The [ColorType::INDEXED8] color to draw with.
+    /// # Example
+    /// ```
+    /// use tinydraw::{Color, Palette};
+    ///
+    /// let palette = Palette::new(&[Color::RGB8([0, 0, 0]), Color::RGB8([255, 0, 0])]).unwrap();
+    /// assert_eq!(palette.nearest(Color::RGB8([200, 30, 10])), Color::INDEXED8(1));
+    /// ```
+    pub fn nearest(&self, color: Color) -> Color {
+        match color {
+            Color::INDEXED8(_) => color,
+            _ => Color::INDEXED8(self.nearest_index(Color::from_rgba_f64(ColorType::RGBA8, color.to_rgba_f64()).as_bytes())),
+        }
+    }
+
+    /// Returns the palette made of the colors of the image.
+    /// # Arguments
+    /// * ```image``` - The image.
+    /// # Returns
+    /// * The palette with the colors of the image in the order of the pixel data if there are at most 256 of them.
+    /// * The palette with the 256 colors of [ColorType::RGB332] otherwise.
+    /// * The palette with the background color of the image, or opaque black without one, if the image has no pixels.
+    pub(crate) fn from_image(image: &Image) -> Self {
+        let mut image = image.clone();
+        image.set_alpha_mode(AlphaMode::Straight);
+        image.convert(ColorType::RGBA8);
+
+        let mut colors = Vec::new();
+        let mut seen = HashSet::new();
+        for pixel in image.data.chunks_exact(4) {
+            let color = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            if seen.insert(color) {
+                if colors.len() == MAX_COLORS {
+                    // too many colors, fall back to the evenly spread ones
+                    colors = (0..=u8::MAX).map(|value| to_rgba8(Color::RGB332(value)).unwrap()).collect();
+                    break;
+                }
+                colors.push(color);
+            }
+        }
+        if colors.is_empty() {
+            // palettes are never empty, so images without pixels get one color
+            colors.push(image.background_color.and_then(|color| to_rgba8(color).ok()).unwrap_or(MISSING_COLOR));
+        }
+        Self { colors }
+    }
+
+    /// Returns the color of the palette for indexed colors, other colors are returned unchanged.
+    /// Indices out of the palette have opaque black color.
+    /// # Arguments
+    /// * ```color``` - The color.
+    /// # Returns
+    /// * The [ColorType::RGBA8] color of the palette or the given color.
+    pub(crate) fn resolve(&self, color: Color) -> Color {
+        match color {
+            Color::INDEXED8(index) => Color::RGBA8(self.colors.get(index as usize).copied().unwrap_or(MISSING_COLOR)),
+            _ => color,
+        }
+    }
+
+    /// Returns the index of the palette color nearest to the given color.
+    /// The colors are compared by the euclidean distance of their channels, the lowest index wins the ties.
+    /// # Arguments
+    /// * ```rgba``` - The bytes of the [ColorType::RGBA8] color with straight alpha.
+    /// # Returns
+    /// * The index of the nearest color.
+    pub(crate) fn nearest_index(&self, rgba: &[u8]) -> u8 {
+        let distance = |color: &[u8; 4]| color.iter().zip(rgba).map(|(&a, &b)| (a as i32 - b as i32).pow(2)).sum::<i32>();
+        // the palette has at most 256 colors, so the index fits into u8
+        self.colors.iter().enumerate().min_by_key(|(_, color)| distance(color)).map_or(0, |(index, _)| index as u8)
+    }
+
+    /// Blends the color with the palette color of the index and snaps the result to the nearest palette color.
+    /// # Arguments
+    /// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with straight alpha in the space of the blend.
+    /// * ```index``` - The index of the destination color.
+    /// * ```blend``` - The blend used to combine the colors.
+    /// # Returns
+    /// * The index of the palette color nearest to the blended color.
+    pub(crate) fn blend_index(&self, source: [f64; 4], index: u8, blend: &Blend) -> u8 {
+        let destination = self.resolve(Color::INDEXED8(index));
+        self.nearest_index(blend.blend_color(source, destination, false).as_bytes())
+    }
+}
+
+/// Converts the direct color to the bytes of the RGBA8 color.
+/// # Arguments
+/// * ```color``` - The color with straight alpha.
+/// # Returns
+/// * [Result] which holds the RGBA8 bytes or [Err] with [Error::WrongColor] for indexed colors.
+fn to_rgba8(color: Color) -> Result<[u8; 4], Error> {
+    if ColorType::from(color) == ColorType::INDEXED8 {
+        return Err(Error::WrongColor);
+    }
+    match Color::from_rgba_f64(ColorType::RGBA8, color.to_rgba_f64()) {
+        Color::RGBA8(rgba) => Ok(rgba),
+        _ => unreachable!("the color was converted to RGBA8"),
+    }
+}
+
+impl Image {
+    /// Returns the palette of the indexed image.
+    /// # Returns
+    /// * The palette or [None] if the image is not indexed or has no palette.
+    #[inline]
+    pub fn palette(&self) -> Option<&Palette> {
+        self.palette.as_ref()
+    }
+
+    /// Returns the palette of the indexed image as mutable.
+    /// Changing the colors of the palette recolors the image.
+    /// # Returns
+    /// * The mutable palette or [None] if the image is not indexed or has no palette.
+    #[inline]
+    pub fn palette_mut(&mut self) -> Option<&mut Palette> {
+        self.palette.as_mut()
+    }
+
+    /// Sets the palette of the indexed image, keeping the indices of the pixels (a palette swap).
+    /// Indices out of the new palette have opaque black color.
+    /// Images without a palette are drawn and converted as if the indices were 8-bit grayscale colors.
+    /// # Arguments
+    /// * ```palette``` - The new palette.
+    /// # Errors
+    /// * [Error::WrongColor] - If the image is not [ColorType::INDEXED8].
+    pub fn set_palette(&mut self, palette: Palette) -> Result<(), Error> {
+        if self.color_type != ColorType::INDEXED8 {
+            return Err(Error::WrongColor);
+        }
+        self.palette = Some(palette);
+        Ok(())
+    }
+
+    /// Converts the image to [ColorType::INDEXED8] with the given palette.
+    /// Every pixel gets the index of the nearest color of the palette, indexed images are converted through their current palette.
+    /// The alpha mode of the image is kept.
+    /// # Arguments
+    /// * ```palette``` - The palette of the converted image.
+    pub fn convert_with_palette(&mut self, palette: Palette) {
+        if self.alpha_mode == AlphaMode::Premultiplied {
+            // convert with straight alpha
+            self.set_alpha_mode(AlphaMode::Straight);
+            self.convert_with_palette(palette);
+            self.set_alpha_mode(AlphaMode::Premultiplied);
+            return;
+        }
+        self.convert(ColorType::RGBA8);
+        self.data = self.data.chunks_exact(4).map(|pixel| palette.nearest_index(pixel)).collect();
+        self.background_color = self.background_color.map(|color| Color::INDEXED8(palette.nearest_index(color.as_bytes())));
+        self.color_type = ColorType::INDEXED8;
+        self.palette = Some(palette);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompositeOp, Filter, ImageViewMut};
+
+    fn gray_palette() -> Palette {
+        Palette::new(&[Color::GRAY8(0), Color::GRAY8(255), Color::GRAY8(128)]).unwrap()
+    }
+
+    #[test]
+    fn test_palette() {
+        assert_eq!(Palette::new(&[]), Err(Error::InvalidPalette));
+        assert_eq!(Palette::new(&[Color::GRAY8(0); 257]), Err(Error::InvalidPalette));
+        assert_eq!(Palette::new(&[Color::GRAY8(0), Color::INDEXED8(1)]), Err(Error::WrongColor));
+
+        let mut palette = Palette::new(&[Color::RGB8([0, 0, 0]), Color::RGBA8([255, 0, 0, 100]), Color::GRAY16(65_535)]).unwrap();
+        assert_eq!(palette.len(), 3);
+        assert!(!palette.is_empty());
+        assert_eq!(palette.get(0), Some(Color::RGBA8([0, 0, 0, 255])));
+        assert_eq!(palette.get(1), Some(Color::RGBA8([255, 0, 0, 100])));
+        assert_eq!(palette.get(2), Some(Color::RGBA8([255, 255, 255, 255])));
+        assert_eq!(palette.get(3), None);
+        assert_eq!(palette.as_bytes(), &[0, 0, 0, 255, 255, 0, 0, 100, 255, 255, 255, 255]);
+
+        assert_eq!(palette.nearest(Color::RGBA8([200, 30, 10, 120])), Color::INDEXED8(1));
+        assert_eq!(palette.nearest(Color::GRAY8(200)), Color::INDEXED8(2));
+        assert_eq!(palette.nearest(Color::INDEXED8(7)), Color::INDEXED8(7));
+
+        palette.set(1, Color::RGB8([0, 0, 255])).unwrap();
+        assert_eq!(palette.get(1), Some(Color::RGBA8([0, 0, 255, 255])));
+        assert_eq!(palette.set(3, Color::RGB8([0, 0, 255])), Err(Error::IndexOutOfBounds));
+        assert_eq!(palette.set(0, Color::INDEXED8(1)), Err(Error::WrongColor));
+    }
+
+    #[test]
+    fn test_palette_drawing() {
+        let mut image = Image::new(20, 20, Color::INDEXED8(0));
+        assert_eq!(image.palette(), None);
+        image.set_palette(gray_palette()).unwrap();
+        assert_eq!(Image::new(1, 1, Color::RGB8([0, 0, 0])).set_palette(gray_palette()), Err(Error::WrongColor));
+
+        // opaque indices are written directly, blended colors are snapped to the palette
+        image.set_composite((2, 2), Color::INDEXED8(1), 1.0, CompositeOp::SourceOver).unwrap();
+        image.set_composite((3, 3), Color::INDEXED8(1), 0.5, CompositeOp::SourceOver).unwrap();
+        image.set_transparent((4, 4), Color::INDEXED8(2), 0.2).unwrap();
+        assert_eq!(image.get((2, 2)).unwrap(), Color::INDEXED8(1));
+        assert_eq!(image.get((3, 3)).unwrap(), Color::INDEXED8(2));
+        assert_eq!(image.get((4, 4)).unwrap(), Color::INDEXED8(0));
+
+        // anti-aliased edges only use the colors of the palette
+        image.draw_circle((12, 12), 5, Color::INDEXED8(1), 0, 1.0, CompositeOp::SourceOver).unwrap();
+        assert_eq!(image.get((12, 12)).unwrap(), Color::INDEXED8(1));
+        assert!(image.as_bytes().iter().all(|&index| index < 3));
+        assert!(image.as_bytes().contains(&2));
+
+        // images are drawn with the nearest colors of the palette
+        image.draw_image(&Image::new(2, 2, Color::RGB8([250, 240, 255])), (0, 0), 1.0, CompositeOp::SourceOver).unwrap();
+        assert_eq!(image.get((1, 1)).unwrap(), Color::INDEXED8(1));
+
+        // a palette swap recolors the image
+        image.palette_mut().unwrap().set(1, Color::RGB8([255, 0, 0])).unwrap();
+        let mut swapped = image.clone();
+        swapped.convert(ColorType::RGB8);
+        assert_eq!(swapped.get((2, 2)).unwrap(), Color::RGB8([255, 0, 0]));
+        assert_eq!(swapped.get((3, 3)).unwrap(), Color::RGB8([128, 128, 128]));
+        assert_eq!(swapped.palette(), None);
+    }
+
+    #[test]
+    fn test_palette_view() {
+        let palette = gray_palette();
+        let mut data = vec![0_u8; 4 * 3];
+        let mut view = ImageViewMut::new(&mut data, 4, 3, 4, ColorType::INDEXED8).unwrap().with_palette(&palette).unwrap();
+        assert_eq!(view.palette(), Some(&palette));
+        assert_eq!(view.set_composite((1, 1), Color::GRAY8(255), 0.5, CompositeOp::SourceOver), Err(Error::WrongColor));
+        view.set_composite((1, 1), Color::INDEXED8(1), 0.5, CompositeOp::SourceOver).unwrap();
+        assert_eq!(view.get((1, 1)).unwrap(), Color::INDEXED8(2));
+        assert_eq!(view.to_image().palette(), Some(&palette));
+
+        let mut rgb = [0_u8; 3];
+        assert_eq!(ImageViewMut::new(&mut rgb, 1, 1, 3, ColorType::RGB8).unwrap().with_palette(&palette).unwrap_err(), Error::WrongColor);
+
+        // views and crops of indexed images keep the palette
+        let mut image = Image::new(10, 10, Color::INDEXED8(1));
+        image.set_palette(palette.clone()).unwrap();
+        image.sub_image((2, 2), (3, 3)).unwrap().set_transparent((0, 0), Color::INDEXED8(0), 0.5).unwrap();
+        assert_eq!(image.get((2, 2)).unwrap(), Color::INDEXED8(2));
+        assert_eq!(image.crop((1, 1), (4, 4)).unwrap().palette(), Some(&palette));
+    }
+
+    #[test]
+    fn test_palette_resampling() {
+        let mut image = Image::new(8, 8, Color::INDEXED8(0));
+        image.set_palette(gray_palette()).unwrap();
+        image.set((0..4, ..), Color::INDEXED8(1)).unwrap();
+
+        // the resampled colors are snapped to the palette, the half white half black rows average to gray
+        let resized = image.resize(1, 4, Filter::Bilinear).unwrap();
+        assert_eq!(resized.color_type(), ColorType::INDEXED8);
+        assert_eq!(resized.palette(), image.palette());
+        assert_eq!(resized.get((0, 0)).unwrap(), Color::INDEXED8(2));
+
+        let resized = image.resize(4, 4, Filter::Nearest).unwrap();
+        assert_eq!(resized.get((0, 0)).unwrap(), Color::INDEXED8(1));
+        assert_eq!(resized.get((3, 3)).unwrap(), Color::INDEXED8(0));
+    }
+}
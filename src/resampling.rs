@@ -0,0 +1,239 @@
+//! A module containing the resampling filters and the functions for resizing images.
+//!
+//! Images are filtered with premultiplied alpha, so colors of transparent pixels don't bleed into their neighbours,
+//! and in floating point, so 16-bit images keep their precision.
+
+use crate::error::Error;
+use crate::image::Image;
+use std::f64::consts::PI;
+
+/// An enum that represents the filters used for resampling images
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Filter {
+    /// The nearest pixel is used, fast but blocky
+    Nearest,
+    /// The linear interpolation of the neighbouring pixels (triangle filter)
+    #[default]
+    Bilinear,
+    /// The cubic interpolation of the neighbouring pixels (Catmull-Rom spline), sharper than bilinear
+    Bicubic,
+    /// The windowed sinc filter with 3 lobes, the sharpest, may slightly ring at hard edges
+    Lanczos3,
+}
+impl Filter {
+    /// Returns the radius of the filter in pixels.
+    /// # Returns
+    /// * The radius beyond which the filter is 0.
+    pub(crate) fn support(&self) -> f64 {
+        match self {
+            Filter::Nearest => 0.5,
+            Filter::Bilinear => 1.0,
+            Filter::Bicubic => 2.0,
+            Filter::Lanczos3 => 3.0,
+        }
+    }
+
+    /// Returns the value of the filter at the given distance from its center.
+    /// # Arguments
+    /// * ```x``` - The distance from the center of the filter in pixels.
+    /// # Returns
+    /// * The (not normalized) weight of the pixel.
+    pub(crate) fn kernel(&self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            Filter::Nearest => {
+                if x < 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Bilinear => (1.0 - x).max(0.0),
+            Filter::Bicubic => {
+                // Catmull-Rom spline (a = -0.5)
+                if x < 1.0 {
+                    1.5 * x * x * x - 2.5 * x * x + 1.0
+                } else if x < 2.0 {
+                    -0.5 * x * x * x + 2.5 * x * x - 4.0 * x + 2.0
+                } else {
+                    0.0
+                }
+            }
+            Filter::Lanczos3 => {
+                if x == 0.0 {
+                    1.0
+                } else if x < 3.0 {
+                    let pi_x = PI * x;
+                    3.0 * pi_x.sin() * (pi_x / 3.0).sin() / (pi_x * pi_x)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// Returns the source pixels and their weights for every destination pixel along one axis.
+/// When downscaling, the filter is stretched so that every source pixel contributes to the result.
+/// # Arguments
+/// * ```source_len``` - The number of source pixels.
+/// * ```destination_len``` - The number of destination pixels.
+/// * ```filter``` - The filter used.
+/// # Returns
+/// * For every destination pixel, the index of the first source pixel and the normalized weights of the following source pixels.
+fn axis_weights(source_len: usize, destination_len: usize, filter: Filter) -> Vec<(usize, Vec<f64>)> {
+    let scale = source_len as f64 / destination_len as f64;
+    (0..destination_len)
+        .map(|i| {
+            // pixel centers are at half-integer coordinates
+            let center = (i as f64 + 0.5) * scale;
+            if filter == Filter::Nearest {
+                return ((center as usize).min(source_len - 1), vec![1.0]);
+            }
+
+            let filter_scale = scale.max(1.0);
+            let radius = filter.support() * filter_scale;
+            let start = ((center - radius).floor().max(0.0) as usize).min(source_len - 1);
+            let end = ((center + radius).ceil() as usize).clamp(start + 1, source_len);
+            let mut weights: Vec<f64> = (start..end).map(|j| filter.kernel((j as f64 + 0.5 - center) / filter_scale)).collect();
+            let sum: f64 = weights.iter().sum();
+            if sum.abs() > f64::EPSILON {
+                for weight in &mut weights {
+                    *weight /= sum;
+                }
+            } else {
+                weights = vec![0.0; end - start];
+                weights[((center as usize).min(end - 1)) - start] = 1.0;
+            }
+            (start, weights)
+        })
+        .collect()
+}
+
+impl Image {
+    /// Returns the image resized to the given dimensions.
+    /// # Arguments
+    /// * ```width``` - The width of the new image.
+    /// * ```height``` - The height of the new image.
+    /// * ```filter``` - The filter used for resampling.
+    /// # Returns
+    /// * [Result] which holds the new image with the same color type and alpha mode, or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height, of the new image or of this image, is 0.
+    pub fn resize(&self, width: usize, height: usize, filter: Filter) -> Result<Image, Error> {
+        if width == 0 || height == 0 || self.width == 0 || self.height == 0 {
+            return Err(Error::InvalidSize);
+        }
+
+        let source = self.to_rgba_f64_premultiplied();
+
+        // horizontal pass
+        let x_weights = axis_weights(self.width, width, filter);
+        let mut temp = vec![[0.0; 4]; width * self.height];
+        for y in 0..self.height {
+            let row = &source[y * self.width..(y + 1) * self.width];
+            for (x, (start, weights)) in x_weights.iter().enumerate() {
+                let pixel = &mut temp[y * width + x];
+                for (source_pixel, weight) in row[*start..].iter().zip(weights) {
+                    for channel in 0..4 {
+                        pixel[channel] += source_pixel[channel] * weight;
+                    }
+                }
+            }
+        }
+
+        // vertical pass
+        let y_weights = axis_weights(self.height, height, filter);
+        let mut result = vec![[0.0; 4]; width * height];
+        for (y, (start, weights)) in y_weights.iter().enumerate() {
+            for (i, weight) in weights.iter().enumerate() {
+                let row = &temp[(start + i) * width..(start + i + 1) * width];
+                for (pixel, source_pixel) in result[y * width..(y + 1) * width].iter_mut().zip(row) {
+                    for channel in 0..4 {
+                        pixel[channel] += source_pixel[channel] * weight;
+                    }
+                }
+            }
+        }
+
+        let mut image = self.derive_from_rgba_f64_premultiplied(width, height, &result);
+        image.background_color = self.background_color;
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::{AlphaMode, Color, ColorType};
+
+    #[test]
+    fn test_filter_kernel() {
+        for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic, Filter::Lanczos3] {
+            assert_eq!(filter.kernel(0.0), 1.0);
+            assert_eq!(filter.kernel(filter.support()), 0.0);
+            assert_eq!(filter.kernel(-0.3), filter.kernel(0.3));
+        }
+        assert_eq!(Filter::Bilinear.kernel(0.25), 0.75);
+        assert_eq!(Filter::Bicubic.kernel(1.0), 0.0);
+        assert!(Filter::Bicubic.kernel(1.5) < 0.0);
+        assert!(Filter::Lanczos3.kernel(1.0).abs() < 1e-12);
+        assert!(Filter::Lanczos3.kernel(1.5) < 0.0);
+    }
+
+    #[test]
+    fn test_resize() {
+        // test errors
+        let image = Image::new(4, 3, Color::RGB8([1, 2, 3]));
+        assert_eq!(image.resize(0, 3, Filter::Bilinear), Err(Error::InvalidSize));
+        assert_eq!(Image::new(0, 3, Color::RGB8([1, 2, 3])).resize(4, 3, Filter::Bilinear), Err(Error::InvalidSize));
+        assert_eq!(Image::new(4, 0, Color::RGB8([1, 2, 3])).resize(4, 3, Filter::Lanczos3), Err(Error::InvalidSize));
+
+        // nearest neighbour duplicates and drops pixels
+        let mut image = Image::new(2, 2, Color::GRAY8(0));
+        image.set((1, 0), Color::GRAY8(100)).unwrap();
+        image.set((0, 1), Color::GRAY8(200)).unwrap();
+        let upscaled = image.resize(4, 4, Filter::Nearest).unwrap();
+        assert_eq!(upscaled.as_bytes(), &[200, 200, 0, 0, 200, 200, 0, 0, 0, 0, 100, 100, 0, 0, 100, 100]);
+        assert_eq!(upscaled.resize(2, 2, Filter::Nearest).unwrap().as_bytes(), image.as_bytes());
+
+        // bilinear downscaling stretches the filter over all source pixels
+        assert_eq!(image.resize(1, 1, Filter::Bilinear).unwrap().as_bytes(), &[75]);
+        let row = Image::from_bytes(4, 1, ColorType::GRAY8, &[0, 100, 200, 100]).unwrap();
+        assert_eq!(row.resize(2, 1, Filter::Bilinear).unwrap().as_bytes(), &[71, 143]);
+        assert_eq!(row.resize(8, 1, Filter::Bilinear).unwrap().as_bytes(), &[0, 25, 75, 125, 175, 175, 125, 100]);
+
+        // uniform images stay uniform and 16-bit precision is kept
+        for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic, Filter::Lanczos3] {
+            let color = Color::RGBA16([12_345, 54_321, 1, 40_000]);
+            let image = Image::new(7, 5, color);
+            for (width, height) in [(3, 2), (7, 5), (15, 11)] {
+                let resized = image.resize(width, height, filter).unwrap();
+                assert_eq!((resized.width(), resized.height(), resized.color_type()), (width, height, ColorType::RGBA16));
+                assert!((0..height).all(|y| (0..width).all(|x| resized.get((x, y)).unwrap() == color)), "{:?} {}x{}", filter, width, height);
+            }
+        }
+
+        // ringing at hard edges is clamped
+        let edge = Image::from_bytes(4, 1, ColorType::GRAY8, &[0, 0, 255, 255]).unwrap();
+        let resized = edge.resize(16, 1, Filter::Lanczos3).unwrap();
+        assert_eq!(&resized.as_bytes()[2..6], &[0, 0, 0, 0]);
+        assert_eq!(&resized.as_bytes()[10..14], &[255, 255, 255, 255]);
+        assert!(resized.as_bytes()[6..10].windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_resize_alpha() {
+        // colors of transparent pixels don't bleed
+        let image = Image::from_bytes(2, 1, ColorType::RGBA8, &[255, 0, 0, 255, 0, 255, 0, 0]).unwrap();
+        assert_eq!(image.resize(1, 1, Filter::Bilinear).unwrap().get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 128]));
+
+        // alpha mode is kept
+        let mut premultiplied = image.clone();
+        premultiplied.set_alpha_mode(AlphaMode::Premultiplied);
+        let resized = premultiplied.resize(1, 1, Filter::Bicubic).unwrap();
+        assert_eq!(resized.alpha_mode(), AlphaMode::Premultiplied);
+        assert_eq!(resized.get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 128]));
+        assert_eq!(resized.as_bytes(), &[128, 0, 0, 128]);
+    }
+}
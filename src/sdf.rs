@@ -0,0 +1,572 @@
+//! A module containing shapes described by signed distance functions and their rendering.
+//!
+//! The coverage of every pixel is computed from the exact distance of the pixel to the edge of the shape,
+//! which gives consistent anti-aliasing for any size and position of the shape.
+//! Pixel centers lie on integer coordinates, so the pixel ```(x, y)``` is sampled at ```(x as f64, y as f64)```.
+
+use crate::blending::Blend;
+use crate::colors::{Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use crate::mask::{fill_mask, Mask};
+use crate::target::{fill, DrawTarget};
+
+/// An enum that holds the shapes which can be rendered using their signed distance functions
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SdfShape {
+    /// A circle
+    Circle {
+        /// The coordinates of the center of the circle
+        center: (f64, f64),
+        /// The radius of the circle
+        radius: f64,
+    },
+    /// An axis-aligned ellipse
+    Ellipse {
+        /// The coordinates of the center of the ellipse
+        center: (f64, f64),
+        /// The lengths of the horizontal and vertical semi-axes of the ellipse
+        axes: (f64, f64),
+    },
+    /// An axis-aligned rectangle with rounded corners
+    RoundedRectangle {
+        /// The coordinates of the first corner of the rectangle
+        point1: (f64, f64),
+        /// The coordinates of the opposite corner of the rectangle
+        point2: (f64, f64),
+        /// The radius of the corners, limited to half of the shorter side of the rectangle
+        radius: f64,
+    },
+    /// A line segment with round caps
+    Capsule {
+        /// The coordinates of the first end of the segment
+        point1: (f64, f64),
+        /// The coordinates of the second end of the segment
+        point2: (f64, f64),
+        /// The radius of the capsule (half of its width)
+        radius: f64,
+    },
+}
+impl SdfShape {
+    /// Returns the signed distance from the point to the edge of the shape.
+    /// # Arguments
+    /// * ```point``` - The coordinates of the point.
+    /// # Returns
+    /// * The distance, negative inside of the shape and positive outside of it.
+    pub fn distance(&self, point: (f64, f64)) -> f64 {
+        match *self {
+            SdfShape::Circle { center, radius } => (point.0 - center.0).hypot(point.1 - center.1) - radius,
+            SdfShape::Ellipse { center, axes } => ellipse_distance((point.0 - center.0, point.1 - center.1), (axes.0.abs(), axes.1.abs())),
+            SdfShape::RoundedRectangle { point1, point2, radius } => {
+                let half_width = (point2.0 - point1.0).abs() / 2.0;
+                let half_height = (point2.1 - point1.1).abs() / 2.0;
+                let radius = radius.clamp(0.0, half_width.min(half_height));
+                let qx = (point.0 - (point1.0 + point2.0) / 2.0).abs() - half_width + radius;
+                let qy = (point.1 - (point1.1 + point2.1) / 2.0).abs() - half_height + radius;
+                qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - radius
+            }
+            SdfShape::Capsule { point1, point2, radius } => {
+                let (pax, pay) = (point.0 - point1.0, point.1 - point1.1);
+                let (bax, bay) = (point2.0 - point1.0, point2.1 - point1.1);
+                let length_squared = bax * bax + bay * bay;
+                let h = if length_squared > 0.0 {
+                    ((pax * bax + pay * bay) / length_squared).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (pax - bax * h).hypot(pay - bay * h) - radius
+            }
+        }
+    }
+
+    /// Returns the bounding box of the shape.
+    /// # Returns
+    /// * The tuple with the smallest and the largest coordinates of the shape.
+    pub fn bounds(&self) -> ((f64, f64), (f64, f64)) {
+        match *self {
+            SdfShape::Circle { center, radius } => ((center.0 - radius, center.1 - radius), (center.0 + radius, center.1 + radius)),
+            SdfShape::Ellipse { center, axes } => {
+                let (a, b) = (axes.0.abs(), axes.1.abs());
+                ((center.0 - a, center.1 - b), (center.0 + a, center.1 + b))
+            }
+            SdfShape::RoundedRectangle { point1, point2, .. } => ((point1.0.min(point2.0), point1.1.min(point2.1)), (point1.0.max(point2.0), point1.1.max(point2.1))),
+            SdfShape::Capsule { point1, point2, radius } => (
+                (point1.0.min(point2.0) - radius, point1.1.min(point2.1) - radius),
+                (point1.0.max(point2.0) + radius, point1.1.max(point2.1) + radius),
+            ),
+        }
+    }
+
+    /// Returns the shape moved by the offset.
+    /// # Arguments
+    /// * ```offset``` - The offset by which the shape is moved.
+    /// # Returns
+    /// * The moved shape.
+    pub fn translated(&self, offset: (f64, f64)) -> Self {
+        let translate = |point: (f64, f64)| (point.0 + offset.0, point.1 + offset.1);
+        match *self {
+            SdfShape::Circle { center, radius } => SdfShape::Circle { center: translate(center), radius },
+            SdfShape::Ellipse { center, axes } => SdfShape::Ellipse { center: translate(center), axes },
+            SdfShape::RoundedRectangle { point1, point2, radius } => SdfShape::RoundedRectangle {
+                point1: translate(point1),
+                point2: translate(point2),
+                radius,
+            },
+            SdfShape::Capsule { point1, point2, radius } => SdfShape::Capsule {
+                point1: translate(point1),
+                point2: translate(point2),
+                radius,
+            },
+        }
+    }
+
+    /// Returns the coverage of the point by the shape.
+    /// # Arguments
+    /// * ```point``` - The coordinates of the point.
+    /// * ```thickness``` - The thickness of the outline, centered on the edge. If the thickness is 0, the shape is filled.
+    /// * ```falloff``` - The width of the transition from the full to no coverage, centered on the edge.
+    /// # Returns
+    /// * The coverage in range ```[0.0, 1.0]```.
+    pub(crate) fn coverage(&self, point: (f64, f64), thickness: f64, falloff: f64) -> f64 {
+        let mut distance = self.distance(point);
+        if thickness > 0.0 {
+            distance = distance.abs() - thickness / 2.0;
+        }
+        let coverage = if falloff > 0.0 {
+            0.5 - distance / falloff
+        } else if distance <= 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        // NaN distances (degenerate shapes) give no coverage
+        if coverage > 0.0 {
+            coverage.min(1.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Returns the signed distance from the point to the edge of an ellipse centered at the origin.
+/// The closest point on the ellipse is found iteratively.
+/// # Arguments
+/// * ```point``` - The coordinates of the point relative to the center of the ellipse.
+/// * ```axes``` - The non-negative lengths of the semi-axes of the ellipse.
+/// # Returns
+/// * The signed distance.
+fn ellipse_distance(point: (f64, f64), axes: (f64, f64)) -> f64 {
+    let (a, b) = axes;
+    let (px, py) = (point.0.abs(), point.1.abs());
+    if a == 0.0 || b == 0.0 {
+        // degenerate ellipse is a line segment
+        let (half_x, half_y) = (a, b);
+        return (px - px.min(half_x)).hypot(py - py.min(half_y));
+    }
+
+    // parametric angle of the closest point, stored as its cosine and sine
+    let (mut tx, mut ty) = (std::f64::consts::FRAC_1_SQRT_2, std::f64::consts::FRAC_1_SQRT_2);
+    for _ in 0..4 {
+        let (x, y) = (a * tx, b * ty);
+        let ex = (a * a - b * b) * tx.powi(3) / a;
+        let ey = (b * b - a * a) * ty.powi(3) / b;
+        let (rx, ry) = (x - ex, y - ey);
+        let (qx, qy) = (px - ex, py - ey);
+        let r = rx.hypot(ry);
+        let q = qx.hypot(qy);
+        if q == 0.0 {
+            break;
+        }
+        tx = ((qx * r / q + ex) / a).clamp(0.0, 1.0);
+        ty = ((qy * r / q + ey) / b).clamp(0.0, 1.0);
+        let t = tx.hypot(ty);
+        tx /= t;
+        ty /= t;
+    }
+
+    let distance = (px - a * tx).hypot(py - b * ty);
+    if (px / a).powi(2) + (py / b).powi(2) < 1.0 {
+        -distance
+    } else {
+        distance
+    }
+}
+
+/// A struct that holds the parameters of a shadow cast by a shape.
+/// With no offset and a light color blended with [BlendMode::Screen](crate::BlendMode::Screen) it gives a glow.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Shadow {
+    /// The offset of the shadow from the shape
+    pub offset: (f64, f64),
+    /// The blur radius of the shadow, the shadow spreads roughly this far beyond the edge of the shape
+    pub blur: f64,
+    /// The color of the shadow
+    pub color: Color,
+}
+impl Shadow {
+    /// Creates a new shadow.
+    /// # Arguments
+    /// * ```offset``` - The offset of the shadow from the shape.
+    /// * ```blur``` - The blur radius of the shadow.
+    /// * ```color``` - The color of the shadow.
+    /// # Returns
+    /// * The new shadow.
+    pub const fn new(offset: (f64, f64), blur: f64, color: Color) -> Self {
+        Self { offset, blur, color }
+    }
+}
+
+impl Image {
+    /// Draws a shape on the image using its signed distance function, see [DrawTarget::draw_sdf()].
+    pub fn draw_sdf<B: Into<Blend>>(&mut self, shape: &SdfShape, color: Color, thickness: f64, falloff: f64, opacity: f64, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_sdf(self, shape, color, thickness, falloff, opacity, blend)
+    }
+
+    /// Draws the shadow of a shape on the image, see [DrawTarget::draw_shadow()].
+    pub fn draw_shadow<B: Into<Blend>>(&mut self, shape: &SdfShape, thickness: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_shadow(self, shape, thickness, shadow, blend)
+    }
+
+    /// Draws a shape with its shadow underneath on the image using its signed distance function, see [DrawTarget::draw_sdf_with_shadow()].
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_sdf_with_shadow<B: Into<Blend>>(&mut self, shape: &SdfShape, color: Color, thickness: f64, falloff: f64, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        DrawTarget::draw_sdf_with_shadow(self, shape, color, thickness, falloff, opacity, shadow, blend)
+    }
+}
+
+/// Draws a shape using its signed distance function, see [DrawTarget::draw_sdf()].
+pub(crate) fn draw_sdf<T: DrawTarget + ?Sized>(target: &mut T, shape: &SdfShape, color: Color, thickness: f64, falloff: f64, opacity: f64, blend: Blend) -> Result<(), Error> {
+    check_sdf_arguments(target.color_type(), color, thickness, falloff, opacity)?;
+
+    // if opacity is 0.0 and the operator keeps the destination, nothing is to be drawn
+    if opacity == 0.0 && blend.composite_op.keeps_destination() {
+        return Ok(());
+    }
+
+    // find the pixels which can be covered by the shape
+    let ((min_x, min_y), (max_x, max_y)) = shape.bounds();
+    let margin = thickness / 2.0 + falloff / 2.0;
+    let min_x = (min_x - margin).floor().max(0.0);
+    let min_y = (min_y - margin).floor().max(0.0);
+    let max_x = (max_x + margin).ceil().min(target.width() as f64 - 1.0);
+    let max_y = (max_y + margin).ceil().min(target.height() as f64 - 1.0);
+    if !(min_x <= max_x && min_y <= max_y) {
+        return Ok(()); // shape is out of image (or degenerate), nothing is to be drawn.
+    }
+
+    for y in (min_y as usize)..=(max_y as usize) {
+        for x in (min_x as usize)..=(max_x as usize) {
+            let coverage = shape.coverage((x as f64, y as f64), thickness, falloff);
+            if coverage > 0.0 {
+                fill(target, x..(x + 1), y..(y + 1), color, opacity * coverage, blend);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Draws the shadow of a shape, see [DrawTarget::draw_shadow()].
+pub(crate) fn draw_shadow<T: DrawTarget + ?Sized>(target: &mut T, shape: &SdfShape, thickness: f64, shadow: &Shadow, blend: Blend) -> Result<(), Error> {
+    check_shadow_arguments(target.color_type(), thickness, shadow)?;
+
+    // render the moved shape into the mask,
+    // covering the image and the margin from which the blur reaches into the image,
+    // which is at most the size of the image, as the blur of larger sizes is spread too thin to be seen
+    let sigma = shadow.blur / 2.0;
+    let margin = (3.0 * sigma).ceil().min(target.width().max(target.height()) as f64) as isize;
+    let moved_shape = shape.translated(shadow.offset);
+    let Some(mut mask) = Mask::from_shape(
+        &moved_shape,
+        thickness,
+        1.0,
+        margin,
+        (-margin, -margin),
+        ((target.width() as isize).saturating_add(margin - 1), (target.height() as isize).saturating_add(margin - 1)),
+    ) else {
+        return Ok(()); // shadow is out of image, nothing is to be drawn.
+    };
+
+    mask.gaussian_blur(sigma);
+    fill_mask(target, &mask, shadow.color, 1.0, blend);
+
+    Ok(())
+}
+
+/// Draws a shape with its shadow underneath, see [DrawTarget::draw_sdf_with_shadow()].
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_sdf_with_shadow<T: DrawTarget + ?Sized>(target: &mut T, shape: &SdfShape, color: Color, thickness: f64, falloff: f64, opacity: f64, shadow: &Shadow, blend: Blend) -> Result<(), Error> {
+    // check all arguments before anything is drawn
+    check_sdf_arguments(target.color_type(), color, thickness, falloff, opacity)?;
+    check_shadow_arguments(target.color_type(), thickness, shadow)?;
+
+    draw_shadow(target, shape, thickness, shadow, blend)?;
+    draw_sdf(target, shape, color, thickness, falloff, opacity, blend)
+}
+
+/// Checks the arguments of [draw_sdf()].
+/// # Errors
+/// * [Error::WrongColor] - If the color type of the target does not match the color type of the color.
+/// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+/// * [Error::InvalidThickness] - If the thickness is NaN, infinite or negative.
+/// * [Error::InvalidFalloff] - If the falloff is NaN, infinite or negative.
+fn check_sdf_arguments(color_type: ColorType, color: Color, thickness: f64, falloff: f64, opacity: f64) -> Result<(), Error> {
+    if ColorType::from(color) != color_type {
+        return Err(Error::WrongColor);
+    }
+    if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
+        return Err(Error::InvalidOpacity);
+    }
+    if !thickness.is_finite() || thickness < 0.0 {
+        return Err(Error::InvalidThickness);
+    }
+    if !falloff.is_finite() || falloff < 0.0 {
+        return Err(Error::InvalidFalloff);
+    }
+    Ok(())
+}
+
+/// Checks the arguments of [draw_shadow()].
+/// # Errors
+/// * [Error::WrongColor] - If the color type of the target does not match the color type of the shadow color.
+/// * [Error::InvalidThickness] - If the thickness is NaN, infinite or negative.
+/// * [Error::InvalidBlur] - If the blur radius is NaN, infinite or negative.
+pub(crate) fn check_shadow_arguments(color_type: ColorType, thickness: f64, shadow: &Shadow) -> Result<(), Error> {
+    if ColorType::from(shadow.color) != color_type {
+        return Err(Error::WrongColor);
+    }
+    if !thickness.is_finite() || thickness < 0.0 {
+        return Err(Error::InvalidThickness);
+    }
+    if !shadow.blur.is_finite() || shadow.blur < 0.0 {
+        return Err(Error::InvalidBlur);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BlendMode;
+
+    #[test]
+    fn test_circle_distance() {
+        let circle = SdfShape::Circle { center: (10.0, 10.0), radius: 5.0 };
+        assert_eq!(circle.distance((10.0, 10.0)), -5.0);
+        assert_eq!(circle.distance((10.0, 15.0)), 0.0);
+        assert_eq!(circle.distance((13.0, 14.0)), 0.0);
+        assert_eq!(circle.distance((20.0, 10.0)), 5.0);
+        assert_eq!(circle.bounds(), ((5.0, 5.0), (15.0, 15.0)));
+    }
+
+    #[test]
+    fn test_ellipse_distance() {
+        let ellipse = SdfShape::Ellipse { center: (0.0, 0.0), axes: (8.0, 3.0) };
+        assert!((ellipse.distance((0.0, 0.0)) + 3.0).abs() < 1e-9);
+        assert!((ellipse.distance((10.0, 0.0)) - 2.0).abs() < 1e-9);
+        assert!((ellipse.distance((0.0, -5.0)) - 2.0).abs() < 1e-9);
+        assert_eq!(ellipse.bounds(), ((-8.0, -3.0), (8.0, 3.0)));
+
+        // compare with the brute force distance to the sampled edge
+        for point in [(3.0, 2.0), (-6.0, 4.0), (9.0, -1.5), (1.0, -0.5), (-20.0, 20.0)] {
+            let brute_force = (0..100_000)
+                .map(|i| {
+                    let angle = i as f64 / 100_000.0 * std::f64::consts::TAU;
+                    (point.0 - 8.0 * angle.cos()).hypot(point.1 - 3.0 * angle.sin())
+                })
+                .fold(f64::INFINITY, f64::min);
+            assert!((ellipse.distance(point).abs() - brute_force).abs() < 1e-3, "{:?}", point);
+        }
+
+        let circle = SdfShape::Circle { center: (1.0, 2.0), radius: 4.0 };
+        let ellipse = SdfShape::Ellipse { center: (1.0, 2.0), axes: (4.0, 4.0) };
+        for point in [(0.0, 0.0), (7.0, -3.0), (2.0, 2.5)] {
+            assert!((circle.distance(point) - ellipse.distance(point)).abs() < 1e-9);
+        }
+
+        let flat = SdfShape::Ellipse { center: (0.0, 0.0), axes: (5.0, 0.0) };
+        assert_eq!(flat.distance((3.0, 2.0)), 2.0);
+        assert_eq!(flat.distance((8.0, 4.0)), 5.0);
+    }
+
+    #[test]
+    fn test_rounded_rectangle_distance() {
+        let rectangle = SdfShape::RoundedRectangle { point1: (10.0, 0.0), point2: (0.0, 6.0), radius: 2.0 };
+        assert_eq!(rectangle.distance((5.0, 3.0)), -3.0);
+        assert_eq!(rectangle.distance((5.0, 8.0)), 2.0);
+        assert_eq!(rectangle.distance((13.0, 3.0)), 3.0);
+        assert!((rectangle.distance((-1.0, -1.0)) - (18.0_f64.sqrt() - 2.0)).abs() < 1e-9);
+        assert_eq!(rectangle.bounds(), ((0.0, 0.0), (10.0, 6.0)));
+
+        let sharp = SdfShape::RoundedRectangle { point1: (0.0, 0.0), point2: (10.0, 6.0), radius: 0.0 };
+        assert_eq!(sharp.distance((13.0, 10.0)), 5.0);
+    }
+
+    #[test]
+    fn test_capsule_distance() {
+        let capsule = SdfShape::Capsule { point1: (0.0, 0.0), point2: (10.0, 0.0), radius: 2.0 };
+        assert_eq!(capsule.distance((5.0, 0.0)), -2.0);
+        assert_eq!(capsule.distance((5.0, 5.0)), 3.0);
+        assert_eq!(capsule.distance((-3.0, 4.0)), 3.0);
+        assert_eq!(capsule.distance((13.0, 0.0)), 1.0);
+        assert_eq!(capsule.bounds(), ((-2.0, -2.0), (12.0, 2.0)));
+
+        let dot = SdfShape::Capsule { point1: (1.0, 1.0), point2: (1.0, 1.0), radius: 1.0 };
+        assert_eq!(dot.distance((4.0, 5.0)), 4.0);
+    }
+
+    #[test]
+    fn test_coverage() {
+        let circle = SdfShape::Circle { center: (0.0, 0.0), radius: 10.0 };
+        assert_eq!(circle.coverage((0.0, 0.0), 0.0, 1.0), 1.0);
+        assert_eq!(circle.coverage((10.0, 0.0), 0.0, 1.0), 0.5);
+        assert_eq!(circle.coverage((10.25, 0.0), 0.0, 1.0), 0.25);
+        assert_eq!(circle.coverage((11.0, 0.0), 0.0, 1.0), 0.0);
+        assert_eq!(circle.coverage((12.0, 0.0), 0.0, 8.0), 0.25);
+        assert_eq!(circle.coverage((10.0, 0.0), 0.0, 0.0), 1.0);
+        assert_eq!(circle.coverage((10.1, 0.0), 0.0, 0.0), 0.0);
+        assert_eq!(circle.coverage((0.0, 0.0), 2.0, 1.0), 0.0);
+        assert_eq!(circle.coverage((10.0, 0.0), 2.0, 1.0), 1.0);
+        assert_eq!(circle.coverage((9.0, 0.0), 2.0, 1.0), 0.5);
+        assert_eq!(circle.coverage((11.0, 0.0), 2.0, 1.0), 0.5);
+
+        let degenerate = SdfShape::Circle { center: (f64::NAN, 0.0), radius: 10.0 };
+        assert_eq!(degenerate.coverage((0.0, 0.0), 0.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_draw_sdf() {
+        let mut image = Image::new(41, 41, Color::GRAY8(0));
+
+        // test errors
+        let circle = SdfShape::Circle { center: (20.0, 20.0), radius: 10.0 };
+        assert_eq!(image.draw_sdf(&circle, Color::RGB8([255, 255, 255]), 0.0, 1.0, 1.0, Blend::default()), Err(Error::WrongColor));
+        assert_eq!(image.draw_sdf(&circle, Color::GRAY8(255), 0.0, 1.0, 1.5, Blend::default()), Err(Error::InvalidOpacity));
+        assert_eq!(image.draw_sdf(&circle, Color::GRAY8(255), -1.0, 1.0, 1.0, Blend::default()), Err(Error::InvalidThickness));
+        assert_eq!(image.draw_sdf(&circle, Color::GRAY8(255), 0.0, f64::NAN, 1.0, Blend::default()), Err(Error::InvalidFalloff));
+
+        // test drawing
+        image.draw_sdf(&circle, Color::GRAY8(255), 0.0, 1.0, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((20, 20)).unwrap(), Color::GRAY8(255));
+        assert_eq!(image.get((30, 20)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((20, 10)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((31, 20)).unwrap(), Color::GRAY8(0));
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAY8(0));
+
+        // the result is symmetric
+        for y in 0..41 {
+            for x in 0..41 {
+                assert_eq!(image.get((x, y)).unwrap(), image.get((40 - x, y)).unwrap());
+                assert_eq!(image.get((x, y)).unwrap(), image.get((y, x)).unwrap());
+            }
+        }
+
+        // shapes partially or fully outside of the image
+        let capsule = SdfShape::Capsule { point1: (-10.0, -10.0), point2: (60.0, 60.0), radius: 3.0 };
+        image.draw_sdf(&capsule, Color::GRAY8(100), 2.0, 4.0, 0.5, BlendMode::Screen).unwrap();
+        let outside = SdfShape::Ellipse { center: (-100.0, 20.0), axes: (10.0, 5.0) };
+        image.draw_sdf(&outside, Color::GRAY8(100), 0.0, 1.0, 1.0, Blend::default()).unwrap();
+        let rectangle = SdfShape::RoundedRectangle { point1: (30.0, -5.0), point2: (50.0, 5.0), radius: 3.0 };
+        image.draw_sdf(&rectangle, Color::GRAY8(200), 0.0, 1.0, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((40, 0)).unwrap(), Color::GRAY8(200));
+    }
+
+    #[test]
+    fn test_translated() {
+        let offset = (1.5, -2.0);
+        for shape in [
+            SdfShape::Circle { center: (1.0, 2.0), radius: 3.0 },
+            SdfShape::Ellipse { center: (1.0, 2.0), axes: (3.0, 4.0) },
+            SdfShape::RoundedRectangle { point1: (1.0, 2.0), point2: (5.0, 7.0), radius: 1.0 },
+            SdfShape::Capsule { point1: (1.0, 2.0), point2: (5.0, 7.0), radius: 1.0 },
+        ] {
+            let moved = shape.translated(offset);
+            assert!((moved.distance((4.0, 1.0)) - shape.distance((2.5, 3.0))).abs() < 1e-9);
+            let ((min_x, min_y), (max_x, max_y)) = shape.bounds();
+            assert_eq!(moved.bounds(), ((min_x + 1.5, min_y - 2.0), (max_x + 1.5, max_y - 2.0)));
+        }
+    }
+
+    #[test]
+    fn test_draw_shadow() {
+        let mut image = Image::new(60, 60, Color::RGBA8([255, 255, 255, 255]));
+        let card = SdfShape::RoundedRectangle { point1: (10.0, 20.0), point2: (40.0, 50.0), radius: 4.0 };
+        let shadow = Shadow::new((4.0, -4.0), 6.0, Color::RGBA8([0, 0, 0, 128]));
+
+        // test errors
+        assert_eq!(image.draw_shadow(&card, 0.0, &Shadow::new((0.0, 0.0), 1.0, Color::GRAY8(0)), Blend::default()), Err(Error::WrongColor));
+        assert_eq!(image.draw_shadow(&card, f64::INFINITY, &shadow, Blend::default()), Err(Error::InvalidThickness));
+        assert_eq!(image.draw_shadow(&card, 0.0, &Shadow::new((0.0, 0.0), -1.0, shadow.color), Blend::default()), Err(Error::InvalidBlur));
+
+        // test drawing
+        image.draw_shadow(&card, 0.0, &shadow, Blend::default()).unwrap();
+        image.draw_sdf(&card, Color::RGBA8([255, 0, 0, 255]), 0.0, 1.0, 1.0, Blend::default()).unwrap();
+
+        // the shape is drawn over the shadow
+        assert_eq!(image.get((25, 35)).unwrap(), Color::RGBA8([255, 0, 0, 255]));
+        // the shadow is darkest under the moved shape and fades out
+        let Color::RGBA8(under) = image.get((42, 25)).unwrap() else { panic!() };
+        let Color::RGBA8(edge) = image.get((46, 25)).unwrap() else { panic!() };
+        let Color::RGBA8(far) = image.get((52, 25)).unwrap() else { panic!() };
+        assert!(under[0] < edge[0] && edge[0] < far[0], "{:?} {:?} {:?}", under, edge, far);
+        assert_eq!(image.get((5, 55)).unwrap(), Color::RGBA8([255, 255, 255, 255]));
+        assert_eq!(image.get((2, 2)).unwrap(), Color::RGBA8([255, 255, 255, 255]));
+
+        // unblurred shadow is a moved copy of the shape
+        let mut shadow_image = Image::new(60, 60, Color::GRAY8(0));
+        let mut shape_image = Image::new(60, 60, Color::GRAY8(0));
+        shadow_image.draw_shadow(&card, 2.0, &Shadow::new((5.0, 3.0), 0.0, Color::GRAY8(255)), Blend::default()).unwrap();
+        shape_image.draw_sdf(&card.translated((5.0, 3.0)), Color::GRAY8(255), 2.0, 1.0, 1.0, Blend::default()).unwrap();
+        assert_eq!(shadow_image, shape_image);
+
+        // shadow of a shape outside of the image reaches into the image
+        let mut image = Image::new(20, 20, Color::GRAY8(255));
+        let outside = SdfShape::Circle { center: (-5.0, 10.0), radius: 4.0 };
+        image.draw_shadow(&outside, 0.0, &Shadow::new((0.0, 0.0), 8.0, Color::GRAY8(0)), Blend::default()).unwrap();
+        assert!(image.get((0, 10)).unwrap() != Color::GRAY8(255));
+        image.draw_shadow(&outside, 0.0, &Shadow::new((-100.0, 0.0), 8.0, Color::GRAY8(0)), Blend::default()).unwrap();
+
+        // large blur spreads the shadow too thin to be seen, without a mask larger than three times the image
+        let mut image = Image::new(20, 20, Color::GRAY8(255));
+        let inside = SdfShape::Circle { center: (10.0, 10.0), radius: 4.0 };
+        for blur in [1e4, 1e30, f64::MAX] {
+            image.draw_shadow(&inside, 0.0, &Shadow::new((0.0, 0.0), blur, Color::GRAY8(0)), Blend::default()).unwrap();
+        }
+        assert_eq!(image, Image::new(20, 20, Color::GRAY8(255)));
+    }
+
+    #[test]
+    fn test_draw_sdf_with_shadow() {
+        let card = SdfShape::RoundedRectangle { point1: (10.0, 20.0), point2: (40.0, 50.0), radius: 4.0 };
+        let shadow = Shadow::new((4.0, -4.0), 6.0, Color::RGBA8([0, 0, 0, 128]));
+        let mut image = Image::new(60, 60, Color::RGBA8([255, 255, 255, 255]));
+
+        // test errors, nothing is drawn if any of the arguments is invalid
+        assert_eq!(image.draw_sdf_with_shadow(&card, Color::RGBA8([255, 0, 0, 255]), 0.0, 1.0, 1.0, &Shadow::new((0.0, 0.0), 1.0, Color::GRAY8(0)), Blend::default()), Err(Error::WrongColor));
+        assert_eq!(image.draw_sdf_with_shadow(&card, Color::RGBA8([255, 0, 0, 255]), 0.0, -1.0, 1.0, &shadow, Blend::default()), Err(Error::InvalidFalloff));
+        assert_eq!(image.draw_sdf_with_shadow(&card, Color::RGBA8([255, 0, 0, 255]), 0.0, 1.0, 1.0, &Shadow::new((0.0, 0.0), -1.0, shadow.color), Blend::default()), Err(Error::InvalidBlur));
+        assert_eq!(image, Image::new(60, 60, Color::RGBA8([255, 255, 255, 255])));
+
+        // the shadow is drawn underneath the shape
+        let mut expected = image.clone();
+        image.draw_sdf_with_shadow(&card, Color::RGBA8([255, 0, 0, 255]), 2.0, 1.0, 1.0, &shadow, BlendMode::Multiply).unwrap();
+        expected.draw_shadow(&card, 2.0, &shadow, BlendMode::Multiply).unwrap();
+        expected.draw_sdf(&card, Color::RGBA8([255, 0, 0, 255]), 2.0, 1.0, 1.0, BlendMode::Multiply).unwrap();
+        assert_eq!(image, expected);
+    }
+
+    #[test]
+    fn test_draw_glow() {
+        let mut image = Image::new(30, 30, Color::RGB8([40, 40, 40]));
+        let button = SdfShape::Capsule { point1: (10.0, 15.0), point2: (20.0, 15.0), radius: 4.0 };
+        let glow = Shadow::new((0.0, 0.0), 4.0, Color::RGB8([0, 200, 255]));
+        image.draw_shadow(&button, 0.0, &glow, BlendMode::Screen).unwrap();
+
+        // screen only lightens the image, most near the shape
+        let Color::RGB8(near) = image.get((15, 21)).unwrap() else { panic!() };
+        let Color::RGB8(far) = image.get((15, 23)).unwrap() else { panic!() };
+        assert_eq!(near[0], 40);
+        assert!(near[2] > far[2] && far[2] > 40, "{:?} {:?}", near, far);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGB8([40, 40, 40]));
+    }
+}
@@ -0,0 +1,193 @@
+//! A module containing the supersampled rendering mode.
+//!
+//! Shapes are drawn onto an internal canvas scaled up by an integer factor,
+//! which is averaged (box-filtered) down to the target image on commit.
+//! Every primitive gets high-quality anti-aliasing this way, at the cost of memory and speed.
+
+use crate::blending::Blend;
+use crate::colors::{Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use crate::resampling::Filter;
+use crate::target::DrawTarget;
+use std::ops::Range;
+
+/// A struct that holds the scaled canvas of the supersampled rendering.
+/// Coordinates and sizes used for drawing onto it are in canvas pixels, ```factor``` times larger than the pixels of the target.
+/// The drawing is written to the target image by [Supersampled::commit()], dropping it discards the drawing.
+#[derive(Debug)]
+pub struct Supersampled<'a> {
+    /// The image the drawing is committed to
+    target: &'a mut Image,
+    /// The number of canvas pixels per target pixel along each axis
+    factor: usize,
+    /// The scaled canvas
+    canvas: Image,
+}
+impl<'a> Supersampled<'a> {
+    /// Creates a new supersampled canvas for the target image.
+    /// The canvas starts with the current content of the target.
+    /// # Arguments
+    /// * ```target``` - The image the drawing is committed to.
+    /// * ```factor``` - The number of canvas pixels per target pixel along each axis.
+    /// # Returns
+    /// * [Result] which holds the new [Supersampled] canvas or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the factor is 0, the width or the height of the target is 0, or the canvas would be too large.
+    pub fn new(target: &'a mut Image, factor: usize) -> Result<Self, Error> {
+        if factor == 0 {
+            return Err(Error::InvalidSize);
+        }
+        let width = target.width.checked_mul(factor).ok_or(Error::InvalidSize)?;
+        let height = target.height.checked_mul(factor).ok_or(Error::InvalidSize)?;
+        // the canvas has to fit into memory addresses as well
+        width.checked_mul(height).and_then(|pixels| pixels.checked_mul(target.color_type.bytes_per_pixel())).ok_or(Error::InvalidSize)?;
+        let canvas = target.resize(width, height, Filter::Nearest)?;
+        Ok(Self { target, factor, canvas })
+    }
+
+    /// Returns the number of canvas pixels per target pixel along each axis.
+    /// # Returns
+    /// * The factor of the canvas.
+    #[inline]
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Returns the scaled canvas.
+    /// # Returns
+    /// * The reference to the canvas.
+    #[inline]
+    pub fn canvas(&self) -> &Image {
+        &self.canvas
+    }
+
+    /// Returns the scaled canvas as mutable.
+    /// # Returns
+    /// * The mutable reference to the canvas.
+    #[inline]
+    pub fn canvas_mut(&mut self) -> &mut Image {
+        &mut self.canvas
+    }
+
+    /// Averages the canvas down to the size of the target and writes it to the target.
+    pub fn commit(self) {
+        let canvas = self.canvas.to_rgba_f64_premultiplied();
+        let canvas_width = self.canvas.width;
+        let samples = (self.factor * self.factor) as f64;
+        let mut pixels = vec![[0.0; 4]; self.target.width * self.target.height];
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            let x = (i % self.target.width) * self.factor;
+            let y = (i / self.target.width) * self.factor;
+            for row in canvas[y * canvas_width..].chunks(canvas_width).take(self.factor) {
+                for sample in &row[x..x + self.factor] {
+                    for channel in 0..4 {
+                        pixel[channel] += sample[channel];
+                    }
+                }
+            }
+            for channel in pixel.iter_mut() {
+                *channel /= samples;
+            }
+        }
+        let averaged = self.target.derive_from_rgba_f64_premultiplied(self.target.width, self.target.height, &pixels);
+        self.target.data = averaged.data;
+    }
+}
+
+impl DrawTarget for Supersampled<'_> {
+    fn width(&self) -> usize {
+        self.canvas.width
+    }
+
+    fn height(&self) -> usize {
+        self.canvas.height
+    }
+
+    fn color_type(&self) -> ColorType {
+        self.canvas.color_type
+    }
+
+    fn get_pixel(&self, index: (usize, usize)) -> Color {
+        self.canvas.get_pixel(index)
+    }
+
+    fn set_pixel(&mut self, index: (usize, usize), color: Color) {
+        self.canvas.set_pixel(index, color);
+    }
+
+    fn fill_span(&mut self, y: usize, x: Range<usize>, color: Color) {
+        self.canvas.fill_span(y, x, color);
+    }
+
+    fn blend_span(&mut self, y: usize, x: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+        self.canvas.blend_span(y, x, color, opacity, blend);
+    }
+}
+
+impl Image {
+    /// Starts the supersampled rendering onto the image, see [Supersampled].
+    /// # Arguments
+    /// * ```factor``` - The number of canvas pixels per image pixel along each axis.
+    /// # Returns
+    /// * [Result] which holds the new [Supersampled] canvas or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the factor is 0, the width or the height of the image is 0, or the canvas would be too large.
+    pub fn supersampled(&mut self, factor: usize) -> Result<Supersampled<'_>, Error> {
+        Supersampled::new(self, factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::AlphaMode;
+
+    #[test]
+    fn test_supersampled() {
+        let mut image = Image::new(4, 3, Color::GRAY8(0));
+        image.set((3, 2), Color::GRAY8(77)).unwrap();
+        assert_eq!(image.supersampled(0).unwrap_err(), Error::InvalidSize);
+        assert_eq!(image.supersampled(usize::MAX / 2).unwrap_err(), Error::InvalidSize);
+        assert_eq!(image.supersampled(1 << 31).unwrap_err(), Error::InvalidSize);
+
+        // committing without drawing keeps the image
+        let original = image.clone();
+        let supersampled = image.supersampled(3).unwrap();
+        assert_eq!((supersampled.canvas().width(), supersampled.canvas().height()), (12, 9));
+        assert_eq!(supersampled.canvas().get((11, 8)).unwrap(), Color::GRAY8(77));
+        supersampled.commit();
+        assert_eq!(image, original);
+
+        // dropping discards the drawing
+        let mut supersampled = image.supersampled(2).unwrap();
+        supersampled.draw_rectangle((0, 0), (7, 5), Color::GRAY8(255), 0, 1.0, Blend::default()).unwrap();
+        drop(supersampled);
+        assert_eq!(image, original);
+
+        // edges between target pixels are anti-aliased
+        let mut supersampled = image.supersampled(4).unwrap();
+        assert_eq!(supersampled.factor(), 4);
+        supersampled.draw_rectangle((2, 0), (5, 3), Color::GRAY8(255), 0, 1.0, Blend::default()).unwrap();
+        supersampled.draw_rectangle((8, 4), (15, 11), Color::GRAY8(255), 1, 1.0, Blend::default()).unwrap();
+        supersampled.canvas_mut().set((15, 11), Color::GRAY8(0)).unwrap();
+        supersampled.commit();
+        assert_eq!(image.get((0, 0)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((1, 0)).unwrap(), Color::GRAY8(128));
+        assert_eq!(image.get((0, 1)).unwrap(), Color::GRAY8(0));
+        assert_eq!(image.get((2, 1)).unwrap(), Color::GRAY8(112));
+        assert_eq!(image.get((3, 2)).unwrap(), Color::GRAY8(139));
+    }
+
+    #[test]
+    fn test_supersampled_alpha() {
+        let mut image = Image::new(2, 1, Color::RGBA16([0, 0, 0, 0]));
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        let mut supersampled = image.supersampled(2).unwrap();
+        supersampled.draw_rectangle((0, 0), (0, 1), Color::RGBA16([65_535, 0, 0, 65_535]), 0, 1.0, Blend::default()).unwrap();
+        supersampled.commit();
+        assert_eq!(image.alpha_mode(), AlphaMode::Premultiplied);
+        assert_eq!(image.get((0, 0)).unwrap(), Color::RGBA16([65_535, 0, 0, 32_768]));
+        assert_eq!(image.get((1, 0)).unwrap(), Color::RGBA16([0, 0, 0, 0]));
+    }
+}
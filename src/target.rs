@@ -0,0 +1,417 @@
+//! A module containing the [DrawTarget] trait, which allows drawing onto any pixel buffer.
+//!
+//! All shapes are rasterized through the few pixel access methods of the trait,
+//! so implementing them for a framebuffer, a staging buffer or a custom image type gives access to all drawing functions.
+//! [Image] is one implementation of the trait.
+
+use crate::blending::Blend;
+use crate::colors::{Color, ColorType};
+use crate::drawing;
+use crate::error::Error;
+use crate::image::Image;
+use crate::sdf::{self, SdfShape, Shadow};
+use std::ops::Range;
+
+/// Trait for pixel buffers which shapes can be drawn onto.
+/// Coordinates origin is in the bottom left corner, as in [Image].
+/// All colors passed to and returned from the trait methods use straight (not premultiplied) alpha.
+pub trait DrawTarget {
+    /// Returns the width of the target.
+    /// # Returns
+    /// * The width of the target.
+    fn width(&self) -> usize;
+
+    /// Returns the height of the target.
+    /// # Returns
+    /// * The height of the target.
+    fn height(&self) -> usize;
+
+    /// Returns the color type of the target.
+    /// # Returns
+    /// * The color type of the target.
+    fn color_type(&self) -> ColorType;
+
+    /// Returns the value of the pixel at the given coordinates.
+    /// The coordinates are always inside of the target and the returned color must have the color type of the target.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * The value of the pixel.
+    fn get_pixel(&self, index: (usize, usize)) -> Color;
+
+    /// Sets the value of the pixel at the given coordinates.
+    /// The coordinates are always inside of the target and the color always has the color type of the target.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```color``` - The color to set.
+    fn set_pixel(&mut self, index: (usize, usize), color: Color);
+
+    /// Sets the value of the horizontal span of pixels.
+    /// The default implementation sets the pixels one by one, targets with faster access should override it.
+    /// # Arguments
+    /// * ```y``` - The y coordinate of the span.
+    /// * ```x``` - The range of the x coordinates of the span.
+    /// * ```color``` - The color to set.
+    fn fill_span(&mut self, y: usize, x: Range<usize>, color: Color) {
+        for x in x {
+            self.set_pixel((x, y), color);
+        }
+    }
+
+    /// Blends the color with the horizontal span of pixels.
+    /// Unlike in [DrawTarget::set_pixel()], the color does not have to have the color type of the target.
+    /// It can carry an alpha channel the target lacks (see [ColorType::with_alpha()]), e.g. when drawing images with transparency,
+    /// and indexed targets get direct colors instead of palette indices. The resulting pixels must have the color type of the target.
+    /// The default implementation blends the pixels one by one, targets with faster access should override it.
+    /// # Arguments
+    /// * ```y``` - The y coordinate of the span.
+    /// * ```x``` - The range of the x coordinates of the span.
+    /// * ```color``` - The color to blend.
+    /// * ```opacity``` - The opacity of the color. It multiplies the alpha channel of the color.
+    /// * ```blend``` - The blend used to combine the color with the pixels.
+    fn blend_span(&mut self, y: usize, x: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+        let mut source = blend.space.decode(color);
+        source[3] *= opacity;
+        for x in x {
+            let new_color = blend.blend_color(source, self.get_pixel((x, y)), false);
+            self.set_pixel((x, y), new_color);
+        }
+    }
+
+    /// Draws a circle on the target. If the circle is not fully contained in the target, it will be clipped.
+    /// The circle is anti-aliased using its signed distance function (see [DrawTarget::draw_sdf()]).
+    /// # Arguments
+    /// * ```center``` - The coordinates of the center of the circle.
+    /// * ```radius``` - The radius of the circle.
+    /// * ```color``` - The color of the circle.
+    /// * ```thickness``` - The thickness of the circle, measured inwards from the radius. If the thickness is 0, the circle will be filled.
+    /// * ```opacity``` - The opacity of the circle.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the circle with the target.
+    fn draw_circle<B: Into<Blend>>(&mut self, center: (usize, usize), radius: usize, color: Color, thickness: usize, opacity: f64, blend: B) -> Result<(), Error> {
+        drawing::draw_ellipse(self, center, (radius, radius), color, thickness, opacity, blend.into())
+    }
+
+    /// Draws an ellipse on the target. If the ellipse is not fully contained in the target, it will be clipped.
+    /// The ellipse is anti-aliased using its signed distance function (see [DrawTarget::draw_sdf()]).
+    /// # Arguments
+    /// * ```center``` - The coordinates of the center of the ellipse.
+    /// * ```axes``` - The lengths of the axes of the ellipse.
+    /// * ```color``` - The color of the ellipse.
+    /// * ```thickness``` - The thickness of the ellipse, measured inwards from the axes. If the thickness is 0, the ellipse will be filled.
+    /// * ```opacity``` - The opacity of the ellipse.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the ellipse with the target.
+    fn draw_ellipse<B: Into<Blend>>(&mut self, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64, blend: B) -> Result<(), Error> {
+        drawing::draw_ellipse(self, center, axes, color, thickness, opacity, blend.into())
+    }
+
+    /// Draws a circle with its shadow underneath on the target. Parts of the circle and the shadow outside of the target are clipped.
+    /// All arguments are checked before anything is drawn, then the shadow and the circle are drawn with the same blend.
+    /// # Arguments
+    /// * ```center``` - The coordinates of the center of the circle.
+    /// * ```radius``` - The radius of the circle.
+    /// * ```color``` - The color of the circle.
+    /// * ```thickness``` - The thickness of the circle, measured inwards from the radius. If the thickness is 0, the circle will be filled.
+    /// * ```opacity``` - The opacity of the circle. The opacity of the shadow is given by the alpha of its color.
+    /// * ```shadow``` - The shadow cast by the circle.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the shadow and the circle with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the target does not match the color type of the color or the shadow color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    /// * [Error::InvalidBlur] - If the blur radius is NaN, infinite or negative.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_circle_with_shadow<B: Into<Blend>>(&mut self, center: (usize, usize), radius: usize, color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        drawing::draw_ellipse_with_shadow(self, center, (radius, radius), color, thickness, opacity, shadow, blend.into())
+    }
+
+    /// Draws an ellipse with its shadow underneath on the target. Parts of the ellipse and the shadow outside of the target are clipped.
+    /// All arguments are checked before anything is drawn, then the shadow and the ellipse are drawn with the same blend.
+    /// # Arguments
+    /// * ```center``` - The coordinates of the center of the ellipse.
+    /// * ```axes``` - The lengths of the axes of the ellipse.
+    /// * ```color``` - The color of the ellipse.
+    /// * ```thickness``` - The thickness of the ellipse, measured inwards from the axes. If the thickness is 0, the ellipse will be filled.
+    /// * ```opacity``` - The opacity of the ellipse. The opacity of the shadow is given by the alpha of its color.
+    /// * ```shadow``` - The shadow cast by the ellipse.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the shadow and the ellipse with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the target does not match the color type of the color or the shadow color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    /// * [Error::InvalidBlur] - If the blur radius is NaN, infinite or negative.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_ellipse_with_shadow<B: Into<Blend>>(&mut self, center: (usize, usize), axes: (usize, usize), color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        drawing::draw_ellipse_with_shadow(self, center, axes, color, thickness, opacity, shadow, blend.into())
+    }
+
+    /// Draws a rectangle on the target. If the rectangle is not fully contained in the target, it will be clipped.
+    /// # Arguments
+    /// * ```point1``` - The coordinates of the first point of the rectangle.
+    /// * ```point2``` - The coordinates of the second point of the rectangle.
+    /// * ```color``` - The color of the rectangle.
+    /// * ```thickness``` - The thickness of the rectangle. If the thickness is 0, the rectangle will be filled.
+    /// * ```opacity``` - The opacity of the rectangle.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the rectangle with the target.
+    fn draw_rectangle<B: Into<Blend>>(&mut self, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64, blend: B) -> Result<(), Error> {
+        drawing::draw_rectangle(self, point1, point2, color, thickness, opacity, blend.into())
+    }
+
+    /// Draws a rectangle with its shadow underneath on the target. Parts of the rectangle and the shadow outside of the target are clipped.
+    /// All arguments are checked before anything is drawn, then the shadow and the rectangle are drawn with the same blend.
+    /// # Arguments
+    /// * ```point1``` - The coordinates of the first point of the rectangle.
+    /// * ```point2``` - The coordinates of the second point of the rectangle.
+    /// * ```color``` - The color of the rectangle.
+    /// * ```thickness``` - The thickness of the rectangle. If the thickness is 0, the rectangle will be filled.
+    /// * ```opacity``` - The opacity of the rectangle. The opacity of the shadow is given by the alpha of its color.
+    /// * ```shadow``` - The shadow cast by the rectangle.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the shadow and the rectangle with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the target does not match the color type of the color or the shadow color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    /// * [Error::InvalidBlur] - If the blur radius is NaN, infinite or negative.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_rectangle_with_shadow<B: Into<Blend>>(&mut self, point1: (usize, usize), point2: (usize, usize), color: Color, thickness: usize, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        drawing::draw_rectangle_with_shadow(self, point1, point2, color, thickness, opacity, shadow, blend.into())
+    }
+
+    /// Draws the source image onto the target. Parts of the source image outside of the target are clipped.
+    /// The visible part of the source image is converted to the color type of the target (keeping its alpha channel) and blended in spans of equal pixels.
+    /// Indexed targets get the colors of the source image snapped to the nearest colors of their palette.
+    /// Only the pixels of the target covered by the source image are changed, even by operators like [CompositeOp::Copy](crate::CompositeOp::Copy).
+    /// # Arguments
+    /// * ```source``` - The image to draw.
+    /// * ```position``` - The coordinates of the bottom left corner of the source image on the target, can be negative.
+    /// * ```opacity``` - The opacity of the source image.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the source image with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    fn draw_image<B: Into<Blend>>(&mut self, source: &Image, position: (isize, isize), opacity: f64, blend: B) -> Result<(), Error> {
+        drawing::draw_image(self, source, position, opacity, blend.into())
+    }
+
+    /// Draws a shape using its signed distance function. Parts of the shape outside of the target are clipped.
+    /// # Arguments
+    /// * ```shape``` - The shape to draw.
+    /// * ```color``` - The color of the shape.
+    /// * ```thickness``` - The thickness of the outline, centered on the edge of the shape. If the thickness is 0, the shape will be filled.
+    /// * ```falloff``` - The width of the edge in pixels, over which the shape fades out.
+    ///   1.0 gives regular anti-aliasing, larger values give soft edges and glows, 0.0 gives hard, aliased edges.
+    /// * ```opacity``` - The opacity of the shape.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the shape with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the target does not match the color type of the color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    /// * [Error::InvalidThickness] - If the thickness is NaN, infinite or negative.
+    /// * [Error::InvalidFalloff] - If the falloff is NaN, infinite or negative.
+    fn draw_sdf<B: Into<Blend>>(&mut self, shape: &SdfShape, color: Color, thickness: f64, falloff: f64, opacity: f64, blend: B) -> Result<(), Error> {
+        sdf::draw_sdf(self, shape, color, thickness, falloff, opacity, blend.into())
+    }
+
+    /// Draws a shape using its signed distance function, with its shadow underneath. Parts of the shape and the shadow outside of the target are clipped.
+    /// All arguments are checked before anything is drawn, then the shadow and the shape are drawn with the same blend.
+    /// # Arguments
+    /// * ```shape``` - The shape to draw.
+    /// * ```color``` - The color of the shape.
+    /// * ```thickness``` - The thickness of the outline, centered on the edge of the shape. If the thickness is 0, the shape will be filled.
+    /// * ```falloff``` - The width of the edge in pixels, over which the shape fades out.
+    /// * ```opacity``` - The opacity of the shape. The opacity of the shadow is given by the alpha of its color.
+    /// * ```shadow``` - The shadow cast by the shape.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the shadow and the shape with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the target does not match the color type of the color or the shadow color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    /// * [Error::InvalidThickness] - If the thickness is NaN, infinite or negative.
+    /// * [Error::InvalidFalloff] - If the falloff is NaN, infinite or negative.
+    /// * [Error::InvalidBlur] - If the blur radius is NaN, infinite or negative.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_sdf_with_shadow<B: Into<Blend>>(&mut self, shape: &SdfShape, color: Color, thickness: f64, falloff: f64, opacity: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        sdf::draw_sdf_with_shadow(self, shape, color, thickness, falloff, opacity, shadow, blend.into())
+    }
+
+    /// Draws the shadow of a shape. Parts of the shadow outside of the target are clipped.
+    /// The coverage of the shape is rendered into a temporary mask, which is moved, blurred and composited onto the target.
+    /// To place the shadow underneath a shape, use [DrawTarget::draw_sdf_with_shadow()] or one of the other ```*_with_shadow``` methods.
+    /// # Arguments
+    /// * ```shape``` - The shape casting the shadow.
+    /// * ```thickness``` - The thickness of the outline of the shape, centered on its edge. If the thickness is 0, the shape is filled.
+    /// * ```shadow``` - The shadow to draw.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the shadow with the target.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the target does not match the color type of the shadow color.
+    /// * [Error::InvalidThickness] - If the thickness is NaN, infinite or negative.
+    /// * [Error::InvalidBlur] - If the blur radius is NaN, infinite or negative.
+    fn draw_shadow<B: Into<Blend>>(&mut self, shape: &SdfShape, thickness: f64, shadow: &Shadow, blend: B) -> Result<(), Error> {
+        sdf::draw_shadow(self, shape, thickness, shadow, blend.into())
+    }
+}
+
+impl DrawTarget for Image {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+
+    fn get_pixel(&self, index: (usize, usize)) -> Color {
+        self.get_unchecked(index)
+    }
+
+    fn set_pixel(&mut self, index: (usize, usize), color: Color) {
+        self.set_unchecked(index, color);
+    }
+
+    fn fill_span(&mut self, y: usize, x: Range<usize>, color: Color) {
+        self.set_unchecked((x, y), color);
+    }
+
+    fn blend_span(&mut self, y: usize, x: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+        self.set_composite_unchecked((x, y), color, opacity, blend);
+    }
+}
+
+/// Fills the rectangular area of the target with the color without performing checks.
+/// Opaque colors of the target color type drawn with plain source-over compositing are copied directly, everything else is blended.
+/// # Arguments
+/// * ```target``` - The target to draw onto.
+/// * ```x``` - The range of the x coordinates of the area.
+/// * ```y``` - The range of the y coordinates of the area.
+/// * ```color``` - The color to fill with.
+/// * ```opacity``` - The opacity of the color.
+/// * ```blend``` - The blend used to combine the color with the target.
+pub(crate) fn fill<T: DrawTarget + ?Sized>(target: &mut T, x: Range<usize>, y: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+    let opaque = blend.is_source_over() && opacity >= 1.0 && color.to_rgba_f64()[3] >= 1.0 && ColorType::from(color) == target.color_type();
+    for y in y {
+        if opaque {
+            target.fill_span(y, x.clone(), color);
+        } else {
+            target.blend_span(y, x.clone(), color, opacity, blend);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlendMode, CompositeOp};
+
+    /// A framebuffer with packed ```0xAARRGGBB``` pixels, stored from the top row.
+    struct Framebuffer {
+        width: usize,
+        height: usize,
+        pixels: Vec<u32>,
+    }
+    impl DrawTarget for Framebuffer {
+        fn width(&self) -> usize {
+            self.width
+        }
+        fn height(&self) -> usize {
+            self.height
+        }
+        fn color_type(&self) -> ColorType {
+            ColorType::RGBA8
+        }
+        fn get_pixel(&self, index: (usize, usize)) -> Color {
+            let [a, r, g, b] = self.pixels[(self.height - index.1 - 1) * self.width + index.0].to_be_bytes();
+            Color::RGBA8([r, g, b, a])
+        }
+        fn set_pixel(&mut self, index: (usize, usize), color: Color) {
+            let Color::RGBA8([r, g, b, a]) = color else { unreachable!() };
+            self.pixels[(self.height - index.1 - 1) * self.width + index.0] = u32::from_be_bytes([a, r, g, b]);
+        }
+    }
+
+    #[test]
+    fn test_custom_target() {
+        let background = Color::RGBA8([250, 240, 230, 255]);
+        let mut framebuffer = Framebuffer { width: 40, height: 30, pixels: vec![0xFF_FA_F0_E6; 40 * 30] };
+        let mut image = Image::new(40, 30, background);
+
+        // test errors
+        assert_eq!(framebuffer.draw_rectangle((0, 0), (5, 5), Color::RGB8([0, 0, 0]), 0, 1.0, Blend::default()), Err(Error::WrongColor));
+        assert_eq!(framebuffer.draw_circle((5, 5), 3, Color::RGBA8([0, 0, 0, 255]), 0, 2.0, Blend::default()), Err(Error::InvalidOpacity));
+
+        // the same drawing calls give the same result as on the image
+        fn draw<T: DrawTarget>(target: &mut T) {
+            target.draw_rectangle((2, 3), (20, 15), Color::RGBA8([200, 0, 0, 255]), 0, 1.0, CompositeOp::SourceOver).unwrap();
+            target.draw_rectangle((10, 10), (35, 25), Color::RGBA8([0, 0, 200, 255]), 2, 0.5, BlendMode::Multiply).unwrap();
+            target.draw_circle((25, 12), 8, Color::RGBA8([0, 200, 0, 128]), 0, 1.0, Blend::default()).unwrap();
+            target.draw_ellipse((15, 20), (12, 5), Color::RGBA8([20, 20, 20, 255]), 2, 1.0, BlendMode::Screen).unwrap();
+            let card = SdfShape::RoundedRectangle { point1: (5.0, 5.0), point2: (15.0, 12.0), radius: 2.0 };
+            let shadow = Shadow::new((2.0, -2.0), 3.0, Color::RGBA8([0, 0, 0, 100]));
+            target.draw_sdf_with_shadow(&card, Color::RGBA8([255, 255, 255, 255]), 0.0, 1.0, 1.0, &shadow, Blend::default()).unwrap();
+        }
+        draw(&mut framebuffer);
+        draw(&mut image);
+        for y in 0..30 {
+            for x in 0..40 {
+                assert_eq!(framebuffer.get_pixel((x, y)), image.get_unchecked((x, y)), "pixel ({}, {})", x, y);
+            }
+        }
+        assert_ne!(image.get_unchecked((3, 4)), background);
+    }
+
+    /// An image counting the spans drawn onto it.
+    struct SpanCounter {
+        image: Image,
+        spans: usize,
+    }
+    impl DrawTarget for SpanCounter {
+        fn width(&self) -> usize {
+            self.image.width
+        }
+        fn height(&self) -> usize {
+            self.image.height
+        }
+        fn color_type(&self) -> ColorType {
+            self.image.color_type
+        }
+        fn get_pixel(&self, index: (usize, usize)) -> Color {
+            self.image.get_unchecked(index)
+        }
+        fn set_pixel(&mut self, index: (usize, usize), color: Color) {
+            self.image.set_unchecked(index, color);
+        }
+        fn fill_span(&mut self, y: usize, x: Range<usize>, color: Color) {
+            self.spans += 1;
+            self.image.fill_span(y, x, color);
+        }
+        fn blend_span(&mut self, y: usize, x: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+            self.spans += 1;
+            self.image.blend_span(y, x, color, opacity, blend);
+        }
+    }
+
+    #[test]
+    fn test_draw_image_spans() {
+        // only the visible part of the source image is drawn, each row of equal pixels as one span
+        let mut sprite = Image::new(100, 10, Color::RGBA8([255, 0, 0, 128]));
+        sprite.set((60..100, 0..10), Color::RGBA8([0, 0, 255, 255])).unwrap();
+        let mut counter = SpanCounter { image: Image::new(20, 5, Color::RGB8([255, 255, 255])), spans: 0 };
+        let mut expected = counter.image.clone();
+        counter.draw_image(&sprite, (-50, -3), 1.0, Blend::default()).unwrap();
+        expected.draw_image(&sprite, (-50, -3), 1.0, Blend::default()).unwrap();
+        assert_eq!(counter.spans, 2 * 5);
+        assert_eq!(counter.image, expected);
+        assert_eq!(expected.get_unchecked((0, 0)), Color::RGB8([255, 127, 127]));
+        assert_eq!(expected.get_unchecked((15, 4)), Color::RGB8([0, 0, 255]));
+    }
+}
@@ -0,0 +1,256 @@
+//! Lossless geometric transforms (flips, rotations by multiples of 90 degrees and transposition) of the [Image].
+
+use crate::image::Image;
+
+impl Image {
+    /// Applies the transform to the image with packed pixels (less than 8 bits per pixel), moving the pixels one by one into new pixel data.
+    /// # Arguments
+    /// * ```size``` - The width and the height of the transformed image.
+    /// * ```transform``` - The function mapping the coordinates of a pixel to its coordinates in the transformed image.
+    /// # Returns
+    /// * ```true``` if the pixels are packed and the transform was applied, ```false``` otherwise.
+    fn transform_packed(&mut self, size: (usize, usize), transform: impl Fn((usize, usize)) -> (usize, usize)) -> bool {
+        if self.color_type.bits_per_pixel() >= 8 {
+            return false;
+        }
+        let mut image = Image {
+            data: vec![0; self.color_type.row_len(size.0) * size.1],
+            width: size.0,
+            height: size.1,
+            color_type: self.color_type,
+            background_color: None,
+            alpha_mode: self.alpha_mode,
+            palette: None,
+        };
+        let view = self.view();
+        let mut transformed = image.view_mut();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                transformed.set_unchecked(transform((x, y)), view.get_unchecked((x, y)));
+            }
+        }
+        self.data = image.data;
+        (self.width, self.height) = size;
+        true
+    }
+
+    /// Flips the image horizontally (mirrors it over its vertical axis), in place.
+    pub fn flip_horizontal(&mut self) {
+        if self.width == 0 || self.height == 0 {
+            return; // there are no rows to mirror
+        }
+        let (width, height) = (self.width, self.height);
+        if self.transform_packed((width, height), |(x, y)| (width - x - 1, y)) {
+            return;
+        }
+        let bytes_per_pixel = self.color_type.bytes_per_pixel();
+        for row in self.data.chunks_exact_mut(self.width * bytes_per_pixel) {
+            for x in 0..(self.width / 2) {
+                let (left, right) = row.split_at_mut((self.width - x - 1) * bytes_per_pixel);
+                left[x * bytes_per_pixel..(x + 1) * bytes_per_pixel].swap_with_slice(&mut right[..bytes_per_pixel]);
+            }
+        }
+    }
+
+    /// Flips the image vertically (mirrors it over its horizontal axis), in place.
+    pub fn flip_vertical(&mut self) {
+        let row_len = self.color_type.row_len(self.width);
+        for y in 0..(self.height / 2) {
+            let (top, bottom) = self.data.split_at_mut((self.height - y - 1) * row_len);
+            top[y * row_len..(y + 1) * row_len].swap_with_slice(&mut bottom[..row_len]);
+        }
+    }
+
+    /// Rotates the image by 180 degrees, in place.
+    pub fn rotate180(&mut self) {
+        let (width, height) = (self.width, self.height);
+        if self.transform_packed((width, height), |(x, y)| (width - x - 1, height - y - 1)) {
+            return;
+        }
+        let bytes_per_pixel = self.color_type.bytes_per_pixel();
+        let pixels = self.width * self.height;
+        for i in 0..(pixels / 2) {
+            let (first, second) = self.data.split_at_mut((pixels - i - 1) * bytes_per_pixel);
+            first[i * bytes_per_pixel..(i + 1) * bytes_per_pixel].swap_with_slice(&mut second[..bytes_per_pixel]);
+        }
+    }
+
+    /// Transposes the image, mirroring it over the diagonal from its top left to its bottom right corner.
+    /// The rows of the image become its columns, so the width and the height are swapped.
+    /// Square images are transposed in place.
+    pub fn transpose(&mut self) {
+        let (width, height) = (self.width, self.height);
+        // the top row becomes the left column
+        if self.transform_packed((height, width), |(x, y)| (height - y - 1, width - x - 1)) {
+            return;
+        }
+        let bytes_per_pixel = self.color_type.bytes_per_pixel();
+        if self.width == self.height {
+            let size = self.width;
+            for row in 0..size {
+                for column in (row + 1)..size {
+                    let (first, second) = self.data.split_at_mut((column * size + row) * bytes_per_pixel);
+                    let index = (row * size + column) * bytes_per_pixel;
+                    first[index..index + bytes_per_pixel].swap_with_slice(&mut second[..bytes_per_pixel]);
+                }
+            }
+        } else {
+            let mut data = vec![0; self.data.len()];
+            for (i, pixel) in self.data.chunks_exact(bytes_per_pixel).enumerate() {
+                let (row, column) = (i / self.width, i % self.width);
+                let index = (column * self.height + row) * bytes_per_pixel;
+                data[index..index + bytes_per_pixel].copy_from_slice(pixel);
+            }
+            self.data = data;
+            (self.width, self.height) = (self.height, self.width);
+        }
+    }
+
+    /// Rotates the image by 90 degrees clockwise. The width and the height are swapped.
+    /// Square images are rotated in place.
+    pub fn rotate90(&mut self) {
+        self.transpose();
+        self.flip_horizontal();
+    }
+
+    /// Rotates the image by 270 degrees clockwise (90 degrees counterclockwise). The width and the height are swapped.
+    /// Square images are rotated in place.
+    pub fn rotate270(&mut self) {
+        self.transpose();
+        self.flip_vertical();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::colors::{Color, ColorType};
+    use crate::image::Image;
+
+    /// Creates a 3x2 test image with a different color in every pixel.
+    fn test_image() -> Image {
+        let mut image = Image::new(3, 2, Color::RGB16([0, 0, 0]));
+        for y in 0..2 {
+            for x in 0..3 {
+                image.set((x, y), Color::RGB16([x as u16 * 1000 + 1, y as u16 * 1000 + 1, 7])).unwrap();
+            }
+        }
+        image
+    }
+
+    /// Checks that every pixel of the original image moved to the expected coordinates.
+    fn check(original: &Image, transformed: &Image, map: impl Fn(usize, usize) -> (usize, usize)) {
+        for y in 0..original.height() {
+            for x in 0..original.width() {
+                assert_eq!(transformed.get(map(x, y)).unwrap(), original.get((x, y)).unwrap(), "pixel ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_packed() {
+        let mut image = Image::from_bytes(3, 2, ColorType::GRAY1, &[0b1100_0000, 0b0010_0000]).unwrap();
+        image.flip_horizontal();
+        assert_eq!(image.as_bytes(), &[0b0110_0000, 0b1000_0000]);
+        image.flip_vertical();
+        assert_eq!(image.as_bytes(), &[0b1000_0000, 0b0110_0000]);
+        image.rotate90();
+        assert_eq!((image.width(), image.height()), (2, 3));
+        assert_eq!(image.as_bytes(), &[0b0100_0000, 0b1000_0000, 0b1000_0000]);
+
+        let original = Image::from_bytes(3, 2, ColorType::GRAY4, &[0x12, 0x30, 0x45, 0x60]).unwrap();
+        let mut image = original.clone();
+        image.rotate180();
+        assert_eq!(image.as_bytes(), &[0x65, 0x40, 0x32, 0x10]);
+        let mut image = original.clone();
+        image.transpose();
+        check(&original, &image, |x, y| (1 - y, 2 - x));
+        assert_eq!(image.as_bytes(), &[0x14, 0x25, 0x36]);
+    }
+
+    #[test]
+    fn test_flips() {
+        let original = test_image();
+        let mut image = original.clone();
+        image.flip_horizontal();
+        check(&original, &image, |x, y| (2 - x, y));
+        image.flip_horizontal();
+        assert_eq!(image, original);
+
+        image.flip_vertical();
+        check(&original, &image, |x, y| (x, 1 - y));
+        image.flip_vertical();
+        assert_eq!(image, original);
+
+        let mut odd = Image::from_bytes(3, 3, ColorType::GRAY8, &[1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        odd.flip_horizontal();
+        assert_eq!(odd.as_bytes(), &[3, 2, 1, 6, 5, 4, 9, 8, 7]);
+        odd.flip_vertical();
+        assert_eq!(odd.as_bytes(), &[9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let original = test_image();
+        let mut image = original.clone();
+        image.rotate180();
+        check(&original, &image, |x, y| (2 - x, 1 - y));
+        image.rotate180();
+        assert_eq!(image, original);
+
+        image.rotate90();
+        assert_eq!((image.width(), image.height()), (2, 3));
+        check(&original, &image, |x, y| (y, 2 - x));
+        image.rotate270();
+        assert_eq!(image, original);
+
+        image.rotate270();
+        check(&original, &image, |x, y| (1 - y, x));
+        for _ in 0..3 {
+            image.rotate270();
+        }
+        assert_eq!(image, original);
+
+        // square images are rotated in place
+        let mut square = Image::from_bytes(2, 2, ColorType::GRAYA8, &[1, 10, 2, 20, 3, 30, 4, 40]).unwrap();
+        square.rotate90();
+        assert_eq!(square.as_bytes(), &[3, 30, 1, 10, 4, 40, 2, 20]);
+        square.rotate270();
+        square.rotate270();
+        assert_eq!(square.as_bytes(), &[2, 20, 4, 40, 1, 10, 3, 30]);
+    }
+
+    #[test]
+    fn test_transpose() {
+        let original = test_image();
+        let mut image = original.clone();
+        image.transpose();
+        assert_eq!((image.width(), image.height()), (2, 3));
+        check(&original, &image, |x, y| (1 - y, 2 - x));
+        image.transpose();
+        assert_eq!(image, original);
+
+        let mut square = Image::from_bytes(3, 3, ColorType::GRAY8, &[1, 2, 3, 4, 5, 6, 7, 8, 9]).unwrap();
+        square.transpose();
+        assert_eq!(square.as_bytes(), &[1, 4, 7, 2, 5, 8, 3, 6, 9]);
+    }
+
+    #[test]
+    fn test_zero_size() {
+        for (width, height) in [(0, 3), (3, 0), (0, 0)] {
+            for color in [Color::RGB8([1, 2, 3]), Color::GRAY1(1)] {
+                let original = Image::new(width, height, color);
+                let mut image = original.clone();
+                image.flip_horizontal();
+                image.flip_vertical();
+                image.rotate180();
+                assert_eq!(image, original);
+                image.rotate90();
+                assert_eq!((image.width(), image.height(), image.as_bytes().len()), (height, width, 0));
+                image.rotate270();
+                image.transpose();
+                image.transpose();
+                assert_eq!(image, original);
+            }
+        }
+    }
+}
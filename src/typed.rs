@@ -0,0 +1,753 @@
+//! A module containing the statically typed [TypedImage] and its [Pixel] types.
+//!
+//! The color type of a [TypedImage] is part of its type, so functions working with it are compiled for each pixel type
+//! and passing a pixel of the wrong type is a compile error instead of [Error::WrongColor].
+//! Filling and blending work on the channels of the pixel type, without the dynamic [Color] of every pixel.
+//! Typed images convert to and from the dynamic [Image] without any loss.
+
+use crate::blending::{linear_to_srgb, linear_to_srgb_u8, premultiply, srgb_to_linear, srgb_u8_to_linear, unpremultiply, Blend, BlendSpace};
+use crate::colors::{AlphaMode, Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use crate::target::DrawTarget;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Range;
+
+/// Trait for the pixel types of the [TypedImage].
+/// There is one pixel type for each gray and RGB [ColorType] with 8 or 16 bits per channel, in all channel orders.
+/// Floating point, packed and indexed color types have no pixel type, their images stay dynamic.
+pub trait Pixel: Copy + Debug + Eq + Hash {
+    /// The color type of the pixel.
+    const COLOR_TYPE: ColorType;
+
+    /// Converts the pixel to the dynamic color.
+    /// # Returns
+    /// * The color with the same value.
+    fn to_color(self) -> Color;
+
+    /// Converts the dynamic color to the pixel.
+    /// # Arguments
+    /// * ```color``` - The color to convert.
+    /// # Returns
+    /// * The pixel with the same value, [None] if the color is of other type.
+    fn from_color(color: Color) -> Option<Self>;
+
+    /// Returns the normalized ```[red, green, blue, alpha]``` channels of the pixel in the blend space.
+    /// Gray pixels are expanded to equal red, green and blue channels, pixels without an alpha channel are fully opaque.
+    /// # Arguments
+    /// * ```space``` - The space of the channels, the alpha channel is never decoded.
+    /// # Returns
+    /// * The normalized channels.
+    fn to_rgba_f64(self, space: BlendSpace) -> [f64; 4];
+
+    /// Creates the pixel from the normalized ```[red, green, blue, alpha]``` channels in the blend space.
+    /// Values outside of range ```[0.0, 1.0]``` are clamped, gray pixels take the average of the red, green and blue channels
+    /// and the alpha channel is dropped for pixels without one.
+    /// # Arguments
+    /// * ```rgba``` - The normalized channels.
+    /// * ```space``` - The space of the channels, the alpha channel is never encoded.
+    /// # Returns
+    /// * The new pixel.
+    fn from_rgba_f64(rgba: [f64; 4], space: BlendSpace) -> Self;
+}
+
+/// Trait for the values of the channels of the pixel types.
+trait Channel: Copy {
+    /// Converts the value to the normalized value.
+    fn to_f64(self) -> f64;
+
+    /// Converts the normalized value to the value, values outside of range ```[0.0, 1.0]``` are clamped.
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts the sRGB-encoded value to the normalized linear light value.
+    fn to_linear(self) -> f64;
+
+    /// Converts the normalized linear light value to the sRGB-encoded value.
+    fn from_linear(value: f64) -> Self;
+
+    /// Converts the value to the normalized value in the blend space.
+    #[inline]
+    fn decode(self, space: BlendSpace) -> f64 {
+        match space {
+            BlendSpace::Srgb => self.to_f64(),
+            BlendSpace::Linear => self.to_linear(),
+        }
+    }
+
+    /// Converts the normalized value in the blend space to the value.
+    #[inline]
+    fn encode(value: f64, space: BlendSpace) -> Self {
+        match space {
+            BlendSpace::Srgb => Self::from_f64(value),
+            BlendSpace::Linear => Self::from_linear(value),
+        }
+    }
+
+    /// Converts the normalized red, green and blue values in the blend space to the gray value, their average.
+    #[inline]
+    fn encode_gray(rgb: [f64; 3], space: BlendSpace) -> Self {
+        Self::encode((rgb[0] + rgb[1] + rgb[2]) / 3.0, space)
+    }
+}
+impl Channel for u8 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64 / 255.0
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    }
+
+    #[inline]
+    fn to_linear(self) -> f64 {
+        srgb_u8_to_linear(self)
+    }
+
+    #[inline]
+    fn from_linear(value: f64) -> Self {
+        linear_to_srgb_u8(value)
+    }
+}
+impl Channel for u16 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64 / 65535.0
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        (value.clamp(0.0, 1.0) * 65535.0).round() as u16
+    }
+
+    #[inline]
+    fn to_linear(self) -> f64 {
+        srgb_to_linear(self.to_f64())
+    }
+
+    #[inline]
+    fn from_linear(value: f64) -> Self {
+        Self::from_f64(linear_to_srgb(value.clamp(0.0, 1.0)))
+    }
+
+    #[inline]
+    fn encode_gray(rgb: [f64; 3], space: BlendSpace) -> Self {
+        // the channels are encoded before they are averaged, as for the dynamic 16-bit colors
+        match space {
+            BlendSpace::Srgb => Self::from_f64((rgb[0] + rgb[1] + rgb[2]) / 3.0),
+            BlendSpace::Linear => Self::from_f64(rgb.map(|value| linear_to_srgb(value.clamp(0.0, 1.0))).iter().sum::<f64>() / 3.0),
+        }
+    }
+}
+
+/// Defines the conversions of the pixel type to and from the normalized channels.
+/// The layout is ```gray```, ```gray_alpha``` or the positions of the red, green, blue (and alpha) channels in the value.
+macro_rules! channels {
+    (gray) => {
+        #[inline]
+        fn to_rgba_f64(self, space: BlendSpace) -> [f64; 4] {
+            let gray = self.0.decode(space);
+            [gray, gray, gray, 1.0]
+        }
+
+        #[inline]
+        fn from_rgba_f64(rgba: [f64; 4], space: BlendSpace) -> Self {
+            Self(Channel::encode_gray([rgba[0], rgba[1], rgba[2]], space))
+        }
+    };
+    (gray_alpha) => {
+        #[inline]
+        fn to_rgba_f64(self, space: BlendSpace) -> [f64; 4] {
+            let gray = self.0[0].decode(space);
+            [gray, gray, gray, self.0[1].to_f64()]
+        }
+
+        #[inline]
+        fn from_rgba_f64(rgba: [f64; 4], space: BlendSpace) -> Self {
+            Self([Channel::encode_gray([rgba[0], rgba[1], rgba[2]], space), Channel::from_f64(rgba[3])])
+        }
+    };
+    ([$red:literal, $green:literal, $blue:literal]) => {
+        #[inline]
+        fn to_rgba_f64(self, space: BlendSpace) -> [f64; 4] {
+            [self.0[$red].decode(space), self.0[$green].decode(space), self.0[$blue].decode(space), 1.0]
+        }
+
+        #[inline]
+        fn from_rgba_f64(rgba: [f64; 4], space: BlendSpace) -> Self {
+            let mut value = Self::default().0;
+            value[$red] = Channel::encode(rgba[0], space);
+            value[$green] = Channel::encode(rgba[1], space);
+            value[$blue] = Channel::encode(rgba[2], space);
+            Self(value)
+        }
+    };
+    ([$red:literal, $green:literal, $blue:literal, $alpha:literal]) => {
+        #[inline]
+        fn to_rgba_f64(self, space: BlendSpace) -> [f64; 4] {
+            [self.0[$red].decode(space), self.0[$green].decode(space), self.0[$blue].decode(space), self.0[$alpha].to_f64()]
+        }
+
+        #[inline]
+        fn from_rgba_f64(rgba: [f64; 4], space: BlendSpace) -> Self {
+            let mut value = Self::default().0;
+            value[$red] = Channel::encode(rgba[0], space);
+            value[$green] = Channel::encode(rgba[1], space);
+            value[$blue] = Channel::encode(rgba[2], space);
+            value[$alpha] = Channel::from_f64(rgba[3]);
+            Self(value)
+        }
+    };
+}
+
+/// Defines the pixel type wrapping the value of the color variant.
+macro_rules! pixel {
+    ($(#[$meta:meta])* $name:ident($value:ty), $color:ident, $layout:tt) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+        pub struct $name(pub $value);
+        impl Pixel for $name {
+            const COLOR_TYPE: ColorType = ColorType::$color;
+
+            #[inline]
+            fn to_color(self) -> Color {
+                Color::$color(self.0)
+            }
+
+            #[inline]
+            fn from_color(color: Color) -> Option<Self> {
+                match color {
+                    Color::$color(value) => Some(Self(value)),
+                    _ => None,
+                }
+            }
+
+            channels!($layout);
+        }
+    };
+}
+
+pixel!(
+    /// The 8-bit grayscale pixel
+    Luma8(u8),
+    GRAY8,
+    gray
+);
+pixel!(
+    /// The 8-bit grayscale pixel with alpha, ```[gray, alpha]```
+    LumaA8([u8; 2]),
+    GRAYA8,
+    gray_alpha
+);
+pixel!(
+    /// The 16-bit grayscale pixel
+    Luma16(u16),
+    GRAY16,
+    gray
+);
+pixel!(
+    /// The 16-bit grayscale pixel with alpha, ```[gray, alpha]```
+    LumaA16([u16; 2]),
+    GRAYA16,
+    gray_alpha
+);
+pixel!(
+    /// The 8-bit RGB pixel
+    Rgb8([u8; 3]),
+    RGB8,
+    [0, 1, 2]
+);
+pixel!(
+    /// The 8-bit RGBA pixel
+    Rgba8([u8; 4]),
+    RGBA8,
+    [0, 1, 2, 3]
+);
+pixel!(
+    /// The 16-bit RGB pixel
+    Rgb16([u16; 3]),
+    RGB16,
+    [0, 1, 2]
+);
+pixel!(
+    /// The 16-bit RGBA pixel
+    Rgba16([u16; 4]),
+    RGBA16,
+    [0, 1, 2, 3]
+);
+pixel!(
+    /// The 8-bit RGB pixel in the blue, green, red order
+    Bgr8([u8; 3]),
+    BGR8,
+    [2, 1, 0]
+);
+pixel!(
+    /// The 8-bit RGBA pixel in the blue, green, red, alpha order
+    Bgra8([u8; 4]),
+    BGRA8,
+    [2, 1, 0, 3]
+);
+pixel!(
+    /// The 8-bit RGBA pixel in the alpha, red, green, blue order
+    Argb8([u8; 4]),
+    ARGB8,
+    [1, 2, 3, 0]
+);
+pixel!(
+    /// The 16-bit RGB pixel in the blue, green, red order
+    Bgr16([u16; 3]),
+    BGR16,
+    [2, 1, 0]
+);
+pixel!(
+    /// The 16-bit RGBA pixel in the blue, green, red, alpha order
+    Bgra16([u16; 4]),
+    BGRA16,
+    [2, 1, 0, 3]
+);
+pixel!(
+    /// The 16-bit RGBA pixel in the alpha, red, green, blue order
+    Argb16([u16; 4]),
+    ARGB16,
+    [1, 2, 3, 0]
+);
+
+/// A struct that holds an image with the pixel type known at compile time
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct TypedImage<P: Pixel> {
+    /// The pixels of the image, stored row by row from the top as in [Image]
+    data: Vec<P>,
+    /// The width of the image
+    width: usize,
+    /// The height of the image
+    height: usize,
+    /// The background color of the image, None if not set
+    background_color: Option<P>,
+    /// The way the alpha channel is stored in the pixels
+    alpha_mode: AlphaMode,
+}
+impl<P: Pixel> TypedImage<P> {
+    /// Creates a new image with the given width, height, and filled with the background color.
+    /// # Arguments
+    /// * ```width``` - The width of the image.
+    /// * ```height``` - The height of the image.
+    /// * ```background_color``` - The background color of the image.
+    /// # Returns
+    /// * The new image.
+    pub fn new(width: usize, height: usize, background_color: P) -> Self {
+        Self {
+            data: vec![background_color; width * height],
+            width,
+            height,
+            background_color: Some(background_color),
+            alpha_mode: AlphaMode::Straight,
+        }
+    }
+
+    /// Returns the width of the image.
+    /// # Returns
+    /// * The width of the image.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image.
+    /// # Returns
+    /// * The height of the image.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the alpha mode of the image.
+    /// # Returns
+    /// * The way the alpha channel is stored in the pixels.
+    #[inline]
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    /// Returns the background color of the image.
+    /// # Returns
+    /// * The background color of the image.
+    #[inline]
+    pub fn background_color(&self) -> Option<P> {
+        self.background_color
+    }
+
+    /// Returns the pixels of the image as stored, row by row from the top.
+    /// # Returns
+    /// * The slice of the pixels.
+    #[inline]
+    pub fn as_slice(&self) -> &[P] {
+        &self.data
+    }
+
+    /// Returns the pixels of the image as stored, row by row from the top, as mutable.
+    /// # Returns
+    /// * The mutable slice of the pixels.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [P] {
+        &mut self.data
+    }
+
+    /// Returns the position of the pixel at the given coordinates in the pixel slice without performing checks.
+    #[inline]
+    fn position(&self, index: (usize, usize)) -> usize {
+        (self.height - index.1 - 1) * self.width + index.0
+    }
+
+    /// Converts the pixel with straight alpha to the pixel as stored.
+    #[inline]
+    fn encode(&self, pixel: P) -> P {
+        match self.alpha_mode {
+            AlphaMode::Straight => pixel,
+            AlphaMode::Premultiplied if P::COLOR_TYPE.has_alpha() => P::from_rgba_f64(premultiply(pixel.to_rgba_f64(BlendSpace::Srgb)), BlendSpace::Srgb),
+            AlphaMode::Premultiplied => pixel,
+        }
+    }
+
+    /// Converts the pixel as stored to the pixel with straight alpha.
+    #[inline]
+    fn decode(&self, pixel: P) -> P {
+        match self.alpha_mode {
+            AlphaMode::Straight => pixel,
+            AlphaMode::Premultiplied if P::COLOR_TYPE.has_alpha() => P::from_rgba_f64(unpremultiply(pixel.to_rgba_f64(BlendSpace::Srgb)), BlendSpace::Srgb),
+            AlphaMode::Premultiplied => pixel,
+        }
+    }
+
+    /// Returns the value of the pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * [Result] which holds the value of the pixel with straight alpha or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: (usize, usize)) -> Result<P, Error> {
+        if index.0 >= self.width || index.1 >= self.height {
+            Err(Error::IndexOutOfBounds)
+        } else {
+            Ok(self.get_unchecked(index))
+        }
+    }
+
+    /// Returns the value of the pixel at the given coordinates without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * The value of the pixel with straight alpha.
+    #[inline]
+    pub fn get_unchecked(&self, index: (usize, usize)) -> P {
+        self.decode(self.data[self.position(index)])
+    }
+
+    /// Sets the value of the pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```pixel``` - The value with straight alpha.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    #[inline]
+    pub fn set(&mut self, index: (usize, usize), pixel: P) -> Result<(), Error> {
+        if index.0 >= self.width || index.1 >= self.height {
+            Err(Error::IndexOutOfBounds)
+        } else {
+            self.set_unchecked(index, pixel);
+            Ok(())
+        }
+    }
+
+    /// Sets the value of the pixel at the given coordinates without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```pixel``` - The value with straight alpha.
+    #[inline]
+    pub fn set_unchecked(&mut self, index: (usize, usize), pixel: P) {
+        let position = self.position(index);
+        self.data[position] = self.encode(pixel);
+    }
+
+    /// Sets the value of the pixel at the given coordinates by placing the pixel over the current value (source-over compositing).
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```pixel``` - The value with straight alpha.
+    /// * ```opacity``` - The opacity for blending. It multiplies the alpha channel of the pixel.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    #[inline]
+    pub fn set_transparent(&mut self, index: (usize, usize), pixel: P, opacity: f64) -> Result<(), Error> {
+        self.set_composite(index, pixel, opacity, Blend::default())
+    }
+
+    /// Sets the value of the pixel at the given coordinates by placing the pixel over the current value (source-over compositing) without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```pixel``` - The value with straight alpha.
+    /// * ```opacity``` - The opacity for blending. It multiplies the alpha channel of the pixel.
+    #[inline]
+    pub fn set_transparent_unchecked(&mut self, index: (usize, usize), pixel: P, opacity: f64) {
+        self.set_composite_unchecked(index, pixel, opacity, Blend::default());
+    }
+
+    /// Sets the value of the pixel at the given coordinates by blending and compositing the pixel with the current value.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```pixel``` - The value with straight alpha.
+    /// * ```opacity``` - The opacity of the pixel. It multiplies the alpha channel of the pixel.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the pixel with the current value.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    #[inline]
+    pub fn set_composite<B: Into<Blend>>(&mut self, index: (usize, usize), pixel: P, opacity: f64, blend: B) -> Result<(), Error> {
+        if index.0 >= self.width || index.1 >= self.height {
+            return Err(Error::IndexOutOfBounds);
+        }
+        if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
+            return Err(Error::InvalidOpacity);
+        }
+        self.set_composite_unchecked(index, pixel, opacity, blend);
+        Ok(())
+    }
+
+    /// Sets the value of the pixel at the given coordinates by blending and compositing the pixel with the current value without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```pixel``` - The value with straight alpha.
+    /// * ```opacity``` - The opacity of the pixel. It multiplies the alpha channel of the pixel.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the pixel with the current value.
+    #[inline]
+    pub fn set_composite_unchecked<B: Into<Blend>>(&mut self, index: (usize, usize), pixel: P, opacity: f64, blend: B) {
+        let blend = blend.into();
+        let mut source = pixel.to_rgba_f64(blend.space);
+        source[3] *= opacity;
+        let position = self.position(index);
+        self.data[position] = blend_pixel(source, self.data[position], self.alpha_mode, &blend);
+    }
+}
+
+/// Blends the color with the pixel as stored, as [Blend] blends dynamic colors, on the channels of the pixel type.
+/// # Arguments
+/// * ```source``` - The normalized ```[red, green, blue, alpha]``` source color with straight alpha in the space of the blend.
+/// * ```destination``` - The pixel as stored in the image.
+/// * ```alpha_mode``` - The alpha mode of the image.
+/// * ```blend``` - The blend used to combine the color with the pixel.
+/// # Returns
+/// * The resulting pixel to be stored in the image.
+#[inline]
+fn blend_pixel<P: Pixel>(source: [f64; 4], destination: P, alpha_mode: AlphaMode, blend: &Blend) -> P {
+    let premultiplied = alpha_mode == AlphaMode::Premultiplied && P::COLOR_TYPE.has_alpha();
+    if premultiplied && blend.space == BlendSpace::Srgb {
+        return P::from_rgba_f64(blend.blend_premultiplied(source, destination.to_rgba_f64(BlendSpace::Srgb)), BlendSpace::Srgb);
+    }
+
+    let destination = if premultiplied {
+        P::from_rgba_f64(unpremultiply(destination.to_rgba_f64(BlendSpace::Srgb)), BlendSpace::Srgb)
+    } else {
+        destination
+    };
+    let mut result = blend.blend(source, destination.to_rgba_f64(blend.space));
+    if !P::COLOR_TYPE.has_alpha() {
+        // flatten the result onto black, as the image can't store transparency
+        let alpha = result[3];
+        for channel in &mut result[..3] {
+            *channel *= alpha;
+        }
+    }
+    let result = P::from_rgba_f64(result, blend.space);
+    if premultiplied {
+        P::from_rgba_f64(premultiply(result.to_rgba_f64(BlendSpace::Srgb)), BlendSpace::Srgb)
+    } else {
+        result
+    }
+}
+
+impl<P: Pixel> From<TypedImage<P>> for Image {
+    fn from(image: TypedImage<P>) -> Self {
+        let mut data = Vec::with_capacity(image.data.len() * P::COLOR_TYPE.bytes_per_pixel());
+        for pixel in image.data {
+            data.extend_from_slice(pixel.to_color().as_bytes());
+        }
+        Image {
+            data,
+            width: image.width,
+            height: image.height,
+            color_type: P::COLOR_TYPE,
+            background_color: image.background_color.map(P::to_color),
+            alpha_mode: image.alpha_mode,
+            palette: None,
+        }
+    }
+}
+
+impl<P: Pixel> TryFrom<Image> for TypedImage<P> {
+    type Error = Error;
+
+    /// Converts the dynamic image to the typed image.
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the image is not the color type of the pixel.
+    fn try_from(image: Image) -> Result<Self, Self::Error> {
+        if image.color_type != P::COLOR_TYPE {
+            return Err(Error::WrongColor);
+        }
+        let data = image
+            .data
+            .chunks_exact(P::COLOR_TYPE.bytes_per_pixel())
+            .map(|bytes| P::from_color(Color::from_bytes(P::COLOR_TYPE, bytes)).unwrap())
+            .collect();
+        Ok(Self {
+            data,
+            width: image.width,
+            height: image.height,
+            background_color: image.background_color.and_then(P::from_color),
+            alpha_mode: image.alpha_mode,
+        })
+    }
+}
+
+impl<P: Pixel> DrawTarget for TypedImage<P> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn color_type(&self) -> ColorType {
+        P::COLOR_TYPE
+    }
+
+    fn get_pixel(&self, index: (usize, usize)) -> Color {
+        self.get_unchecked(index).to_color()
+    }
+
+    fn set_pixel(&mut self, index: (usize, usize), color: Color) {
+        // the trait guarantees the color type of the target
+        self.set_unchecked(index, P::from_color(color).unwrap());
+    }
+
+    fn fill_span(&mut self, y: usize, x: Range<usize>, color: Color) {
+        let pixel = self.encode(P::from_color(color).unwrap());
+        let start = self.position((0, y));
+        self.data[start + x.start..start + x.end].fill(pixel);
+    }
+
+    fn blend_span(&mut self, y: usize, x: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+        // the color is decoded once, the pixels are blended on their channels
+        let mut source = blend.space.decode(color);
+        source[3] *= opacity;
+        let start = self.position((0, y));
+        for pixel in &mut self.data[start + x.start..start + x.end] {
+            *pixel = blend_pixel(source, *pixel, self.alpha_mode, &blend);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blending::{BlendMode, CompositeOp};
+
+    #[test]
+    fn test_pixel() {
+        assert_eq!(Rgb8::COLOR_TYPE, ColorType::RGB8);
+        assert_eq!(LumaA16::COLOR_TYPE, ColorType::GRAYA16);
+        assert_eq!(Rgba16([1, 2, 3, 4]).to_color(), Color::RGBA16([1, 2, 3, 4]));
+        assert_eq!(Luma8::from_color(Color::GRAY8(7)), Some(Luma8(7)));
+        assert_eq!(Luma8::from_color(Color::GRAY16(7)), None);
+    }
+
+    #[test]
+    fn test_typed_image() {
+        let mut image = TypedImage::new(3, 2, Rgb16([1, 2, 3]));
+        assert_eq!((image.width(), image.height(), image.alpha_mode()), (3, 2, AlphaMode::Straight));
+        assert_eq!(image.background_color(), Some(Rgb16([1, 2, 3])));
+        image.set((2, 0), Rgb16([40_000, 0, 0])).unwrap();
+        assert_eq!(image.get((2, 0)), Ok(Rgb16([40_000, 0, 0])));
+        assert_eq!(image.as_slice()[5], Rgb16([40_000, 0, 0]));
+        assert_eq!(image.get((3, 0)), Err(Error::IndexOutOfBounds));
+        assert_eq!(image.set((0, 2), Rgb16([0, 0, 0])), Err(Error::IndexOutOfBounds));
+        image.as_mut_slice()[0] = Rgb16([9, 9, 9]);
+        assert_eq!(image.get((0, 1)), Ok(Rgb16([9, 9, 9])));
+    }
+
+    #[test]
+    fn test_conversion() {
+        let mut image = Image::from_fn(3, 2, ColorType::RGBA8, |x, y| Color::RGBA8([x as u8 * 50, y as u8 * 50, 0, 128])).unwrap();
+        image.set_alpha_mode(AlphaMode::Premultiplied);
+        assert_eq!(TypedImage::<Rgb8>::try_from(image.clone()), Err(Error::WrongColor));
+
+        let typed = TypedImage::<Rgba8>::try_from(image.clone()).unwrap();
+        assert_eq!(typed.alpha_mode(), AlphaMode::Premultiplied);
+        assert_eq!(typed.get((2, 1)).unwrap().to_color(), image.get((2, 1)).unwrap());
+        assert_eq!(typed.as_slice()[2], Rgba8([50, 25, 0, 128]));
+        assert_eq!(Image::from(typed), image);
+
+        let typed = TypedImage::new(2, 2, Luma16(60_000));
+        let image = Image::from(typed.clone());
+        assert_eq!(image, Image::new(2, 2, Color::GRAY16(60_000)));
+        assert_eq!(TypedImage::try_from(image), Ok(typed));
+    }
+
+    #[test]
+    fn test_typed_drawing() {
+        let mut typed = TypedImage::new(30, 20, Rgba8([250, 240, 230, 255]));
+        let mut image = Image::new(30, 20, Color::RGBA8([250, 240, 230, 255]));
+        fn draw<T: DrawTarget>(target: &mut T) {
+            target.draw_rectangle((2, 3), (20, 15), Color::RGBA8([200, 0, 0, 255]), 0, 1.0, Blend::default()).unwrap();
+            target.draw_circle((15, 10), 6, Color::RGBA8([0, 200, 0, 128]), 0, 1.0, BlendMode::Multiply).unwrap();
+        }
+        draw(&mut typed);
+        draw(&mut image);
+        assert_eq!(Image::from(typed), image);
+    }
+
+    #[test]
+    fn test_typed_blending() {
+        // the channels of the pixel types give the same results as the dynamic colors
+        fn check<P: Pixel>(background: P, pixel: P) {
+            for space in [BlendSpace::Srgb, BlendSpace::Linear] {
+                assert_eq!(P::from_rgba_f64(pixel.to_rgba_f64(space), space), pixel);
+                assert_eq!(pixel.to_rgba_f64(space), space.decode(pixel.to_color()));
+                for alpha_mode in [AlphaMode::Straight, AlphaMode::Premultiplied] {
+                    for blend in [Blend::default(), BlendMode::Multiply.into(), Blend::from(CompositeOp::Xor).in_space(space)] {
+                        let mut image = Image::new(3, 1, background.to_color());
+                        image.set_alpha_mode(alpha_mode);
+                        let mut typed = TypedImage::<P>::try_from(image.clone()).unwrap();
+                        typed.set_composite((0, 0), pixel, 0.5, blend).unwrap();
+                        image.set_composite((0, 0), pixel.to_color(), 0.5, blend).unwrap();
+                        typed.set_transparent((1, 0), pixel, 1.0).unwrap();
+                        image.set_transparent((1, 0), pixel.to_color(), 1.0).unwrap();
+                        typed.blend_span(0, 2..3, Color::RGBA8([10, 200, 30, 100]), 0.8, blend);
+                        image.blend_span(0, 2..3, Color::RGBA8([10, 200, 30, 100]), 0.8, blend);
+                        assert_eq!(Image::from(typed), image, "{:?} {:?} {:?}", pixel, alpha_mode, blend);
+                    }
+                }
+            }
+        }
+        check(Luma8(200), Luma8(13));
+        check(LumaA16([50_000, 30_000]), LumaA16([1_000, 40_000]));
+        check(Rgb16([1, 30_000, 65_535]), Rgb16([60_000, 2, 500]));
+        check(Bgra8([1, 2, 250, 128]), Bgra8([90, 180, 30, 200]));
+        check(Argb16([40_000, 1, 2, 60_000]), Argb16([65_535, 30_000, 200, 9]));
+
+        let mut image = TypedImage::new(2, 2, Rgb8([0, 0, 0]));
+        assert_eq!(image.set_composite((2, 0), Rgb8([1, 2, 3]), 1.0, Blend::default()), Err(Error::IndexOutOfBounds));
+        assert_eq!(image.set_transparent((0, 0), Rgb8([1, 2, 3]), f64::NAN), Err(Error::InvalidOpacity));
+        image.set_transparent_unchecked((0, 0), Rgb8([100, 200, 50]), 0.5);
+        assert_eq!(image.get((0, 0)), Ok(Rgb8([50, 100, 25])));
+    }
+}
@@ -0,0 +1,1011 @@
+//! A module containing borrowed views over pixel data owned by someone else.
+//!
+//! Views don't copy the pixel data, so they can be used to read and draw onto buffers of video frames, windows or other libraries.
+//! Rows of a view may be padded, the distance between the starts of two rows in bytes is the ```stride```.
+//! Packed pixels with less than 8 bits are stored from the most significant bit as in [Image], views of their regions can start inside of a byte.
+//! As in [Image], the first row in memory is the top row of the image and coordinates origin is in the bottom left corner.
+
+use crate::blending::Blend;
+use crate::colors::{AlphaMode, Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use crate::indexing::ImageSetIndex;
+use crate::palette::Palette;
+use crate::target::DrawTarget;
+use std::ops::Range;
+
+/// A struct that holds a read-only view over borrowed pixel data
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ImageView<'a> {
+    /// The borrowed pixel data
+    data: &'a [u8],
+    /// The width of the view
+    width: usize,
+    /// The height of the view
+    height: usize,
+    /// The number of bytes between the starts of two rows
+    stride: usize,
+    /// The number of bits before the first pixel of the rows, for packed pixels starting inside of a byte
+    offset: usize,
+    /// The color type of the view
+    color_type: ColorType,
+    /// The way the alpha channel is stored in the pixel data
+    alpha_mode: AlphaMode,
+    /// The palette of the indexed pixel data, None if not set
+    palette: Option<&'a Palette>,
+}
+
+/// A struct that holds a mutable view over borrowed pixel data
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub struct ImageViewMut<'a> {
+    /// The borrowed pixel data
+    data: &'a mut [u8],
+    /// The width of the view
+    width: usize,
+    /// The height of the view
+    height: usize,
+    /// The number of bytes between the starts of two rows
+    stride: usize,
+    /// The number of bits before the first pixel of the rows, for packed pixels starting inside of a byte
+    offset: usize,
+    /// The color type of the view
+    color_type: ColorType,
+    /// The way the alpha channel is stored in the pixel data
+    alpha_mode: AlphaMode,
+    /// The palette of the indexed pixel data, None if not set
+    palette: Option<&'a Palette>,
+}
+
+/// Checks if the data can hold the pixels of the view.
+/// # Arguments
+/// * ```len``` - The length of the data.
+/// * ```width``` - The width of the view.
+/// * ```height``` - The height of the view.
+/// * ```stride``` - The number of bytes between the starts of two rows.
+/// * ```color_type``` - The color type of the view.
+/// # Returns
+/// * [Result] which holds [Ok] or [Err] with [Error].
+fn check_layout(len: usize, width: usize, height: usize, stride: usize, color_type: ColorType) -> Result<(), Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidSize);
+    }
+    // rows too long to be addressed can't fit into the data
+    let row_len = width.checked_mul(color_type.bits_per_pixel()).ok_or(Error::InvalidSize)?.div_ceil(8);
+    if stride < row_len {
+        return Err(Error::InvalidStride);
+    }
+    match (height - 1).checked_mul(stride).and_then(|start| start.checked_add(row_len)) {
+        Some(needed) if needed <= len => Ok(()),
+        _ => Err(Error::InvalidSize),
+    }
+}
+
+/// Checks if the pixels selected by the index are inside of the view.
+/// # Arguments
+/// * ```index``` - The tuple with the coordinates ```(x, y)```.
+/// * ```width``` - The width of the view.
+/// * ```height``` - The height of the view.
+/// # Returns
+/// * [Result] which holds [Ok] or [Err] with [Error].
+/// # Errors
+/// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+fn check_index<RX: ImageSetIndex, RY: ImageSetIndex>(index: &(RX, RY), width: usize, height: usize) -> Result<(), Error> {
+    let x = index.0.start()..index.0.end().unwrap_or(width);
+    let y = index.1.start()..index.1.end().unwrap_or(height);
+    if x.start >= width || y.start >= height || x.end > width || y.end > height {
+        return Err(Error::IndexOutOfBounds);
+    }
+    Ok(())
+}
+
+/// Returns the range of the bytes of the rectangular region of the view.
+/// # Arguments
+/// * ```height``` - The height of the view.
+/// * ```stride``` - The number of bytes between the starts of two rows of the view.
+/// * ```offset``` - The number of bits before the first pixel of the rows of the view.
+/// * ```bits_per_pixel``` - The number of bits per pixel.
+/// * ```point``` - The coordinates of the bottom left corner of the region.
+/// * ```size``` - The width and the height of the region.
+/// # Returns
+/// * The range of the bytes, starting with the byte holding the first pixel of the top row of the region,
+///   and the number of bits before the first pixel of the rows of the region.
+fn region_range(height: usize, stride: usize, offset: usize, bits_per_pixel: usize, point: (usize, usize), size: (usize, usize)) -> (Range<usize>, usize) {
+    let first_bit = offset + point.0 * bits_per_pixel;
+    let start = (height - point.1 - size.1) * stride + first_bit / 8;
+    let row_len = (first_bit % 8 + size.0 * bits_per_pixel).div_ceil(8);
+    (start..(start + (size.1 - 1) * stride + row_len), first_bit % 8)
+}
+
+/// Returns the position of the bits of the packed pixel in its byte.
+/// # Arguments
+/// * ```color_type``` - The color type with less than 8 bits per pixel.
+/// * ```first_bit``` - The position of the first bit of the pixel from the start of its row.
+/// # Returns
+/// * The shift of the bits of the pixel from the least significant bit and the mask of the bits.
+#[inline]
+fn packed_bits(color_type: ColorType, first_bit: usize) -> (usize, u8) {
+    let bits = color_type.bits_per_pixel();
+    // the first pixel of the byte is in its most significant bits
+    (8 - bits - first_bit % 8, ((1u16 << bits) - 1) as u8)
+}
+
+/// Checks if the rectangular region fits into the view.
+/// # Arguments
+/// * ```width``` - The width of the view.
+/// * ```height``` - The height of the view.
+/// * ```point``` - The coordinates of the bottom left corner of the region.
+/// * ```size``` - The width and the height of the region.
+/// # Returns
+/// * [Result] which holds [Ok] or [Err] with [Error].
+/// # Errors
+/// * [Error::InvalidSize] - If the width or the height of the region is 0.
+/// * [Error::IndexOutOfBounds] - If the region is not fully contained in the view.
+fn check_region(width: usize, height: usize, point: (usize, usize), size: (usize, usize)) -> Result<(), Error> {
+    if size.0 == 0 || size.1 == 0 {
+        return Err(Error::InvalidSize);
+    }
+    if point.0 >= width || size.0 > width - point.0 || point.1 >= height || size.1 > height - point.1 {
+        return Err(Error::IndexOutOfBounds);
+    }
+    Ok(())
+}
+
+impl<'a> ImageView<'a> {
+    /// Creates a new view over the given bytes.
+    /// # Arguments
+    /// * ```data``` - The bytes of the pixels, starting with the top row.
+    /// * ```width``` - The width of the view.
+    /// * ```height``` - The height of the view.
+    /// * ```stride``` - The number of bytes between the starts of two rows, at least the number of bytes of a row.
+    /// * ```color_type``` - The color type of the view.
+    /// # Returns
+    /// * [Result] which holds new [ImageView] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height is 0, or the bytes are too short to hold all rows.
+    /// * [Error::InvalidStride] - If the stride is smaller than the number of bytes of a row.
+    pub fn new(data: &'a [u8], width: usize, height: usize, stride: usize, color_type: ColorType) -> Result<Self, Error> {
+        check_layout(data.len(), width, height, stride, color_type)?;
+        Ok(Self {
+            data,
+            width,
+            height,
+            stride,
+            offset: 0,
+            color_type,
+            alpha_mode: AlphaMode::Straight,
+            palette: None,
+        })
+    }
+
+    /// Returns the view with the given alpha mode.
+    /// The pixel data is not changed, the alpha mode only describes how the data is stored.
+    /// # Arguments
+    /// * ```alpha_mode``` - The alpha mode of the pixel data.
+    /// # Returns
+    /// * The view with the given alpha mode.
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Returns the view with the given palette.
+    /// The pixel data of [ColorType::INDEXED8] views is blended with the colors of the palette
+    /// and snapped to the nearest of them, as in indexed images.
+    /// # Arguments
+    /// * ```palette``` - The palette of the indexed pixel data.
+    /// # Returns
+    /// * The view with the given palette.
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the view is not [ColorType::INDEXED8].
+    pub fn with_palette(mut self, palette: &'a Palette) -> Result<Self, Error> {
+        if self.color_type != ColorType::INDEXED8 {
+            return Err(Error::WrongColor);
+        }
+        self.palette = Some(palette);
+        Ok(self)
+    }
+
+    /// Returns the width of the view.
+    /// # Returns
+    /// * The width of the view.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the view.
+    /// # Returns
+    /// * The height of the view.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of bytes between the starts of two rows.
+    /// # Returns
+    /// * The stride of the view.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Returns the color type of the view.
+    /// # Returns
+    /// * The color type of the view.
+    #[inline]
+    pub fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+
+    /// Returns the alpha mode of the view.
+    /// # Returns
+    /// * The alpha mode of the view.
+    #[inline]
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    /// Returns the palette of the indexed view.
+    /// # Returns
+    /// * The palette or [None] if not set.
+    #[inline]
+    pub fn palette(&self) -> Option<&'a Palette> {
+        self.palette
+    }
+
+    /// Returns the borrowed bytes of the view, including the padding of the rows.
+    /// # Returns
+    /// * The bytes of the view.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the index of the first byte of the pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * [Result] which holds the index of the first byte of the pixel or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    #[inline]
+    pub fn index(&self, index: (usize, usize)) -> Result<usize, Error> {
+        if index.0 >= self.width || index.1 >= self.height {
+            Err(Error::IndexOutOfBounds)
+        } else {
+            Ok(self.index_unchecked(index))
+        }
+    }
+
+    /// Returns the index of the first byte of the pixel at the given coordinates without performing checks.
+    /// For the color types with less than 8 bits per pixel, it is the index of the byte holding the pixel.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * The index of the first byte of the pixel.
+    #[inline]
+    pub fn index_unchecked(&self, index: (usize, usize)) -> usize {
+        (self.height - index.1 - 1) * self.stride + (self.offset + index.0 * self.color_type.bits_per_pixel()) / 8
+    }
+
+    /// Returns the value of the pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * [Result] which holds the value of the pixel or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: (usize, usize)) -> Result<Color, Error> {
+        if index.0 >= self.width || index.1 >= self.height {
+            Err(Error::IndexOutOfBounds)
+        } else {
+            Ok(self.get_unchecked(index))
+        }
+    }
+
+    /// Returns the value of the pixel at the given coordinates without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * The value of the pixel.
+    pub fn get_unchecked(&self, index: (usize, usize)) -> Color {
+        let index_temp = self.index_unchecked(index);
+        if self.color_type.bits_per_pixel() < 8 {
+            let (shift, mask) = packed_bits(self.color_type, self.offset + index.0 * self.color_type.bits_per_pixel());
+            return Color::from_bytes(self.color_type, &[(self.data[index_temp] >> shift) & mask]);
+        }
+        let color = Color::from_bytes(self.color_type, &self.data[index_temp..index_temp + self.color_type.bytes_per_pixel()]);
+        match self.alpha_mode {
+            AlphaMode::Straight => color,
+            AlphaMode::Premultiplied => color.unpremultiplied(),
+        }
+    }
+
+    /// Copies the view into a new image. The padding of the rows is dropped.
+    /// # Returns
+    /// * The new image with the same pixels and alpha mode.
+    pub fn to_image(&self) -> Image {
+        let row_len = self.color_type.row_len(self.width);
+        let mut image = Image {
+            data: Vec::with_capacity(row_len * self.height),
+            width: self.width,
+            height: self.height,
+            color_type: self.color_type,
+            background_color: None,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette.cloned(),
+        };
+        if self.color_type.bits_per_pixel() < 8 {
+            // packed pixels are moved to the start of the rows, the padding bits stay cleared
+            image.data.resize(row_len * self.height, 0);
+            let mut view = image.view_mut();
+            for y in 0..self.height {
+                for x in 0..self.width {
+                    let (shift, mask) = packed_bits(self.color_type, self.offset + x * self.color_type.bits_per_pixel());
+                    view.set_packed((x, y), (self.data[self.index_unchecked((x, y))] >> shift) & mask);
+                }
+            }
+        } else {
+            for row in 0..self.height {
+                image.data.extend_from_slice(&self.data[row * self.stride..row * self.stride + row_len]);
+            }
+        }
+        image
+    }
+
+    /// Returns the view of the rectangular region of this view.
+    /// The coordinates in the returned view are local to the region.
+    /// # Arguments
+    /// * ```point``` - The coordinates of the bottom left corner of the region.
+    /// * ```size``` - The width and the height of the region.
+    /// # Returns
+    /// * [Result] which holds the view of the region or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height of the region is 0.
+    /// * [Error::IndexOutOfBounds] - If the region is not fully contained in the view.
+    pub fn sub_image(&self, point: (usize, usize), size: (usize, usize)) -> Result<ImageView<'a>, Error> {
+        check_region(self.width, self.height, point, size)?;
+        let (range, offset) = region_range(self.height, self.stride, self.offset, self.color_type.bits_per_pixel(), point, size);
+        Ok(ImageView {
+            data: &self.data[range],
+            width: size.0,
+            height: size.1,
+            stride: self.stride,
+            offset,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette,
+        })
+    }
+}
+
+impl<'a> ImageViewMut<'a> {
+    /// Creates a new mutable view over the given bytes.
+    /// # Arguments
+    /// * ```data``` - The bytes of the pixels, starting with the top row.
+    /// * ```width``` - The width of the view.
+    /// * ```height``` - The height of the view.
+    /// * ```stride``` - The number of bytes between the starts of two rows, at least the number of bytes of a row.
+    /// * ```color_type``` - The color type of the view.
+    /// # Returns
+    /// * [Result] which holds new [ImageViewMut] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height is 0, or the bytes are too short to hold all rows.
+    /// * [Error::InvalidStride] - If the stride is smaller than the number of bytes of a row.
+    pub fn new(data: &'a mut [u8], width: usize, height: usize, stride: usize, color_type: ColorType) -> Result<Self, Error> {
+        check_layout(data.len(), width, height, stride, color_type)?;
+        Ok(Self {
+            data,
+            width,
+            height,
+            stride,
+            offset: 0,
+            color_type,
+            alpha_mode: AlphaMode::Straight,
+            palette: None,
+        })
+    }
+
+    /// Returns the view with the given alpha mode.
+    /// The pixel data is not changed, the alpha mode only describes how the data is stored.
+    /// # Arguments
+    /// * ```alpha_mode``` - The alpha mode of the pixel data.
+    /// # Returns
+    /// * The view with the given alpha mode.
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Returns the view with the given palette.
+    /// The pixel data of [ColorType::INDEXED8] views is blended with the colors of the palette
+    /// and snapped to the nearest of them, as in indexed images.
+    /// # Arguments
+    /// * ```palette``` - The palette of the indexed pixel data.
+    /// # Returns
+    /// * The view with the given palette.
+    /// # Errors
+    /// * [Error::WrongColor] - If the color type of the view is not [ColorType::INDEXED8].
+    pub fn with_palette(mut self, palette: &'a Palette) -> Result<Self, Error> {
+        if self.color_type != ColorType::INDEXED8 {
+            return Err(Error::WrongColor);
+        }
+        self.palette = Some(palette);
+        Ok(self)
+    }
+
+    /// Returns the read-only view of the same pixels.
+    /// # Returns
+    /// * The read-only view.
+    #[inline]
+    pub fn as_view(&self) -> ImageView<'_> {
+        ImageView {
+            data: self.data,
+            width: self.width,
+            height: self.height,
+            stride: self.stride,
+            offset: self.offset,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette,
+        }
+    }
+
+    /// Returns the width of the view.
+    /// # Returns
+    /// * The width of the view.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the view.
+    /// # Returns
+    /// * The height of the view.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the number of bytes between the starts of two rows.
+    /// # Returns
+    /// * The stride of the view.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    /// Returns the color type of the view.
+    /// # Returns
+    /// * The color type of the view.
+    #[inline]
+    pub fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+
+    /// Returns the alpha mode of the view.
+    /// # Returns
+    /// * The alpha mode of the view.
+    #[inline]
+    pub fn alpha_mode(&self) -> AlphaMode {
+        self.alpha_mode
+    }
+
+    /// Returns the palette of the indexed view.
+    /// # Returns
+    /// * The palette or [None] if not set.
+    #[inline]
+    pub fn palette(&self) -> Option<&'a Palette> {
+        self.palette
+    }
+
+    /// Returns the borrowed bytes of the view, including the padding of the rows.
+    /// # Returns
+    /// * The bytes of the view.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data
+    }
+
+    /// Returns the borrowed bytes of the view as mutable, including the padding of the rows.
+    /// # Returns
+    /// * The mutable bytes of the view.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    /// Returns the index of the first byte of the pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * [Result] which holds the index of the first byte of the pixel or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    #[inline]
+    pub fn index(&self, index: (usize, usize)) -> Result<usize, Error> {
+        self.as_view().index(index)
+    }
+
+    /// Returns the index of the first byte of the pixel at the given coordinates without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * The index of the first byte of the pixel.
+    #[inline]
+    pub fn index_unchecked(&self, index: (usize, usize)) -> usize {
+        self.as_view().index_unchecked(index)
+    }
+
+    /// Returns the value of the pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * [Result] which holds the value of the pixel or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    #[inline]
+    pub fn get(&self, index: (usize, usize)) -> Result<Color, Error> {
+        self.as_view().get(index)
+    }
+
+    /// Returns the value of the pixel at the given coordinates without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// # Returns
+    /// * The value of the pixel.
+    #[inline]
+    pub fn get_unchecked(&self, index: (usize, usize)) -> Color {
+        self.as_view().get_unchecked(index)
+    }
+
+    /// Sets the color of the pixels at the given locations.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    /// * [Error::WrongColor] - If the color type of the view does not match the color type of the color.
+    pub fn set<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color) -> Result<(), Error> {
+        check_index(&index, self.width, self.height)?;
+        if ColorType::from(color) != self.color_type {
+            return Err(Error::WrongColor);
+        }
+
+        self.set_unchecked(index, color);
+
+        Ok(())
+    }
+
+    /// Sets the color of the pixels at the given locations without performing checks.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    pub fn set_unchecked<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color) {
+        let x_range = index.0.start()..index.0.end().unwrap_or(self.width);
+        let y_range = index.1.start()..index.1.end().unwrap_or(self.height);
+
+        let color = match self.alpha_mode {
+            AlphaMode::Straight => color,
+            AlphaMode::Premultiplied => color.premultiplied(),
+        };
+        let bytes = color.as_bytes();
+        if self.color_type.bits_per_pixel() < 8 {
+            for y in y_range {
+                for x in x_range.clone() {
+                    self.set_packed((x, y), bytes[0]);
+                }
+            }
+            return;
+        }
+        for y in y_range {
+            let index_low = self.index_unchecked((x_range.start, y));
+            let index_high = index_low + x_range.len() * bytes.len();
+            for pixel in self.data[index_low..index_high].chunks_exact_mut(bytes.len()) {
+                pixel.copy_from_slice(bytes);
+            }
+        }
+    }
+
+    /// Sets the value of the pixels at the given locations by blending the color with the current value at the given coordinates.
+    /// The color is placed over the current value (source-over compositing) with respect to the alpha channels of both colors.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// * ```opacity``` - The opacity for blending. It multiplies the alpha channel of the color.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    /// * [Error::WrongColor] - If the color type of the view does not match the color type of the color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    #[inline]
+    pub fn set_transparent<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color, opacity: f64) -> Result<(), Error> {
+        self.set_composite(index, color, opacity, Blend::default())
+    }
+
+    /// Sets the value of the pixels at the given locations by blending the color with the current value at the given coordinates without performing checks.
+    /// The color is placed over the current value (source-over compositing) with respect to the alpha channels of both colors.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// * ```opacity``` - The opacity for blending. It multiplies the alpha channel of the color.
+    #[inline]
+    pub fn set_transparent_unchecked<RX: ImageSetIndex, RY: ImageSetIndex>(&mut self, index: (RX, RY), color: Color, opacity: f64) {
+        self.set_composite_unchecked(index, color, opacity, Blend::default());
+    }
+
+    /// Sets the value of the pixels at the given locations by blending and compositing the color with the current value at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// * ```opacity``` - The opacity of the color. It multiplies the alpha channel of the color.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the color with the current value.
+    /// # Returns
+    /// * [Result] which holds [Ok] or [Err] with [Error].
+    /// # Errors
+    /// * [Error::IndexOutOfBounds] - If the index is out of bounds.
+    /// * [Error::WrongColor] - If the color type of the view does not match the color type of the color.
+    /// * [Error::InvalidOpacity] - If the opacity is NaN or not in the range [0.0, 1.0].
+    pub fn set_composite<RX: ImageSetIndex, RY: ImageSetIndex, B: Into<Blend>>(&mut self, index: (RX, RY), color: Color, opacity: f64, blend: B) -> Result<(), Error> {
+        check_index(&index, self.width, self.height)?;
+        if ColorType::from(color) != self.color_type {
+            return Err(Error::WrongColor);
+        }
+        if opacity.is_nan() || !(0.0..=1.0).contains(&opacity) {
+            return Err(Error::InvalidOpacity);
+        }
+
+        self.set_composite_unchecked(index, color, opacity, blend);
+
+        Ok(())
+    }
+
+    /// Sets the value of the pixels at the given locations by blending and compositing the color with the current value at the given coordinates without performing checks.
+    /// Views with a palette (see [ImageViewMut::with_palette()]) store the index of the palette color nearest to the blended color.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates ```(x, y)```. The coordinates can be ```usize```, ```Range<usize>```, ```RangeFrom<usize>```, ```RangeFull```, ```RangeInclusive<usize>```, ```RangeTo<usize>```, ```RangeToInclusive<usize>```.
+    /// * ```color``` - The color to set.
+    /// * ```opacity``` - The opacity of the color. It multiplies the alpha channel of the color.
+    /// * ```blend``` - The [Blend] (or just a [BlendMode](crate::BlendMode) or a [CompositeOp](crate::CompositeOp)) used to combine the color with the current value.
+    pub fn set_composite_unchecked<RX: ImageSetIndex, RY: ImageSetIndex, B: Into<Blend>>(&mut self, index: (RX, RY), color: Color, opacity: f64, blend: B) {
+        let x_range = index.0.start()..index.0.end().unwrap_or(self.width);
+        let y_range = index.1.start()..index.1.end().unwrap_or(self.height);
+
+        let blend = blend.into();
+        let color = match self.palette {
+            Some(palette) => palette.resolve(color),
+            None => color,
+        };
+        let mut source = blend.space.decode(color);
+        source[3] *= opacity;
+        let premultiplied = self.alpha_mode == AlphaMode::Premultiplied;
+
+        if let Some(palette) = self.palette {
+            // the blended colors are snapped to the nearest colors of the palette
+            for y in y_range {
+                let index_low = self.index_unchecked((x_range.start, y));
+                for pixel in &mut self.data[index_low..index_low + x_range.len()] {
+                    *pixel = palette.blend_index(source, *pixel, &blend);
+                }
+            }
+            return;
+        }
+
+        if self.color_type.bits_per_pixel() < 8 {
+            for y in y_range {
+                for x in x_range.clone() {
+                    let new_color = blend.blend_color(source, self.get_unchecked((x, y)), premultiplied);
+                    self.set_packed((x, y), new_color.as_bytes()[0]);
+                }
+            }
+            return;
+        }
+
+        let bytes_per_pixel = self.color_type.bytes_per_pixel();
+        for y in y_range {
+            let index_low = self.index_unchecked((x_range.start, y));
+            let index_high = index_low + x_range.len() * bytes_per_pixel;
+            for pixel in self.data[index_low..index_high].chunks_exact_mut(bytes_per_pixel) {
+                let current_color = Color::from_bytes(self.color_type, pixel);
+                let new_color = blend.blend_color(source, current_color, premultiplied);
+                pixel.copy_from_slice(new_color.as_bytes());
+            }
+        }
+    }
+
+    /// Sets the raw value of the packed pixel at the given coordinates.
+    /// # Arguments
+    /// * ```index``` - The tuple with the coordinates of the pixel (x, y).
+    /// * ```value``` - The value of the pixel.
+    #[inline]
+    fn set_packed(&mut self, index: (usize, usize), value: u8) {
+        let (shift, mask) = packed_bits(self.color_type, self.offset + index.0 * self.color_type.bits_per_pixel());
+        let byte_index = self.index_unchecked(index);
+        self.data[byte_index] = (self.data[byte_index] & !(mask << shift)) | ((value & mask) << shift);
+    }
+
+    /// Copies the view into a new image. The padding of the rows is dropped.
+    /// # Returns
+    /// * The new image with the same pixels and alpha mode.
+    #[inline]
+    pub fn to_image(&self) -> Image {
+        self.as_view().to_image()
+    }
+
+    /// Returns the mutable view of the rectangular region of this view.
+    /// The coordinates in the returned view are local to the region.
+    /// # Arguments
+    /// * ```point``` - The coordinates of the bottom left corner of the region.
+    /// * ```size``` - The width and the height of the region.
+    /// # Returns
+    /// * [Result] which holds the view of the region or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height of the region is 0.
+    /// * [Error::IndexOutOfBounds] - If the region is not fully contained in the view.
+    pub fn sub_image(&mut self, point: (usize, usize), size: (usize, usize)) -> Result<ImageViewMut<'_>, Error> {
+        check_region(self.width, self.height, point, size)?;
+        let (range, offset) = region_range(self.height, self.stride, self.offset, self.color_type.bits_per_pixel(), point, size);
+        Ok(ImageViewMut {
+            data: &mut self.data[range],
+            width: size.0,
+            height: size.1,
+            stride: self.stride,
+            offset,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette,
+        })
+    }
+}
+
+impl DrawTarget for ImageViewMut<'_> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn color_type(&self) -> ColorType {
+        self.color_type
+    }
+
+    fn get_pixel(&self, index: (usize, usize)) -> Color {
+        self.get_unchecked(index)
+    }
+
+    fn set_pixel(&mut self, index: (usize, usize), color: Color) {
+        self.set_unchecked(index, color);
+    }
+
+    fn fill_span(&mut self, y: usize, x: Range<usize>, color: Color) {
+        self.set_unchecked((x, y), color);
+    }
+
+    fn blend_span(&mut self, y: usize, x: Range<usize>, color: Color, opacity: f64, blend: Blend) {
+        self.set_composite_unchecked((x, y), color, opacity, blend);
+    }
+}
+
+impl Image {
+    /// Returns the read-only view of the whole image.
+    /// # Returns
+    /// * The view of the image.
+    #[inline]
+    pub fn view(&self) -> ImageView<'_> {
+        ImageView {
+            data: &self.data,
+            width: self.width,
+            height: self.height,
+            stride: self.color_type.row_len(self.width),
+            offset: 0,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette.as_ref(),
+        }
+    }
+
+    /// Returns the mutable view of the whole image.
+    /// # Returns
+    /// * The mutable view of the image.
+    #[inline]
+    pub fn view_mut(&mut self) -> ImageViewMut<'_> {
+        ImageViewMut {
+            data: &mut self.data,
+            width: self.width,
+            height: self.height,
+            stride: self.color_type.row_len(self.width),
+            offset: 0,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette.as_ref(),
+        }
+    }
+
+    /// Returns the mutable view of the rectangular region of the image.
+    /// The coordinates in the returned view are local to the region, so the region can be drawn onto as if it was a separate image.
+    /// # Arguments
+    /// * ```point``` - The coordinates of the bottom left corner of the region.
+    /// * ```size``` - The width and the height of the region.
+    /// # Returns
+    /// * [Result] which holds the view of the region or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height of the region is 0.
+    /// * [Error::IndexOutOfBounds] - If the region is not fully contained in the image.
+    pub fn sub_image(&mut self, point: (usize, usize), size: (usize, usize)) -> Result<ImageViewMut<'_>, Error> {
+        check_region(self.width, self.height, point, size)?;
+        let stride = self.color_type.row_len(self.width);
+        let (range, offset) = region_range(self.height, stride, 0, self.color_type.bits_per_pixel(), point, size);
+        Ok(ImageViewMut {
+            data: &mut self.data[range],
+            width: size.0,
+            height: size.1,
+            stride,
+            offset,
+            color_type: self.color_type,
+            alpha_mode: self.alpha_mode,
+            palette: self.palette.as_ref(),
+        })
+    }
+
+    /// Copies the rectangular region of the image into a new image.
+    /// # Arguments
+    /// * ```point``` - The coordinates of the bottom left corner of the region.
+    /// * ```size``` - The width and the height of the region.
+    /// # Returns
+    /// * [Result] which holds the new image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height of the region is 0.
+    /// * [Error::IndexOutOfBounds] - If the region is not fully contained in the image.
+    pub fn crop(&self, point: (usize, usize), size: (usize, usize)) -> Result<Image, Error> {
+        let mut image = self.view().sub_image(point, size)?.to_image();
+        image.background_color = self.background_color;
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlendMode, SdfShape};
+
+    #[test]
+    fn test_view_layout() {
+        // 3x2 RGB8 pixels with rows padded to 12 bytes
+        let mut data = vec![0_u8; 24];
+        assert_eq!(ImageViewMut::new(&mut data, 3, 2, 8, ColorType::RGB8).unwrap_err(), Error::InvalidStride);
+        assert_eq!(ImageViewMut::new(&mut data, 3, 3, 12, ColorType::RGB8).unwrap_err(), Error::InvalidSize);
+        assert_eq!(ImageViewMut::new(&mut data, 0, 2, 12, ColorType::RGB8).unwrap_err(), Error::InvalidSize);
+        // the last row doesn't need the padding
+        assert!(ImageView::new(&data[..21], 3, 2, 12, ColorType::RGB8).is_ok());
+        // strides and widths too large to be addressed are rejected
+        assert_eq!(ImageView::new(&[0; 16], 1, 3, usize::MAX / 2 + 1, ColorType::GRAY8).unwrap_err(), Error::InvalidSize);
+        assert_eq!(ImageView::new(&[0; 16], usize::MAX / 2, 1, usize::MAX, ColorType::RGB8).unwrap_err(), Error::InvalidSize);
+
+        let mut view = ImageViewMut::new(&mut data, 3, 2, 12, ColorType::RGB8).unwrap();
+        assert_eq!(view.index((2, 1)).unwrap(), 6);
+        assert_eq!(view.index((0, 0)).unwrap(), 12);
+        assert_eq!(view.index((3, 0)), Err(Error::IndexOutOfBounds));
+        view.set((1, 0), Color::RGB8([1, 2, 3])).unwrap();
+        view.set((.., 1), Color::RGB8([9, 9, 9])).unwrap();
+        assert_eq!(view.set((1..4, 0), Color::RGB8([0, 0, 0])), Err(Error::IndexOutOfBounds));
+        assert_eq!(view.set((0, 0), Color::RGBA8([0, 0, 0, 0])), Err(Error::WrongColor));
+        assert_eq!(view.get((1, 0)).unwrap(), Color::RGB8([1, 2, 3]));
+        assert_eq!(&data[..12], &[9, 9, 9, 9, 9, 9, 9, 9, 9, 0, 0, 0]);
+        assert_eq!(&data[12..], &[0, 0, 0, 1, 2, 3, 0, 0, 0, 0, 0, 0]);
+
+        let image = ImageView::new(&data, 3, 2, 12, ColorType::RGB8).unwrap().to_image();
+        assert_eq!(image.as_bytes(), &[9, 9, 9, 9, 9, 9, 9, 9, 9, 0, 0, 0, 1, 2, 3, 0, 0, 0]);
+        assert_eq!(image.get((1, 0)).unwrap(), Color::RGB8([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_view_drawing() {
+        // drawing onto a padded buffer gives the same pixels as drawing onto an image
+        let mut image = Image::new(20, 15, Color::RGBA8([10, 20, 30, 255]));
+        let row_len = 20 * 4;
+        let stride = row_len + 8;
+        let mut data = vec![0xAB_u8; stride * 15];
+        for row in data.chunks_exact_mut(stride) {
+            row[..row_len].copy_from_slice(image.as_bytes()[..row_len].as_ref());
+        }
+        let mut view = ImageViewMut::new(&mut data, 20, 15, stride, ColorType::RGBA8).unwrap();
+
+        let shape = SdfShape::Capsule { point1: (3.0, 3.0), point2: (16.0, 10.0), radius: 2.5 };
+        view.draw_rectangle((1, 1), (12, 8), Color::RGBA8([200, 100, 0, 255]), 2, 0.7, BlendMode::Overlay).unwrap();
+        view.draw_sdf(&shape, Color::RGBA8([0, 255, 0, 200]), 0.0, 1.0, 1.0, Blend::default()).unwrap();
+        view.set_transparent((5..9, 12), Color::RGBA8([255, 255, 255, 255]), 0.5).unwrap();
+        image.draw_rectangle((1, 1), (12, 8), Color::RGBA8([200, 100, 0, 255]), 2, 0.7, BlendMode::Overlay).unwrap();
+        image.draw_sdf(&shape, Color::RGBA8([0, 255, 0, 200]), 0.0, 1.0, 1.0, Blend::default()).unwrap();
+        image.set_transparent((5..9, 12), Color::RGBA8([255, 255, 255, 255]), 0.5).unwrap();
+
+        assert_eq!(view.to_image().as_bytes(), image.as_bytes());
+        // padding is untouched
+        assert!(data.chunks_exact(stride).all(|row| row[row_len..].iter().all(|&byte| byte == 0xAB)));
+
+        // views of the image share its pixels
+        let mut premultiplied = image.clone();
+        premultiplied.set_alpha_mode(AlphaMode::Premultiplied);
+        premultiplied.view_mut().set((0, 0), Color::RGBA8([255, 0, 0, 128])).unwrap();
+        assert_eq!(premultiplied.view().get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 128]));
+        assert_eq!(premultiplied.get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 128]));
+    }
+
+    #[test]
+    fn test_packed() {
+        let data = [0b1010_0000_u8, 0xFF, 0b0101_0000, 0xFF];
+        let view = ImageView::new(&data, 4, 2, 2, ColorType::GRAY1).unwrap();
+        assert_eq!(view.get((0, 1)).unwrap(), Color::GRAY1(1));
+        assert_eq!(view.get((1, 1)).unwrap(), Color::GRAY1(0));
+        assert_eq!(view.to_image().as_bytes(), &[0b1010_0000, 0b0101_0000]);
+
+        // regions of packed pixels can start inside of a byte
+        let mut image = Image::from_bytes(4, 2, ColorType::GRAY1, &[0b1010_0000, 0b0101_0000]).unwrap();
+        let sub_view = image.view().sub_image((1, 0), (2, 2)).unwrap();
+        assert_eq!(sub_view.get((0, 1)).unwrap(), Color::GRAY1(0));
+        assert_eq!(sub_view.get((1, 1)).unwrap(), Color::GRAY1(1));
+        assert_eq!(sub_view.to_image().as_bytes(), &[0b0100_0000, 0b1000_0000]);
+        let mut region = image.sub_image((1, 0), (2, 2)).unwrap();
+        region.set((.., 0), Color::GRAY1(1)).unwrap();
+        region.draw_rectangle((0, 1), (1, 1), Color::GRAY1(0), 0, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.as_bytes(), &[0b1000_0000, 0b0111_0000]);
+        assert_eq!(image.view_mut().get((0, 0)).unwrap(), Color::GRAY1(0));
+        let cropped = image.crop((1, 0), (3, 1)).unwrap();
+        assert_eq!((cropped.color_type(), cropped.as_bytes()), (ColorType::GRAY1, &[0b1110_0000][..]));
+    }
+
+    #[test]
+    fn test_sub_image() {
+        let mut image = Image::new(10, 8, Color::GRAY8(0));
+        for y in 0..8 {
+            for x in 0..10 {
+                image.set((x, y), Color::GRAY8((y * 10 + x) as u8)).unwrap();
+            }
+        }
+
+        // test errors
+        assert_eq!(image.sub_image((2, 3), (0, 2)).unwrap_err(), Error::InvalidSize);
+        assert_eq!(image.sub_image((2, 3), (9, 2)).unwrap_err(), Error::IndexOutOfBounds);
+        assert_eq!(image.sub_image((2, 3), (2, 6)).unwrap_err(), Error::IndexOutOfBounds);
+        assert_eq!(image.crop((usize::MAX, 0), (2, 2)).unwrap_err(), Error::IndexOutOfBounds);
+
+        // coordinates are local to the region
+        let mut region = image.sub_image((2, 3), (4, 3)).unwrap();
+        assert_eq!((region.width(), region.height(), region.stride()), (4, 3, 10));
+        assert_eq!(region.get((0, 0)).unwrap(), Color::GRAY8(32));
+        assert_eq!(region.get((3, 2)).unwrap(), Color::GRAY8(55));
+        assert_eq!(region.get((4, 0)), Err(Error::IndexOutOfBounds));
+        let mut inner = region.sub_image((1, 1), (2, 2)).unwrap();
+        assert_eq!(inner.get((0, 0)).unwrap(), Color::GRAY8(43));
+        inner.set((.., ..), Color::GRAY8(255)).unwrap();
+        assert_eq!(region.as_view().sub_image((1, 1), (3, 1)).unwrap().get((2, 0)).unwrap(), Color::GRAY8(45));
+
+        // drawing is clipped to the region
+        region.draw_rectangle((0, 0), (100, 0), Color::GRAY8(200), 0, 1.0, Blend::default()).unwrap();
+        region.draw_circle((0, 2), 1, Color::GRAY8(100), 0, 1.0, Blend::default()).unwrap();
+        assert_eq!(image.get((1, 3)).unwrap(), Color::GRAY8(31));
+        assert_eq!(image.get((2, 3)).unwrap(), Color::GRAY8(200));
+        assert_eq!(image.get((5, 3)).unwrap(), Color::GRAY8(200));
+        assert_eq!(image.get((6, 3)).unwrap(), Color::GRAY8(36));
+        assert_eq!(image.get((2, 5)).unwrap(), Color::GRAY8(100));
+        assert_eq!(image.get((1, 5)).unwrap(), Color::GRAY8(51));
+        assert_eq!(image.get((2, 6)).unwrap(), Color::GRAY8(62));
+        assert_eq!(image.get((4, 4)).unwrap(), Color::GRAY8(255));
+
+        // crop copies the region
+        let cropped = image.crop((1, 4), (3, 2)).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (3, 2));
+        for y in 0..2 {
+            for x in 0..3 {
+                assert_eq!(cropped.get((x, y)), image.get((x + 1, y + 4)));
+            }
+        }
+        assert_eq!(cropped.background_color(), Some(Color::GRAY8(0)));
+        assert_eq!(image.crop((0, 0), (10, 8)).unwrap(), image);
+    }
+}
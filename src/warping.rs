@@ -0,0 +1,514 @@
+//! A module containing geometric transforms and the functions for warping images with them.
+//!
+//! Transforms map the coordinates of the source image to the coordinates of the warped image.
+//! As for the shapes, pixel centers lie on integer coordinates and coordinates origin is in the bottom left corner.
+//! Every pixel of the warped image is sampled from the source image at the inversely transformed position.
+
+use crate::blending::premultiply;
+use crate::colors::{Color, ColorType};
+use crate::error::Error;
+use crate::image::Image;
+use crate::resampling::Filter;
+
+/// A struct that holds an affine transform (a combination of translation, rotation, scaling and shearing).
+/// The point ```(x, y)``` is transformed to ```(a * x + b * y + c, d * x + e * y + f)``` where ```[[a, b, c], [d, e, f]]``` is the matrix.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Transform {
+    /// The two rows of the transform matrix
+    pub matrix: [[f64; 3]; 2],
+}
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+impl Transform {
+    /// Creates a new transform from the matrix.
+    /// # Arguments
+    /// * ```matrix``` - The two rows of the transform matrix.
+    /// # Returns
+    /// * The new transform.
+    pub const fn new(matrix: [[f64; 3]; 2]) -> Self {
+        Self { matrix }
+    }
+
+    /// Creates the transform which doesn't move any point.
+    /// # Returns
+    /// * The identity transform.
+    pub const fn identity() -> Self {
+        Self::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0]])
+    }
+
+    /// Creates the transform which moves points by the offset.
+    /// # Arguments
+    /// * ```offset``` - The offset ```(x, y)```.
+    /// # Returns
+    /// * The translation transform.
+    pub const fn translation(offset: (f64, f64)) -> Self {
+        Self::new([[1.0, 0.0, offset.0], [0.0, 1.0, offset.1]])
+    }
+
+    /// Creates the transform which scales points away from the origin.
+    /// # Arguments
+    /// * ```scale``` - The scale along the axes ```(x, y)```.
+    /// # Returns
+    /// * The scaling transform.
+    pub const fn scaling(scale: (f64, f64)) -> Self {
+        Self::new([[scale.0, 0.0, 0.0], [0.0, scale.1, 0.0]])
+    }
+
+    /// Creates the transform which rotates points around the origin.
+    /// # Arguments
+    /// * ```angle``` - The angle in radians, positive angles rotate counterclockwise.
+    /// # Returns
+    /// * The rotation transform.
+    pub fn rotation(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new([[cos, -sin, 0.0], [sin, cos, 0.0]])
+    }
+
+    /// Returns the transform which applies this transform and then the other transform.
+    /// # Arguments
+    /// * ```other``` - The transform applied second.
+    /// # Returns
+    /// * The combined transform.
+    pub fn then(&self, other: &Transform) -> Self {
+        let [[a1, b1, c1], [d1, e1, f1]] = self.matrix;
+        let [[a2, b2, c2], [d2, e2, f2]] = other.matrix;
+        Self::new([
+            [a2 * a1 + b2 * d1, a2 * b1 + b2 * e1, a2 * c1 + b2 * f1 + c2],
+            [d2 * a1 + e2 * d1, d2 * b1 + e2 * e1, d2 * c1 + e2 * f1 + f2],
+        ])
+    }
+
+    /// Returns the transform which reverts this transform.
+    /// # Returns
+    /// * The inverse transform, [None] if the transform is not invertible (it collapses the plane to a line or a point).
+    pub fn inverse(&self) -> Option<Self> {
+        let [[a, b, c], [d, e, f]] = self.matrix;
+        let determinant = a * e - b * d;
+        if determinant.abs() < 1e-12 || !determinant.is_finite() {
+            return None;
+        }
+        Some(Self::new([
+            [e / determinant, -b / determinant, (b * f - c * e) / determinant],
+            [-d / determinant, a / determinant, (c * d - a * f) / determinant],
+        ]))
+    }
+
+    /// Transforms the point.
+    /// # Arguments
+    /// * ```point``` - The point ```(x, y)```.
+    /// # Returns
+    /// * The transformed point.
+    pub fn apply(&self, point: (f64, f64)) -> (f64, f64) {
+        let [[a, b, c], [d, e, f]] = self.matrix;
+        (a * point.0 + b * point.1 + c, d * point.0 + e * point.1 + f)
+    }
+}
+
+/// A struct that holds a projective transform (homography), which maps any quadrilateral to any other quadrilateral.
+/// The point ```(x, y)``` is transformed to ```((a * x + b * y + c) / w, (d * x + e * y + f) / w)``` where ```w = g * x + h * y + i```
+/// and ```[[a, b, c], [d, e, f], [g, h, i]]``` is the matrix.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Homography {
+    /// The three rows of the homography matrix
+    pub matrix: [[f64; 3]; 3],
+}
+impl Default for Homography {
+    fn default() -> Self {
+        Self::from(Transform::identity())
+    }
+}
+impl From<Transform> for Homography {
+    fn from(transform: Transform) -> Self {
+        let [first, second] = transform.matrix;
+        Self::new([first, second, [0.0, 0.0, 1.0]])
+    }
+}
+impl Homography {
+    /// Creates a new homography from the matrix.
+    /// # Arguments
+    /// * ```matrix``` - The three rows of the homography matrix.
+    /// # Returns
+    /// * The new homography.
+    pub const fn new(matrix: [[f64; 3]; 3]) -> Self {
+        Self { matrix }
+    }
+
+    /// Computes the homography which maps the four source points to the four destination points.
+    /// # Arguments
+    /// * ```source``` - The corners of the source quadrilateral.
+    /// * ```destination``` - The corners of the destination quadrilateral, in the same order.
+    /// # Returns
+    /// * The homography, [None] if it doesn't exist (three of the source or destination points lie on a line).
+    pub fn from_points(source: [(f64, f64); 4], destination: [(f64, f64); 4]) -> Option<Self> {
+        // the linear system for the first 8 entries of the matrix, the last one is 1
+        let mut system = [[0.0; 9]; 8];
+        for (i, ((x, y), (u, v))) in source.into_iter().zip(destination).enumerate() {
+            system[2 * i] = [x, y, 1.0, 0.0, 0.0, 0.0, -x * u, -y * u, u];
+            system[2 * i + 1] = [0.0, 0.0, 0.0, x, y, 1.0, -x * v, -y * v, v];
+        }
+
+        // gaussian elimination with partial pivoting
+        let scale = system.iter().flatten().fold(0.0_f64, |max, value| max.max(value.abs()));
+        for column in 0..8 {
+            let pivot = (column..8).max_by(|&a, &b| system[a][column].abs().total_cmp(&system[b][column].abs()))?;
+            if system[pivot][column].abs() <= scale * 1e-12 {
+                return None;
+            }
+            system.swap(column, pivot);
+            let pivot_row = system[column];
+            for (row, values) in system.iter_mut().enumerate() {
+                if row != column {
+                    let factor = values[column] / pivot_row[column];
+                    for (value, pivot_value) in values[column..].iter_mut().zip(&pivot_row[column..]) {
+                        *value -= factor * pivot_value;
+                    }
+                }
+            }
+        }
+        let h: [f64; 8] = std::array::from_fn(|i| system[i][8] / system[i][i]);
+        let homography = Self::new([[h[0], h[1], h[2]], [h[3], h[4], h[5]], [h[6], h[7], 1.0]]);
+        homography.inverse().map(|_| homography)
+    }
+
+    /// Returns the homography which applies this homography and then the other homography.
+    /// # Arguments
+    /// * ```other``` - The homography applied second.
+    /// # Returns
+    /// * The combined homography.
+    pub fn then(&self, other: &Homography) -> Self {
+        Self::new(std::array::from_fn(|row| std::array::from_fn(|column| (0..3).map(|k| other.matrix[row][k] * self.matrix[k][column]).sum())))
+    }
+
+    /// Returns the homography which reverts this homography.
+    /// # Returns
+    /// * The inverse homography, [None] if the homography is not invertible.
+    pub fn inverse(&self) -> Option<Self> {
+        let [[a, b, c], [d, e, f], [g, h, i]] = self.matrix;
+        let cofactors = [e * i - f * h, f * g - d * i, d * h - e * g];
+        let determinant = a * cofactors[0] + b * cofactors[1] + c * cofactors[2];
+        let scale = self.matrix.iter().flatten().fold(0.0_f64, |max, value| max.max(value.abs()));
+        if determinant.abs() <= scale * scale * scale * 1e-12 || !determinant.is_finite() {
+            return None;
+        }
+        Some(Self::new([
+            [cofactors[0] / determinant, (c * h - b * i) / determinant, (b * f - c * e) / determinant],
+            [cofactors[1] / determinant, (a * i - c * g) / determinant, (c * d - a * f) / determinant],
+            [cofactors[2] / determinant, (b * g - a * h) / determinant, (a * e - b * d) / determinant],
+        ]))
+    }
+
+    /// Transforms the point.
+    /// # Arguments
+    /// * ```point``` - The point ```(x, y)```.
+    /// # Returns
+    /// * The transformed point, [None] if the point is mapped to infinity.
+    pub fn apply(&self, point: (f64, f64)) -> Option<(f64, f64)> {
+        let [[a, b, c], [d, e, f], [g, h, i]] = self.matrix;
+        let w = g * point.0 + h * point.1 + i;
+        if w.abs() < 1e-12 {
+            return None;
+        }
+        Some(((a * point.0 + b * point.1 + c) / w, (d * point.0 + e * point.1 + f) / w))
+    }
+}
+
+/// Samples the premultiplied pixels at the point, interpolating between them with the filter.
+/// Samples outside of the pixels take the background value.
+/// # Arguments
+/// * ```pixels``` - The normalized, premultiplied pixels in the order of the pixel data.
+/// * ```size``` - The width and the height of the image.
+/// * ```point``` - The coordinates of the point.
+/// * ```filter``` - The filter used for interpolation.
+/// * ```background``` - The normalized, premultiplied value outside of the image.
+/// # Returns
+/// * The normalized, premultiplied value at the point.
+fn sample(pixels: &[[f64; 4]], size: (usize, usize), point: (f64, f64), filter: Filter, background: [f64; 4]) -> [f64; 4] {
+    let pixel = |x: isize, y: isize| {
+        if x < 0 || y < 0 || x >= size.0 as isize || y >= size.1 as isize {
+            background
+        } else {
+            pixels[(size.1 - y as usize - 1) * size.0 + x as usize]
+        }
+    };
+    if filter == Filter::Nearest {
+        return pixel(point.0.round() as isize, point.1.round() as isize);
+    }
+
+    let support = filter.support();
+    // far away from the image, checked before the coordinates are cast so they stay in range
+    let (width, height) = (size.0 as f64, size.1 as f64);
+    if !(point.0 + support >= -1.0 && point.1 + support >= -1.0 && point.0 - support <= width && point.1 - support <= height) {
+        return background;
+    }
+    let (min_x, max_x) = ((point.0 - support).floor() as isize + 1, (point.0 + support).ceil() as isize - 1);
+    let (min_y, max_y) = ((point.1 - support).floor() as isize + 1, (point.1 + support).ceil() as isize - 1);
+    let mut result = [0.0; 4];
+    let mut total = 0.0;
+    for y in min_y..=max_y {
+        let weight_y = filter.kernel(point.1 - y as f64);
+        for x in min_x..=max_x {
+            let weight = weight_y * filter.kernel(point.0 - x as f64);
+            let value = pixel(x, y);
+            for channel in 0..4 {
+                result[channel] += value[channel] * weight;
+            }
+            total += weight;
+        }
+    }
+    if total == 0.0 {
+        return background; // no pixel is close enough to have weight
+    }
+    result.map(|channel| channel / total)
+}
+
+impl Image {
+    /// Warps the image with the mapping from the coordinates of the warped image to the coordinates of the source image.
+    /// # Arguments
+    /// * ```size``` - The width and the height of the warped image.
+    /// * ```filter``` - The filter used for interpolation.
+    /// * ```background``` - The color of the pixels not covered by the source image.
+    /// * ```map``` - The mapping, [None] for points which don't come from the source image.
+    /// # Returns
+    /// * [Result] which holds the warped image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height is 0.
+    /// * [Error::WrongColor] - If the color type of the image does not match the color type of the background color.
+    pub(crate) fn warp<F: Fn((f64, f64)) -> Option<(f64, f64)>>(&self, size: (usize, usize), filter: Filter, background: Option<Color>, map: F) -> Result<Image, Error> {
+        if size.0 == 0 || size.1 == 0 {
+            return Err(Error::InvalidSize);
+        }
+        let background = background.or(self.background_color);
+        if let Some(color) = background {
+            if ColorType::from(color) != self.color_type {
+                return Err(Error::WrongColor);
+            }
+        }
+        let background_value = background.map_or([0.0; 4], |color| premultiply(color.to_rgba_f64()));
+
+        let source = self.to_rgba_f64_premultiplied();
+        let mut pixels = Vec::with_capacity(size.0 * size.1);
+        for y in (0..size.1).rev() {
+            for x in 0..size.0 {
+                pixels.push(match map((x as f64, y as f64)) {
+                    Some(point) => sample(&source, (self.width, self.height), point, filter, background_value),
+                    None => background_value,
+                });
+            }
+        }
+
+        let mut image = self.derive_from_rgba_f64_premultiplied(size.0, size.1, &pixels);
+        image.background_color = background;
+        Ok(image)
+    }
+
+    /// Returns the image warped with the affine transform.
+    /// # Arguments
+    /// * ```transform``` - The transform from the coordinates of the image to the coordinates of the warped image.
+    /// * ```size``` - The width and the height of the warped image.
+    /// * ```filter``` - The filter used for interpolation, [Filter::Bilinear] or [Filter::Bicubic] are recommended.
+    /// * ```background``` - The color of the pixels not covered by the image.
+    ///   If [None], the background color of the image is used, if that isn't set, the pixels are transparent (or black).
+    /// # Returns
+    /// * [Result] which holds the warped image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height is 0.
+    /// * [Error::WrongColor] - If the color type of the image does not match the color type of the background color.
+    /// * [Error::InvalidTransform] - If the transform is not invertible.
+    pub fn warp_affine(&self, transform: &Transform, size: (usize, usize), filter: Filter, background: Option<Color>) -> Result<Image, Error> {
+        let inverse = transform.inverse().ok_or(Error::InvalidTransform)?;
+        self.warp(size, filter, background, |point| Some(inverse.apply(point)))
+    }
+
+    /// Returns the image warped with the projective transform, e.g. to map it onto a quadrilateral seen in perspective.
+    /// # Arguments
+    /// * ```homography``` - The homography from the coordinates of the image to the coordinates of the warped image.
+    /// * ```size``` - The width and the height of the warped image.
+    /// * ```filter``` - The filter used for interpolation, [Filter::Bilinear] or [Filter::Bicubic] are recommended.
+    /// * ```background``` - The color of the pixels not covered by the image, see [Image::warp_affine()].
+    /// # Returns
+    /// * [Result] which holds the warped image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidSize] - If the width or the height is 0.
+    /// * [Error::WrongColor] - If the color type of the image does not match the color type of the background color.
+    /// * [Error::InvalidTransform] - If the homography is not invertible.
+    pub fn warp_perspective(&self, homography: &Homography, size: (usize, usize), filter: Filter, background: Option<Color>) -> Result<Image, Error> {
+        let inverse = homography.inverse().ok_or(Error::InvalidTransform)?;
+        self.warp(size, filter, background, |point| inverse.apply(point))
+    }
+
+    /// Returns the image rotated around its center by the angle.
+    /// The warped image is enlarged to hold the whole rotated image, uncovered corners are filled as in [Image::warp_affine()].
+    /// # Arguments
+    /// * ```angle``` - The angle in radians, positive angles rotate counterclockwise.
+    /// * ```filter``` - The filter used for interpolation, [Filter::Bilinear] or [Filter::Bicubic] are recommended.
+    /// # Returns
+    /// * [Result] which holds the rotated image or [Err] with [Error].
+    /// # Errors
+    /// * [Error::InvalidTransform] - If the angle is NaN or infinite.
+    pub fn rotate(&self, angle: f64, filter: Filter) -> Result<Image, Error> {
+        if !angle.is_finite() {
+            return Err(Error::InvalidTransform);
+        }
+        let rotation = Transform::rotation(angle);
+        let (sin, cos) = (rotation.matrix[1][0].abs(), rotation.matrix[0][0].abs());
+        let width = (self.width as f64 * cos + self.height as f64 * sin - 1e-9).ceil().max(1.0);
+        let height = (self.width as f64 * sin + self.height as f64 * cos - 1e-9).ceil().max(1.0);
+        let transform = Transform::translation((-(self.width as f64 - 1.0) / 2.0, -(self.height as f64 - 1.0) / 2.0))
+            .then(&rotation)
+            .then(&Transform::translation(((width - 1.0) / 2.0, (height - 1.0) / 2.0)));
+        self.warp_affine(&transform, (width as usize, height as usize), filter, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn assert_close(point1: (f64, f64), point2: (f64, f64)) {
+        assert!((point1.0 - point2.0).abs() < 1e-9 && (point1.1 - point2.1).abs() < 1e-9, "{:?} != {:?}", point1, point2);
+    }
+
+    #[test]
+    fn test_transform() {
+        assert_eq!(Transform::default().apply((3.0, 4.0)), (3.0, 4.0));
+        assert_eq!(Transform::translation((1.0, -2.0)).apply((3.0, 4.0)), (4.0, 2.0));
+        assert_eq!(Transform::scaling((2.0, 0.5)).apply((3.0, 4.0)), (6.0, 2.0));
+        assert_close(Transform::rotation(FRAC_PI_2).apply((1.0, 0.0)), (0.0, 1.0));
+
+        let transform = Transform::scaling((2.0, 3.0)).then(&Transform::rotation(0.3)).then(&Transform::translation((5.0, 1.0)));
+        let point = (1.5, -2.0);
+        assert_close(transform.apply(point), Transform::translation((5.0, 1.0)).apply(Transform::rotation(0.3).apply((3.0, -6.0))));
+        assert_close(transform.inverse().unwrap().apply(transform.apply(point)), point);
+        assert_eq!(Transform::scaling((1.0, 0.0)).inverse(), None);
+    }
+
+    #[test]
+    fn test_warp_affine() {
+        let mut image = Image::new(4, 3, Color::GRAYA8([10, 255]));
+        image.set((0, 0), Color::GRAYA8([200, 255])).unwrap();
+        image.set((3, 2), Color::GRAYA8([100, 255])).unwrap();
+
+        // test errors
+        assert_eq!(image.warp_affine(&Transform::identity(), (0, 3), Filter::Bilinear, None), Err(Error::InvalidSize));
+        assert_eq!(image.warp_affine(&Transform::identity(), (4, 3), Filter::Bilinear, Some(Color::GRAY8(0))), Err(Error::WrongColor));
+        assert_eq!(image.warp_affine(&Transform::scaling((0.0, 1.0)), (4, 3), Filter::Bilinear, None), Err(Error::InvalidTransform));
+
+        // integer translations move pixels exactly, uncovered pixels get the background
+        for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic, Filter::Lanczos3] {
+            let moved = image.warp_affine(&Transform::translation((1.0, 1.0)), (5, 4), filter, Some(Color::GRAYA8([0, 0]))).unwrap();
+            assert_eq!(moved.get((1, 1)).unwrap(), Color::GRAYA8([200, 255]));
+            assert_eq!(moved.get((4, 3)).unwrap(), Color::GRAYA8([100, 255]));
+            assert_eq!(moved.get((2, 2)).unwrap(), Color::GRAYA8([10, 255]));
+            assert_eq!(moved.get((0, 0)).unwrap(), Color::GRAYA8([0, 0]));
+            assert_eq!(moved.background_color(), Some(Color::GRAYA8([0, 0])));
+        }
+
+        // translations far beyond the range of the coordinates give only the background
+        for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic, Filter::Lanczos3] {
+            for offset in [1e300, -1e300] {
+                let moved = image.warp_affine(&Transform::translation((offset, 0.0)), (4, 4), filter, Some(Color::GRAYA8([0, 0]))).unwrap();
+                assert_eq!(moved, Image::new(4, 4, Color::GRAYA8([0, 0])));
+            }
+        }
+
+        // half pixel translation interpolates, the edge fades into the background
+        let mut image = Image::new(2, 1, Color::GRAYA8([0, 255]));
+        image.set((1, 0), Color::GRAYA8([200, 255])).unwrap();
+        image.background_color = None;
+        let moved = image.warp_affine(&Transform::translation((0.5, 0.0)), (3, 1), Filter::Bilinear, None).unwrap();
+        assert_eq!(moved.as_bytes(), &[0, 128, 100, 255, 200, 128]);
+    }
+
+    #[test]
+    fn test_homography() {
+        let transform = Transform::rotation(0.4).then(&Transform::translation((2.0, -1.0)));
+        let homography = Homography::from(transform);
+        assert_eq!(Homography::default().apply((3.0, 4.0)), Some((3.0, 4.0)));
+        assert_close(homography.apply((1.5, 2.5)).unwrap(), transform.apply((1.5, 2.5)));
+        assert_eq!(Homography::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.0, 0.0]]).apply((0.0, 5.0)), None);
+
+        // four point correspondence
+        let source = [(0.0, 0.0), (9.0, 0.0), (9.0, 9.0), (0.0, 9.0)];
+        let destination = [(1.0, 2.0), (20.0, 0.0), (15.0, 12.0), (3.0, 8.0)];
+        let homography = Homography::from_points(source, destination).unwrap();
+        for (point, expected) in source.into_iter().zip(destination) {
+            assert_close(homography.apply(point).unwrap(), expected);
+        }
+        let inverse = homography.inverse().unwrap();
+        assert_close(homography.then(&inverse).apply((4.0, 7.0)).unwrap(), (4.0, 7.0));
+        let affine = Homography::from_points(source, source.map(|point| transform.apply(point))).unwrap();
+        assert_close(affine.apply((4.0, 7.0)).unwrap(), transform.apply((4.0, 7.0)));
+
+        // three points on a line
+        assert_eq!(Homography::from_points([(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (0.0, 1.0)], destination), None);
+        assert_eq!(Homography::from_points(source, [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (0.0, 1.0)]), None);
+    }
+
+    #[test]
+    fn test_warp_perspective() {
+        let image = Image::new(10, 10, Color::RGBA8([0, 0, 255, 255]));
+        assert_eq!(
+            image.warp_perspective(&Homography::new([[1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, 1.0]]), (10, 10), Filter::Bilinear, None),
+            Err(Error::InvalidTransform)
+        );
+
+        // map the image onto a trapezoid
+        let source = [(0.0, 0.0), (9.0, 0.0), (9.0, 9.0), (0.0, 9.0)];
+        let destination = [(0.0, 0.0), (19.0, 0.0), (14.0, 9.0), (5.0, 9.0)];
+        let homography = Homography::from_points(source, destination).unwrap();
+        let warped = image.warp_perspective(&homography, (20, 10), Filter::Bilinear, Some(Color::RGBA8([0, 0, 0, 0]))).unwrap();
+        assert_eq!(warped.get((10, 5)).unwrap(), Color::RGBA8([0, 0, 255, 255]));
+        assert_eq!(warped.get((1, 0)).unwrap(), Color::RGBA8([0, 0, 255, 255]));
+        assert_eq!(warped.get((1, 9)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+        assert_eq!(warped.get((18, 9)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+
+        // affine homographies warp like the affine transform
+        let transform = Transform::rotation(0.3).then(&Transform::translation((3.0, 1.0)));
+        let affine = image.warp_affine(&transform, (16, 16), Filter::Bicubic, None).unwrap();
+        assert_eq!(image.warp_perspective(&Homography::from(transform), (16, 16), Filter::Bicubic, None).unwrap(), affine);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let mut image = Image::new(3, 2, Color::RGB8([0, 0, 0]));
+        for y in 0..2 {
+            for x in 0..3 {
+                image.set((x, y), Color::RGB8([x as u8 * 100, y as u8 * 100, 50])).unwrap();
+            }
+        }
+
+        // test errors
+        assert_eq!(image.rotate(f64::NAN, Filter::Bilinear), Err(Error::InvalidTransform));
+        assert_eq!(image.rotate(f64::INFINITY, Filter::Nearest), Err(Error::InvalidTransform));
+        assert_eq!(Image::new(0, 3, Color::GRAY8(0)).rotate(0.5, Filter::Bilinear).map(|image| image.width()), Ok(2));
+
+        // quarter turns match the lossless rotations
+        let mut rotated = image.clone();
+        rotated.rotate90();
+        for filter in [Filter::Nearest, Filter::Bilinear, Filter::Bicubic] {
+            assert_eq!(image.rotate(-FRAC_PI_2, filter).unwrap().as_bytes(), rotated.as_bytes());
+        }
+        rotated.rotate180();
+        assert_eq!(image.rotate(FRAC_PI_2, Filter::Bilinear).unwrap().as_bytes(), rotated.as_bytes());
+
+        // other angles enlarge the image and fill the corners
+        let image = Image::new(10, 10, Color::RGBA8([255, 0, 0, 255]));
+        let rotated = image.rotate(FRAC_PI_4, Filter::Bicubic).unwrap();
+        assert_eq!((rotated.width(), rotated.height()), (15, 15));
+        assert_eq!(rotated.get((7, 7)).unwrap(), Color::RGBA8([255, 0, 0, 255]));
+        assert_eq!(rotated.get((0, 0)).unwrap(), Color::RGBA8([255, 0, 0, 255]));
+        let mut image = image;
+        image.background_color = None;
+        let rotated = image.rotate(FRAC_PI_4, Filter::Bicubic).unwrap();
+        assert_eq!(rotated.get((7, 7)).unwrap(), Color::RGBA8([255, 0, 0, 255]));
+        assert_eq!(rotated.get((0, 0)).unwrap(), Color::RGBA8([0, 0, 0, 0]));
+        // the tip of the rotated square is anti-aliased
+        let Color::RGBA8(tip) = rotated.get((7, 0)).unwrap() else { panic!() };
+        assert!(tip[3] > 0 && tip[3] < 255);
+        assert_eq!(&tip[..3], &[255, 0, 0]);
+    }
+}